@@ -8,7 +8,7 @@
 use sdl2::audio::AudioCallback;
 
 use nescore::specs::{Sample, SampleBuffer, APU_OUTPUT_RATE};
-use nescore::utils::sampler::DownSampler;
+use nescore::utils::sampler::Resampler;
 
 use std::collections::VecDeque;
 
@@ -16,14 +16,14 @@ const HOST_AUDIO_RATE: f32 = 44100.0;
 
 pub struct AudioStreamSource {
     queue: VecDeque<Sample>,
-    output_rate: f32,
+    resampler: Resampler,
 }
 
 impl Default for AudioStreamSource {
     fn default() -> Self {
         AudioStreamSource {
             queue: VecDeque::new(),
-            output_rate: HOST_AUDIO_RATE,
+            resampler: Resampler::new(APU_OUTPUT_RATE, HOST_AUDIO_RATE),
         }
     }
 }
@@ -47,8 +47,19 @@ impl AudioCallback for AudioStreamSource {
 
 impl AudioStreamSource {
     pub fn update(&mut self, buffer: SampleBuffer) {
-        for sample in DownSampler::new(buffer, APU_OUTPUT_RATE, self.output_rate).into_iter() {
-            self.queue.push_back(sample);
-        }
+        let mut samples = Vec::new();
+        self.resampler.process(&buffer, &mut samples);
+
+        self.queue.extend(samples);
+    }
+
+    /// Number of samples waiting to be played
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Drop queued samples
+    pub fn clear(&mut self) {
+        self.queue.clear();
     }
 }
@@ -0,0 +1,46 @@
+//
+// disasm.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use clap::Clap;
+use nescore::Cartridge;
+use nescore::asm::{disassemble_rom_with_symbols, linker_config, SymbolTable};
+
+use std::fs;
+
+#[derive(Clap)]
+pub struct Options {
+    /// ROM file
+    rom: String,
+    /// Output file name. Prints to stdout if not specified
+    #[clap(short = 'o', long = "output")]
+    output: Option<String>,
+    /// Symbol files (ca65 .dbg or FCEUX .nl) used to name labels
+    #[clap(short = 'g', long = "symbols")]
+    symbols: Vec<String>,
+    /// Also write an ld65 linker config for reassembling the output
+    #[clap(short = 'c', long = "config")]
+    config: Option<String>,
+}
+
+pub fn dispatch(opts: Options) {
+    let (info, prg_rom, _, _) = Cartridge::from_path(&opts.rom).unwrap().into_parts();
+
+    let mut symbols = SymbolTable::default();
+    for path in &opts.symbols {
+        symbols.merge(SymbolTable::from_path(path).unwrap());
+    }
+
+    let source = disassemble_rom_with_symbols(&info, &prg_rom, &symbols);
+
+    match opts.output {
+        Some(path) => fs::write(path, source).unwrap(),
+        None => print!("{}", source),
+    }
+
+    if let Some(path) = opts.config {
+        fs::write(path, linker_config(&info, &prg_rom)).unwrap();
+    }
+}
@@ -12,6 +12,10 @@ pub mod img;
 pub mod apu;
 pub mod audio;
 pub mod perf;
+pub mod disasm;
+pub mod palette;
+pub mod nsf;
+pub mod render_audio;
 
 use clap::Clap;
 
@@ -35,6 +39,18 @@ pub enum Command {
     /// Do nothing but run the emulator
     #[clap(name = "perf", version = "1.0", author = "Natesh Narain")]
     Perf(perf::Options),
+    /// Disassemble the PRG ROM to ca65 source
+    #[clap(name = "disasm", version = "1.0", author = "Natesh Narain")]
+    Disasm(disasm::Options),
+    /// Generate an NTSC palette file
+    #[clap(name = "palette", version = "1.0", author = "Natesh Narain")]
+    Palette(palette::Options),
+    /// Play an NSF or NSFe music file
+    #[clap(name = "nsf", version = "1.0", author = "Natesh Narain")]
+    Nsf(nsf::Options),
+    /// Render ROM or NSF audio to a WAV file
+    #[clap(name = "render-audio", version = "1.0", author = "Natesh Narain")]
+    RenderAudio(render_audio::Options),
 }
 
 #[derive(Clap)]
@@ -11,11 +11,15 @@ fn main() {
     let opts = Options::parse();
 
     match opts.cmd {
-        Command::Run(opts)   => nescli::run::dispatch(opts),
-        Command::Info(opts)  => nescli::info::dispatch(opts),
-        Command::Img(opts)   => nescli::img::dispatch(opts),
-        Command::Apu(opts)   => nescli::apu::dispatch(opts),
-        Command::Audio(opts) => nescli::audio::dispatch(opts),
-        Command::Perf(opts)  => nescli::perf::dispatch(opts),
+        Command::Run(opts)     => nescli::run::dispatch(opts),
+        Command::Info(opts)    => nescli::info::dispatch(opts),
+        Command::Img(opts)     => nescli::img::dispatch(opts),
+        Command::Apu(opts)     => nescli::apu::dispatch(opts),
+        Command::Audio(opts)   => nescli::audio::dispatch(opts),
+        Command::Perf(opts)    => nescli::perf::dispatch(opts),
+        Command::Disasm(opts)  => nescli::disasm::dispatch(opts),
+        Command::Palette(opts) => nescli::palette::dispatch(opts),
+        Command::Nsf(opts)     => nescli::nsf::dispatch(opts),
+        Command::RenderAudio(opts) => nescli::render_audio::dispatch(opts),
     }
 }
@@ -0,0 +1,106 @@
+//
+// nsf.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+
+use clap::Clap;
+
+use sdl2::audio::AudioSpecDesired;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use nescore::{Nes, Nsf};
+
+use crate::common::audio::AudioStreamSource;
+
+use std::time::Duration;
+
+/// APU samples generated between checks of the audio queue
+const CHUNK_SIZE: usize = 4096;
+/// Host samples to keep queued for playback (~100 ms)
+const QUEUE_TARGET: usize = 4410;
+
+#[derive(Clap)]
+pub struct Options {
+    /// NSF or NSFe file
+    file: String,
+    /// Track to play, starting from 1. Defaults to the file's starting track
+    #[clap(short = 't', long = "track")]
+    track: Option<u8>,
+}
+
+pub fn dispatch(opts: Options) {
+    let nsf = Nsf::from_path(&opts.file).unwrap();
+    println!("{}", nsf);
+
+    if nsf.chips.fds || nsf.chips.n163 {
+        eprintln!("Warning: FDS and Namco 163 sound are not supported. Those parts of the tune will be silent");
+    }
+
+    let mut track = opts.track.map(|track| track.saturating_sub(1)).unwrap_or(nsf.start_track);
+    if track >= nsf.num_tracks {
+        eprintln!("Track {} is out of range. The file has {} tracks", track + 1, nsf.num_tracks);
+        return;
+    }
+
+    // Open an SDL window to receive key presses
+    let sdl_context = sdl2::init().unwrap();
+    let video_subsystem = sdl_context.video().unwrap();
+    let audio_subsystem = sdl_context.audio().unwrap();
+
+    let _window = video_subsystem.window("nsf", 250, 250)
+                                .position_centered()
+                                .opengl()
+                                .build()
+                                .unwrap();
+
+    // Audio
+    let audio_spec = AudioSpecDesired {
+        freq: Some(44_100),
+        channels: Some(1),
+        samples: None,
+    };
+
+    let mut audio_device = audio_subsystem.open_playback(None, &audio_spec, |_|{
+        AudioStreamSource::default()
+    }).unwrap();
+    audio_device.resume();
+
+    // Event handling
+    let mut event_pump = sdl_context.event_pump().unwrap();
+
+    let mut nes = Nes::default().with_nsf(&nsf);
+    nes.select_track(track);
+    println!("Playing {}/{}: {}", track + 1, nsf.num_tracks, nsf.track_label(track));
+
+    'running: loop {
+        // Left and right arrow keys change track
+        for event in event_pump.poll_iter() {
+            let next = match event {
+                Event::Quit {..} | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                    break 'running
+                },
+                Event::KeyDown { keycode: Some(Keycode::Right), .. } => (track + 1) % nsf.num_tracks,
+                Event::KeyDown { keycode: Some(Keycode::Left), .. } => (track + nsf.num_tracks - 1) % nsf.num_tracks,
+                _ => continue,
+            };
+
+            track = next;
+            nes.select_track(track);
+            audio_device.lock().clear();
+
+            println!("Playing {}/{}: {}", track + 1, nsf.num_tracks, nsf.track_label(track));
+        }
+
+        // Generate audio until enough is queued for playback
+        if audio_device.lock().queued() < QUEUE_TARGET {
+            let buffer = nes.run_audio(CHUNK_SIZE);
+            audio_device.lock().update(buffer);
+        }
+        else {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
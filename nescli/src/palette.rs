@@ -0,0 +1,44 @@
+//
+// palette.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use clap::Clap;
+use nescore::utils::palette::PaletteGenerator;
+
+use std::fs;
+
+#[derive(Clap)]
+pub struct Options {
+    /// Output file name
+    #[clap(short = 'o', long = "output", default_value = "palette.pal")]
+    output: String,
+    /// Hue rotation in degrees
+    #[clap(long = "hue", default_value = "0")]
+    hue: f64,
+    /// Saturation
+    #[clap(long = "saturation", default_value = "1")]
+    saturation: f64,
+    /// Contrast
+    #[clap(long = "contrast", default_value = "1")]
+    contrast: f64,
+    /// Brightness
+    #[clap(long = "brightness", default_value = "0")]
+    brightness: f64,
+    /// Gamma
+    #[clap(long = "gamma", default_value = "1.8")]
+    gamma: f64,
+}
+
+pub fn dispatch(opts: Options) {
+    let palette = PaletteGenerator::default()
+                    .hue(opts.hue)
+                    .saturation(opts.saturation)
+                    .contrast(opts.contrast)
+                    .brightness(opts.brightness)
+                    .gamma(opts.gamma)
+                    .generate();
+
+    fs::write(&opts.output, palette.to_bytes()).unwrap();
+}
@@ -0,0 +1,219 @@
+//
+// render_audio.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use clap::Clap;
+
+use nescore::{Nes, Cartridge, Nsf, NsfError, Button, Controller};
+use nescore::specs::AudioChannel;
+use nescore::utils::wav::WavWriter;
+
+use std::fs;
+use std::path::Path;
+
+/// NTSC frame rate
+const FRAME_RATE: f64 = 60.0988;
+
+#[derive(Clap)]
+pub struct Options {
+    /// ROM, NSF or NSFe file
+    file: String,
+    /// Output WAV file
+    #[clap(short = 'o', long = "output", default_value = "audio.wav")]
+    output: String,
+    /// Length of the render in seconds
+    #[clap(short = 's', long = "seconds", default_value = "30")]
+    seconds: f64,
+    /// Length of the render in frames. Overrides `--seconds`
+    #[clap(short = 'f', long = "frames")]
+    frames: Option<usize>,
+    /// NSF track to render, starting from 1. Defaults to the file's starting track
+    #[clap(short = 't', long = "track")]
+    track: Option<u8>,
+    /// Input script. Each line is `<frame> <controller 1 buttons> [<controller 2 buttons>]`, with buttons joined by
+    /// `+` (e.g. `120 Start`, `300 Right+A`) and `-` for no buttons. Buttons are held until the next line
+    #[clap(short = 'i', long = "input")]
+    input: Option<String>,
+    /// Output sample rate
+    #[clap(short = 'r', long = "rate", default_value = "44100")]
+    rate: u32,
+    /// Also write a WAV file for each channel, next to the output
+    #[clap(long = "split")]
+    split: bool,
+    /// Also log sound register writes to a VGM file
+    #[clap(long = "vgm")]
+    vgm: Option<String>,
+}
+
+/// Controller state from a given frame
+struct InputEvent {
+    frame: usize,
+    buttons: [u8; 2],
+}
+
+pub fn dispatch(opts: Options) {
+    let source = load_source(&opts.file);
+
+    if let (Source::Nsf(nsf), Some(track)) = (&source, opts.track) {
+        let track = track.saturating_sub(1);
+        if track >= nsf.num_tracks {
+            eprintln!("Track {} is out of range. The file has {} tracks", track + 1, nsf.num_tracks);
+            return;
+        }
+    }
+    let script = opts.input.as_ref().map(|path| load_script(path)).unwrap_or_default();
+    let frames = opts.frames.unwrap_or((opts.seconds * FRAME_RATE).ceil() as usize);
+
+    render(&opts, &source, &script, frames, None, &opts.output, opts.vgm.as_deref());
+
+    if opts.split {
+        // The emulator is deterministic, so each channel is rendered by replaying with the channel soloed
+        for &channel in AudioChannel::ALL.iter() {
+            let path = channel_path(&opts.output, channel);
+            render(&opts, &source, &script, frames, Some(channel), &path, None);
+        }
+    }
+}
+
+/// Audio source to render
+enum Source {
+    Rom(Vec<u8>),
+    Nsf(Nsf),
+}
+
+fn load_source(path: &str) -> Source {
+    match Nsf::from_path(path) {
+        Ok(nsf) => {
+            if nsf.chips.fds || nsf.chips.n163 {
+                eprintln!("Warning: FDS and Namco 163 sound are not supported. Those parts of the tune will be silent");
+            }
+
+            Source::Nsf(nsf)
+        },
+        Err(NsfError::InvalidSig) => Source::Rom(fs::read(path).unwrap()),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render(opts: &Options, source: &Source, script: &[InputEvent], frames: usize, solo: Option<AudioChannel>, path: &str, vgm: Option<&str>) {
+    let mut nes = match source {
+        Source::Rom(rom) => Nes::from(Cartridge::from_slice(rom).unwrap()),
+        Source::Nsf(nsf) => {
+            let mut nes = Nes::default().with_nsf(nsf);
+            nes.select_track(opts.track.map(|track| track.saturating_sub(1)).unwrap_or(nsf.start_track));
+            nes
+        },
+    };
+
+    if let Some(channel) = solo {
+        nes.set_channel_solo(channel, true);
+    }
+
+    if vgm.is_some() {
+        nes.start_audio_log();
+    }
+
+    let mut wav = WavWriter::create(path, opts.rate).unwrap();
+    let mut events = script.iter().peekable();
+
+    for frame in 0..frames {
+        while let Some(event) = events.next_if(|event| event.frame <= frame) {
+            apply_input(&mut nes, event);
+        }
+
+        let (_, samples) = nes.emulate_frame();
+        wav.write(&samples).unwrap();
+    }
+
+    let num_samples = wav.num_samples();
+    wav.finish().unwrap();
+
+    println!("{}: {} samples", path, num_samples);
+
+    if let (Some(vgm), Some(log)) = (vgm, nes.stop_audio_log()) {
+        fs::write(vgm, log.to_vgm()).unwrap();
+        println!("{}: {} register writes", vgm, log.entries().len());
+    }
+}
+
+fn apply_input(nes: &mut Nes, event: &InputEvent) {
+    for (i, &buttons) in event.buttons.iter().enumerate() {
+        for bit in 0..8 {
+            let controller = if i == 0 { Controller::Input1 } else { Controller::Input2 };
+            nes.controller_input(controller, button(bit), buttons & (1 << bit) != 0);
+        }
+    }
+}
+
+fn load_script(path: &str) -> Vec<InputEvent> {
+    let script = fs::read_to_string(path).unwrap();
+
+    let mut events: Vec<InputEvent> = script.lines().enumerate().filter_map(|(n, line)| {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            return None;
+        }
+
+        let mut fields = line.split_whitespace();
+        let frame = fields.next().unwrap().parse::<usize>()
+            .unwrap_or_else(|_| panic!("Line {}: Invalid frame number", n + 1));
+
+        let mut buttons = [0u8; 2];
+        for (controller, field) in fields.take(2).enumerate() {
+            buttons[controller] = parse_buttons(field).unwrap_or_else(|e| panic!("Line {}: {}", n + 1, e));
+        }
+
+        Some(InputEvent { frame, buttons })
+    }).collect();
+
+    events.sort_by_key(|event| event.frame);
+
+    events
+}
+
+fn parse_buttons(field: &str) -> Result<u8, String> {
+    if field == "-" {
+        return Ok(0);
+    }
+
+    field.split('+').try_fold(0u8, |mask, name| {
+        let bit = match name.to_lowercase().as_str() {
+            "a"      => 0,
+            "b"      => 1,
+            "select" => 2,
+            "start"  => 3,
+            "up"     => 4,
+            "down"   => 5,
+            "left"   => 6,
+            "right"  => 7,
+            _ => return Err(format!("Unknown button `{}`", name)),
+        };
+
+        Ok(mask | (1 << bit))
+    })
+}
+
+fn button(bit: u8) -> Button {
+    match bit {
+        0 => Button::A,
+        1 => Button::B,
+        2 => Button::Select,
+        3 => Button::Start,
+        4 => Button::Up,
+        5 => Button::Down,
+        6 => Button::Left,
+        _ => Button::Right,
+    }
+}
+
+/// `out.wav` becomes `out_pulse1.wav`
+fn channel_path(output: &str, channel: AudioChannel) -> String {
+    let path = Path::new(output);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("audio");
+    let name = format!("{}_{}.wav", stem, format!("{:?}", channel).to_lowercase());
+
+    path.with_file_name(name).to_string_lossy().to_string()
+}
@@ -13,8 +13,10 @@ use sdl2::rect::Rect;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 
-use nescore::{Nes, CartridgeLoader, Button};
-use nescore::specs::{DISPLAY_WIDTH, DISPLAY_HEIGHT};
+use nescore::{Nes, CartridgeLoader, Button, Palette};
+use nescore::asm::SymbolTable;
+use nescore::specs::{DISPLAY_WIDTH, DISPLAY_HEIGHT, PixelFormat};
+use nescore::utils::ntsc::{NtscFilter, NtscSetup, NTSC_OUTPUT_WIDTH};
 
 use std::io::prelude::*;
 use std::fs::File;
@@ -34,6 +36,18 @@ pub struct Options {
     /// Enable saves
     #[clap(short = 's')]
     pub save: bool,
+    /// Symbol files (ca65 .dbg or FCEUX .nl) used to label the debug log
+    #[clap(short = 'g', long = "symbols")]
+    pub symbols: Vec<String>,
+    /// Palette file (64 or 512 entry .pal)
+    #[clap(short = 'p', long = "palette")]
+    pub palette: Option<String>,
+    /// Render more than 8 sprites per scanline
+    #[clap(long = "no-sprite-limit")]
+    pub no_sprite_limit: bool,
+    /// Simulate NTSC video output
+    #[clap(long = "ntsc", possible_values = &["composite", "svideo", "rgb"])]
+    pub ntsc: Option<String>,
     /// The ROM file to run
     pub rom: String,
 }
@@ -45,15 +59,48 @@ pub fn dispatch(opts: Options) {
                         .rom_path(&opts.rom)
                         .save_path(&save_file_path)
                         .load()
-                        .map(|cart| Nes::from(cart).debug_mode(opts.debug))
+                        .map(|cart| Nes::from(cart).debug_mode(opts.debug).sprite_limit(!opts.no_sprite_limit))
                         .unwrap();
 
+    if let Some(ref path) = opts.palette {
+        nes.set_palette(Palette::from_path(path).unwrap());
+    }
+
+    // The NTSC filter decodes the raw PPU output itself
+    let mut ntsc_filter = opts.ntsc.as_deref().map(|signal| {
+        let setup = match signal {
+            "svideo" => NtscSetup::svideo(),
+            "rgb" => NtscSetup::rgb(),
+            _ => NtscSetup::composite(),
+        };
+
+        NtscFilter::new(setup)
+    });
+
+    let display_width = if ntsc_filter.is_some() { NTSC_OUTPUT_WIDTH } else { DISPLAY_WIDTH };
+    let mut filter_buffer = vec![0u8; display_width * DISPLAY_HEIGHT * 3];
+    let mut indexed_buffer = vec![0u8; DISPLAY_WIDTH * DISPLAY_HEIGHT * 2];
+
+    if ntsc_filter.is_some() {
+        nes = nes.pixel_format(PixelFormat::Indexed);
+    }
+
+    let mut symbols = SymbolTable::default();
+    for path in &opts.symbols {
+        symbols.merge(SymbolTable::from_path(path).unwrap());
+    }
+
     // Setup console logger
     let cpu_events = nes.cpu_event_channel();
 
     thread::spawn(move || {
         while let Ok(event) = cpu_events.recv() {
-            nescore::log::console(event);
+            if symbols.is_empty() {
+                nescore::log::console(event);
+            }
+            else {
+                nescore::log::console_with_symbols(event, &symbols);
+            }
         }
     });
 
@@ -71,7 +118,7 @@ pub fn dispatch(opts: Options) {
     let texture_creator = canvas.texture_creator();
 
     let mut display = texture_creator.create_texture_streaming(PixelFormatEnum::RGB24,
-                                                               DISPLAY_WIDTH as u32,
+                                                               display_width as u32,
                                                                DISPLAY_HEIGHT as u32).unwrap();
 
     let desired_spec = AudioSpecDesired {
@@ -90,6 +137,10 @@ pub fn dispatch(opts: Options) {
 
     let mut event_pump = sdl_context.event_pump().unwrap();
 
+    // Layer toggles (F1: Background, F2: Sprites)
+    let mut background_visible = true;
+    let mut sprites_visible = true;
+
     'running: loop {
         let instant = Instant::now();
 
@@ -98,6 +149,14 @@ pub fn dispatch(opts: Options) {
                 Event::Quit {..} | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     break 'running
                 },
+                Event::KeyDown { keycode: Some(Keycode::F1), .. } => {
+                    background_visible = !background_visible;
+                    nes.set_background_visible(background_visible);
+                },
+                Event::KeyDown { keycode: Some(Keycode::F2), .. } => {
+                    sprites_visible = !sprites_visible;
+                    nes.set_sprites_visible(sprites_visible);
+                },
                 Event::KeyDown {keycode, ..} => {
                     let btn = keycode.map(map_nes_key).flatten();
                     if let Some(btn) = btn {
@@ -115,7 +174,15 @@ pub fn dispatch(opts: Options) {
         }
 
         // Run the nescore for a single frame
-        let (framebuffer, samplebuffer) = nes.emulate_frame();
+        let (framebuffer, samplebuffer) = if let Some(ref mut filter) = ntsc_filter {
+            let samplebuffer = nes.emulate_frame_into(&mut indexed_buffer, DISPLAY_WIDTH * 2);
+            filter.filter_frame(&indexed_buffer, nes.short_frame(), &mut filter_buffer, display_width * 3);
+
+            (&filter_buffer[..], samplebuffer)
+        }
+        else {
+            nes.emulate_frame()
+        };
 
         {
             // update audio stream
@@ -127,7 +194,7 @@ pub fn dispatch(opts: Options) {
         canvas.clear();
 
         // Update the on screen texture
-        display.update(None, framebuffer, DISPLAY_WIDTH * 3).unwrap();
+        display.update(None, framebuffer, display_width * 3).unwrap();
         // Update the canvas
         canvas.copy(&display, None, Some(Rect::new(0, 0, WINDOW_WIDTH, WINDOW_HEIGHT))).unwrap();
 
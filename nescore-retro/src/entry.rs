@@ -0,0 +1,195 @@
+//
+// entry.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+
+// libretro entry points. These match the ones generated by `libretro_core!`, except `retro_set_environment` also keeps
+// the environment callback so the core can register and read its options.
+
+use libretro_backend::{Retro, construct, libc, libretro_sys};
+use libretro_backend::libretro_sys::{EnvironmentFn, Variable, ENVIRONMENT_SET_VARIABLES, ENVIRONMENT_GET_VARIABLE};
+
+use std::ffi::CStr;
+use std::ptr;
+
+use super::NescoreRetro;
+
+/// Core option selecting the NTSC filter
+pub const NTSC_FILTER_KEY: &[u8] = b"nescore_ntsc\0";
+const NTSC_FILTER_OPTION: &[u8] = b"NTSC filter (restart); disabled|composite|svideo|rgb\0";
+
+static mut ENVIRONMENT: Option<EnvironmentFn> = None;
+static mut INSTANCE: *mut Retro<NescoreRetro> = ptr::null_mut();
+
+/// Read the value of a core option
+pub fn get_variable(key: &[u8]) -> Option<String> {
+    let environment = unsafe { ENVIRONMENT }?;
+
+    let mut variable = Variable {
+        key: key.as_ptr() as *const libc::c_char,
+        value: ptr::null(),
+    };
+
+    unsafe {
+        if environment(ENVIRONMENT_GET_VARIABLE, &mut variable as *mut Variable as *mut libc::c_void) && !variable.value.is_null() {
+            Some(CStr::from_ptr(variable.value).to_string_lossy().into_owned())
+        }
+        else {
+            None
+        }
+    }
+}
+
+/// Register the core options with the frontend
+unsafe fn set_variables(environment: EnvironmentFn) {
+    let variables = [
+        Variable {
+            key: NTSC_FILTER_KEY.as_ptr() as *const libc::c_char,
+            value: NTSC_FILTER_OPTION.as_ptr() as *const libc::c_char,
+        },
+        Variable {
+            key: ptr::null(),
+            value: ptr::null(),
+        },
+    ];
+
+    environment(ENVIRONMENT_SET_VARIABLES, variables.as_ptr() as *mut libc::c_void);
+}
+
+unsafe fn instance() -> &'static mut Retro<NescoreRetro> {
+    assert!(!INSTANCE.is_null());
+    &mut *INSTANCE
+}
+
+#[no_mangle]
+pub extern "C" fn retro_api_version() -> libc::c_uint {
+    libretro_sys::API_VERSION
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_init() {
+    assert!(INSTANCE.is_null());
+    INSTANCE = Box::into_raw(Box::new(construct::<NescoreRetro>()));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_deinit() {
+    assert!(!INSTANCE.is_null());
+    drop(Box::from_raw(INSTANCE));
+    INSTANCE = ptr::null_mut();
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_set_environment(callback: EnvironmentFn) {
+    ENVIRONMENT = Some(callback);
+    set_variables(callback);
+
+    Retro::<NescoreRetro>::on_set_environment(callback)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_set_video_refresh(callback: libretro_sys::VideoRefreshFn) {
+    instance().on_set_video_refresh(callback)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_set_audio_sample(callback: libretro_sys::AudioSampleFn) {
+    instance().on_set_audio_sample(callback)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_set_audio_sample_batch(callback: libretro_sys::AudioSampleBatchFn) {
+    instance().on_set_audio_sample_batch(callback)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_set_input_poll(callback: libretro_sys::InputPollFn) {
+    instance().on_set_input_poll(callback)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_set_input_state(callback: libretro_sys::InputStateFn) {
+    instance().on_set_input_state(callback)
+}
+
+#[no_mangle]
+pub extern "C" fn retro_get_system_info(info: *mut libretro_sys::SystemInfo) {
+    Retro::<NescoreRetro>::on_get_system_info(info)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_system_av_info(info: *mut libretro_sys::SystemAvInfo) {
+    instance().on_get_system_av_info(info)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_set_controller_port_device(port: libc::c_uint, device: libc::c_uint) {
+    instance().on_set_controller_port_device(port, device)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_reset() {
+    instance().on_reset()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_run() {
+    instance().on_run()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_serialize_size() -> libc::size_t {
+    instance().on_serialize_size()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_serialize(data: *mut libc::c_void, size: libc::size_t) -> bool {
+    instance().on_serialize(data, size)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_unserialize(data: *const libc::c_void, size: libc::size_t) -> bool {
+    instance().on_unserialize(data, size)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_cheat_reset() {
+    instance().on_cheat_reset()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_cheat_set(index: libc::c_uint, is_enabled: bool, code: *const libc::c_char) {
+    instance().on_cheat_set(index, is_enabled, code)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_load_game(game: *const libretro_sys::GameInfo) -> bool {
+    instance().on_load_game(game)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_load_game_special(game_type: libc::c_uint, info: *const libretro_sys::GameInfo, num_info: libc::size_t) -> bool {
+    instance().on_load_game_special(game_type, info, num_info)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_unload_game() {
+    instance().on_unload_game()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_region() -> libc::c_uint {
+    instance().on_get_region()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_memory_data(id: libc::c_uint) -> *mut libc::c_void {
+    instance().on_get_memory_data(id)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_memory_size(id: libc::c_uint) -> libc::size_t {
+    instance().on_get_memory_size(id)
+}
@@ -1,29 +1,51 @@
 use nescore::{Nes, Cartridge, Button,
     specs::{DISPLAY_HEIGHT, DISPLAY_WIDTH, APU_OUTPUT_RATE, PixelFormat as NesCorePixelFormat},
-    utils::sampler::DownSampler
+    utils::sampler::Resampler,
+    utils::ntsc::{NtscFilter, NtscSetup, NTSC_OUTPUT_WIDTH},
 };
 use libretro_backend::{
     AudioVideoInfo, Core, CoreInfo, GameData, LoadGameResult,
     PixelFormat, Region, RuntimeHandle, JoypadButton,
-    libretro_core
 };
 
+mod entry;
+
 const HOST_PLAYBACK_RATE: f64 = 44100.0;
 
 struct NescoreRetro {
     core: Nes,
     game_data: Option<GameData>,
+    ntsc_filter: Option<NtscFilter>,
+    indexed_buffer: Vec<u8>,
+    filter_buffer: Vec<u8>,
+    resampler: Resampler,
 }
 
 impl Default for NescoreRetro {
     fn default() -> Self {
         NescoreRetro {
-            core: Nes::default().pixel_format(NesCorePixelFormat::BGRA8),
+            core: Nes::default().pixel_format(NesCorePixelFormat::XRGB8888),
             game_data: None,
+            ntsc_filter: None,
+            indexed_buffer: vec![0; DISPLAY_WIDTH * DISPLAY_HEIGHT * 2],
+            filter_buffer: vec![0; NTSC_OUTPUT_WIDTH * DISPLAY_HEIGHT * 4],
+            resampler: Resampler::new(APU_OUTPUT_RATE, HOST_PLAYBACK_RATE as f32),
         }
     }
 }
 
+/// Create the NTSC filter selected by the core option
+fn ntsc_filter() -> Option<NtscFilter> {
+    let setup = match entry::get_variable(entry::NTSC_FILTER_KEY)?.as_str() {
+        "composite" => NtscSetup::composite(),
+        "svideo" => NtscSetup::svideo(),
+        "rgb" => NtscSetup::rgb(),
+        _ => return None,
+    };
+
+    Some(NtscFilter::new(setup).pixel_format(NesCorePixelFormat::XRGB8888))
+}
+
 impl Core for NescoreRetro {
     fn info() -> CoreInfo {
         CoreInfo::new("nescore", env!("CARGO_PKG_VERSION"))
@@ -48,13 +70,21 @@ impl Core for NescoreRetro {
             match cart {
                 Ok(cart) => {
                     self.game_data = Some(game_data);
+
+                    // The NTSC filter decodes the raw PPU output itself
+                    self.ntsc_filter = ntsc_filter();
+                    let pixel_format = if self.ntsc_filter.is_some() { NesCorePixelFormat::Indexed } else { NesCorePixelFormat::XRGB8888 };
+                    self.core = Nes::default().pixel_format(pixel_format);
+
                     let tv_system = if cart.info.tv_system_pal { Region::PAL } else { Region::NTSC };
                     // FIXME: This will panic on invalid cartridge type
                     self.core.insert(cart);
 
+                    let width = if self.ntsc_filter.is_some() { NTSC_OUTPUT_WIDTH } else { DISPLAY_WIDTH };
+
                     LoadGameResult::Success(
                         AudioVideoInfo::new()
-                            .video(DISPLAY_WIDTH as u32, DISPLAY_HEIGHT as u32, 60.0, PixelFormat::ARGB8888)
+                            .video(width as u32, DISPLAY_HEIGHT as u32, 60.0, PixelFormat::ARGB8888)
                             .audio(HOST_PLAYBACK_RATE)
                             .region(tv_system)
                     )
@@ -80,23 +110,28 @@ impl Core for NescoreRetro {
         }
 
         // Run for a full frame
-        let (framebuffer, audiobuffer) = self.core.emulate_frame();
-        handle.upload_video_frame(framebuffer);
+        let audiobuffer = if let Some(ref mut filter) = self.ntsc_filter {
+            let audiobuffer = self.core.emulate_frame_into(&mut self.indexed_buffer, DISPLAY_WIDTH * 2);
+            filter.filter_frame(&self.indexed_buffer, self.core.short_frame(), &mut self.filter_buffer, NTSC_OUTPUT_WIDTH * 4);
+            handle.upload_video_frame(&self.filter_buffer);
+
+            audiobuffer
+        }
+        else {
+            let (framebuffer, audiobuffer) = self.core.emulate_frame();
+            handle.upload_video_frame(framebuffer);
+
+            audiobuffer
+        };
 
         // process audio to match host system and libretro api
-        // downsample apu output
-        // convert to i16 data type
+        // resample apu output to i16 at the host rate
         // convert to stereo
         let audiobuffer: Vec<i16> = {
-            let mut buf: Vec<i16> = Vec::new();
-            for sample in DownSampler::new(audiobuffer, APU_OUTPUT_RATE, HOST_PLAYBACK_RATE as f32)
-                                        .into_iter()
-                                        .map(|sample| sample as i16) {
-                buf.push(sample);
-                buf.push(sample);
-            }
+            let mut samples: Vec<i16> = Vec::new();
+            self.resampler.process_i16(&audiobuffer, &mut samples);
 
-            buf
+            samples.into_iter().flat_map(|sample| vec![sample, sample]).collect()
         };
         handle.upload_audio_frame(audiobuffer.as_slice());
     }
@@ -122,5 +157,3 @@ fn map_joypad(button: JoypadButton) -> Result<Button, ()> {
         _ => Err(()),
     }
 }
-
-libretro_core!(NescoreRetro);
@@ -6,8 +6,9 @@
 //
 use super::seq::{FrameSequencer, Event};
 use super::chnl::{SoundChannel, Pulse, Triangle, Noise, Dmc, LengthCounterUnit, EnvelopeUnit, NegateAddMode};
+use super::filter::{AudioFilter, FilterChain};
 
-use crate::common::{IoAccess, IoAccessRef, Clockable, Register, Interrupt};
+use crate::common::{IoAccess, IoAccessRef, Clockable, Register};
 
 pub type Sample = f32;
 pub const APU_OUTPUT_RATE: f32 = 895_000.0;
@@ -24,10 +25,55 @@ pub mod events {
         pub triangle: f32,
         pub noise: f32,
         pub dmc: f32,
+        pub expansion: f32,
         pub mixer: f32,
     }
 }
 
+/// APU sound channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioChannel {
+    Pulse1,
+    Pulse2,
+    Triangle,
+    Noise,
+    Dmc,
+    /// Cartridge sound chip
+    Expansion,
+}
+
+const NUM_CHANNELS: usize = 6;
+
+impl AudioChannel {
+    /// All channels, in mixer order
+    pub const ALL: [AudioChannel; NUM_CHANNELS] = [
+        AudioChannel::Pulse1,
+        AudioChannel::Pulse2,
+        AudioChannel::Triangle,
+        AudioChannel::Noise,
+        AudioChannel::Dmc,
+        AudioChannel::Expansion,
+    ];
+}
+
+/// Per-channel gain controls applied before mixing
+#[derive(Debug, Clone, Copy)]
+struct ChannelControl {
+    volume: f32,
+    muted: bool,
+    solo: bool,
+}
+
+impl Default for ChannelControl {
+    fn default() -> Self {
+        ChannelControl {
+            volume: 1.0,
+            muted: false,
+            solo: false,
+        }
+    }
+}
+
 /// NES APU
 pub struct Apu {
     pulse1: Pulse,
@@ -41,6 +87,13 @@ pub struct Apu {
     pulse_table: [f32; 31],
     tnd_table: [f32; 203],
 
+    filter: FilterChain,
+
+    controls: [ChannelControl; NUM_CHANNELS],
+
+    expansion: Sample,    // Output of the cartridge sound chip
+    expansion_level: f32, // Level of the cartridge sound chip relative to the APU
+
     bus: Option<IoAccessRef>,
 
     // Event logging
@@ -78,6 +131,13 @@ impl Default for Apu {
             pulse_table,
             tnd_table,
 
+            filter: FilterChain::default(),
+
+            controls: [ChannelControl::default(); NUM_CHANNELS],
+
+            expansion: 0.0,
+            expansion_level: 1.0,
+
             bus: None,
 
             #[cfg(feature="events")]
@@ -99,12 +159,8 @@ impl Clockable<Sample> for Apu {
                     self.clock_length();
                     self.clock_sweep();
                 },
-                Event::Irq => {
-                    if let Some(ref mut bus) = self.bus {
-                        bus.borrow_mut().raise_interrupt(Interrupt::Irq);
-                    }
-                },
-                Event::None => {}
+                // The frame IRQ flag drives the IRQ line. See `Apu::irq`
+                Event::Irq | Event::None => {}
             }
         }
 
@@ -122,8 +178,9 @@ impl Clockable<Sample> for Apu {
         // Clock DMC
         self.dmc.tick();
 
-        // Mix channel outputs into final sample
-        self.mix()
+        // Mix channel outputs and apply the analogue output stage
+        let mixed = self.mix();
+        self.filter.process(mixed)
     }
 }
 
@@ -179,11 +236,18 @@ impl Apu {
         let noise = self.noise.output() as f32;
         let dmc = self.dmc.output() as f32;
 
-        let pulse_out = self.pulse_table[(pulse1 + pulse2) as usize];
+        let gain = |channel| self.gain(channel);
 
-        let tnd_out = self.tnd_table[(3.0 * triangle + 2.0 * noise + dmc) as usize];
+        let pulse_out = helpers::lookup(&self.pulse_table, pulse1 * gain(AudioChannel::Pulse1) + pulse2 * gain(AudioChannel::Pulse2));
 
-        let mixed = pulse_out + tnd_out;
+        let tnd_out = helpers::lookup(
+            &self.tnd_table,
+            3.0 * triangle * gain(AudioChannel::Triangle) + 2.0 * noise * gain(AudioChannel::Noise) + dmc * gain(AudioChannel::Dmc)
+        );
+
+        let expansion = self.expansion * self.expansion_level * gain(AudioChannel::Expansion);
+
+        let mixed = pulse_out + tnd_out + expansion;
 
         #[cfg(feature="events")]
         {
@@ -193,6 +257,7 @@ impl Apu {
                 triangle,
                 noise,
                 dmc,
+                expansion,
                 mixer: mixed,
             };
 
@@ -204,6 +269,49 @@ impl Apu {
         mixed
     }
 
+    /// Gain of a channel after applying mute, solo and volume
+    fn gain(&self, channel: AudioChannel) -> f32 {
+        let control = &self.controls[channel as usize];
+        let solo_active = self.controls.iter().any(|c| c.solo);
+
+        if control.muted || (solo_active && !control.solo) {
+            0.0
+        }
+        else {
+            control.volume
+        }
+    }
+
+    /// Mute or unmute a channel
+    pub fn set_channel_muted(&mut self, channel: AudioChannel, muted: bool) {
+        self.controls[channel as usize].muted = muted;
+    }
+
+    /// Solo a channel. When any channel is soloed, only soloed channels are heard
+    pub fn set_channel_solo(&mut self, channel: AudioChannel, solo: bool) {
+        self.controls[channel as usize].solo = solo;
+    }
+
+    /// Scale the output of a channel. 1.0 is the channel's normal level
+    pub fn set_channel_volume(&mut self, channel: AudioChannel, volume: f32) {
+        self.controls[channel as usize].volume = volume.max(0.0);
+    }
+
+    /// Set the current output of the cartridge sound chip
+    pub fn set_expansion_output(&mut self, sample: Sample) {
+        self.expansion = sample;
+    }
+
+    /// Scale the cartridge sound chip relative to the APU. 1.0 approximates the level on hardware
+    pub fn set_expansion_level(&mut self, level: f32) {
+        self.expansion_level = level.max(0.0);
+    }
+
+    /// State of the APU IRQ output. Asserted while the frame or DMC interrupt flag is set
+    pub fn irq(&self) -> bool {
+        self.sequencer.irq() || self.dmc.irq()
+    }
+
     fn status(&self) -> u8 {
         (self.pulse1.length_status() as u8)
         | (self.pulse2.length_status() as u8) << 1
@@ -211,6 +319,7 @@ impl Apu {
         | (self.noise.length_status() as u8) << 3
         | (self.dmc.status() as u8) << 4
         | (self.sequencer.irq_status() as u8) << 6
+        | (self.dmc.irq() as u8) << 7
     }
 
     fn clock_length(&mut self) {
@@ -231,6 +340,11 @@ impl Apu {
         self.pulse2.clock_sweep();
     }
 
+    /// Set the output filter chain. Resets filter state
+    pub fn set_filter(&mut self, filter: &AudioFilter) {
+        self.filter = FilterChain::new(filter);
+    }
+
     pub fn load_bus(&mut self, bus: IoAccessRef) {
         self.dmc.load_bus(bus.clone());
         self.bus = Some(bus);
@@ -242,6 +356,25 @@ impl Apu {
     }
 }
 
+mod helpers {
+    /// Linearly interpolated table lookup, so scaled channel outputs can fall between entries
+    pub fn lookup(table: &[f32], index: f32) -> f32 {
+        let max = (table.len() - 1) as f32;
+        let index = index.min(max);
+
+        let lower = index.floor();
+        let frac = index - lower;
+        let lower = lower as usize;
+
+        if frac == 0.0 {
+            table[lower]
+        }
+        else {
+            table[lower] + (table[lower + 1] - table[lower]) * frac
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -287,6 +420,66 @@ mod tests {
         assert!(bit_is_clear!(status, 1));
     }
 
+    #[test]
+    fn expansion_audio() {
+        let mut apu = init_apu();
+        let base = apu.mix();
+
+        apu.set_expansion_output(0.1);
+        assert!((apu.mix() - (base + 0.1)).abs() < 1e-6);
+
+        apu.set_expansion_level(0.5);
+        assert!((apu.mix() - (base + 0.05)).abs() < 1e-6);
+
+        apu.set_channel_muted(AudioChannel::Expansion, true);
+        assert_eq!(apu.mix(), base);
+    }
+
+    #[test]
+    fn dmc_irq_status() {
+        let mut apu = init_apu();
+
+        // IRQ enabled, no loop, highest rate, 1 byte sample
+        apu.write_byte(0x4010, 0x8F);
+        apu.write_byte(0x4013, 0x00);
+        apu.write_byte(0x4015, 0x10);
+        assert!(bit_is_set!(apu.read_byte(0x4015), 4));
+
+        apu.tick();
+
+        let status = apu.read_byte(0x4015);
+        assert!(bit_is_clear!(status, 4));
+        assert!(bit_is_set!(status, 7));
+
+        // Reading status does not acknowledge the DMC IRQ, writing it does
+        assert!(bit_is_set!(apu.read_byte(0x4015), 7));
+        apu.write_byte(0x4015, 0x00);
+        assert!(bit_is_clear!(apu.read_byte(0x4015), 7));
+    }
+
+    #[test]
+    fn channel_controls() {
+        // The triangle channel idles at the top of its sequence
+        let mut apu = init_apu();
+        assert_eq!(apu.mix(), apu.tnd_table[45]);
+
+        apu.set_channel_volume(AudioChannel::Triangle, 0.5);
+        assert_eq!(apu.mix(), (apu.tnd_table[22] + apu.tnd_table[23]) / 2.0);
+
+        apu.set_channel_muted(AudioChannel::Triangle, true);
+        assert_eq!(apu.mix(), 0.0);
+
+        apu.set_channel_muted(AudioChannel::Triangle, false);
+        apu.set_channel_volume(AudioChannel::Triangle, 1.0);
+
+        // Soloing another channel silences the triangle
+        apu.set_channel_solo(AudioChannel::Pulse1, true);
+        assert_eq!(apu.mix(), 0.0);
+
+        apu.set_channel_solo(AudioChannel::Triangle, true);
+        assert_eq!(apu.mix(), apu.tnd_table[45]);
+    }
+
     fn run_for_step4_frame(apu: &mut dyn Clockable<Sample>) {
         for _ in 0..14915 {
             apu.tick();
@@ -307,7 +500,7 @@ mod tests {
 
     impl IoAccess for FakeBus {
         fn read_byte(&self, addr: u16) -> u8 {
-            self.vram[addr as usize]
+            self.vram[addr as usize & 0x3FFF]
         }
         fn write_byte(&mut self, addr: u16, value: u8) {
             self.vram[addr as usize] = value;
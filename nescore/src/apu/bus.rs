@@ -34,4 +34,8 @@ impl IoAccess for ApuIoBus {
     fn raise_interrupt(&mut self, interrupt_type: Interrupt) {
         self.cpu.borrow_mut().raise_interrupt(interrupt_type);
     }
+
+    fn stall(&mut self, cycles: u8) {
+        self.cpu.borrow_mut().stall(cycles);
+    }
 }
@@ -10,14 +10,17 @@ use super::{SoundChannel, Timer};
 
 // Frequency lookup table in CPU cycles
 const FREQ_LOOKUP: [u16; 16] = [428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54];
+/// CPU cycles lost to a sample fetch. Fewer are lost when the fetch lands on a CPU write or OAM DMA, which is not modeled
+const FETCH_STALL_CYCLES: u8 = 4;
 
-#[derive(Default)]
+/// Delta modulation channel
+/// https://wiki.nesdev.com/w/index.php/APU_DMC
 pub struct Dmc {
     irq_enabled: bool,
     loop_enabled: bool,
     sample_address: u16,
     sample_length: u16,
-    enabled: bool,
+    interrupt: bool,
 
     // Output unit
     bits_remaining: u8,
@@ -28,6 +31,7 @@ pub struct Dmc {
 
     timer: Timer,
 
+    // Memory reader
     sample_buffer: Option<u8>,
     current_addr: u16,
     remaining_bytes: u16,
@@ -35,56 +39,39 @@ pub struct Dmc {
     bus: Option<IoAccessRef>,
 }
 
-impl Clockable for Dmc {
-    fn tick(&mut self) {
-        if self.timer.tick() {
-            if self.sample_buffer.is_none() && self.remaining_bytes > 0 {
-                if let Some(ref bus) = self.bus {
-                    self.sample_buffer = Some(bus.borrow().read_byte(self.current_addr));
-                }
+impl Default for Dmc {
+    fn default() -> Self {
+        Dmc {
+            irq_enabled: false,
+            loop_enabled: false,
+            sample_address: 0xC000,
+            sample_length: 0x0001,
+            interrupt: false,
 
-                // Advance sample address. Wrap around to $8000 if needed
-                if self.current_addr == 0xFFFF {
-                    self.current_addr = 0x8000;
-                }
-                else {
-                    self.current_addr += 1;
-                }
+            bits_remaining: 0,
+            silence: true,
+            shift: 0,
 
-                if self.remaining_bytes > 0 {
+            output: 0,
 
-                    self.remaining_bytes -= 1;
+            timer: Timer::default(),
 
-                    if self.remaining_bytes == 0 {
-                        if self.loop_enabled {
-                            self.start_cycle();
-                        }
-                        else if self.irq_enabled {
-                            // TODO: Raise IRQ Interrupt
-                        }
-                    }
-                }
-            }
+            sample_buffer: None,
+            current_addr: 0xC000,
+            remaining_bytes: 0,
 
-            // Output unit
-            if !self.silence && self.output >= 2 && self.output <= 125 {
-                if bit_is_set!(self.shift, 0) {
-                    self.output += 2;
-                }
-                else {
-                    self.output -= 2;
-                }
-            }
-
-            self.shift >>= 1;
+            bus: None,
+        }
+    }
+}
 
-            if self.bits_remaining > 0 {
-                self.bits_remaining -= 1;
+impl Clockable for Dmc {
+    fn tick(&mut self) {
+        // The memory reader refills the sample buffer as soon as it is emptied
+        self.fill_buffer();
 
-                if self.bits_remaining == 0 {
-                    self.start_cycle();
-                }
-            }
+        if self.timer.tick() {
+            self.clock_output();
         }
     }
 }
@@ -106,9 +93,14 @@ impl IoAccess for Dmc {
             0 => {
                 self.irq_enabled = bit_is_set!(data, 7);
                 self.loop_enabled = bit_is_set!(data, 6);
-                self.timer.set_period(FREQ_LOOKUP[(data & 0x0F) as usize] / 2);
+                // Rates are in CPU cycles and the timer is clocked every APU cycle
+                self.timer.set_period(FREQ_LOOKUP[(data & 0x0F) as usize] / 2 - 1);
+
+                if !self.irq_enabled {
+                    self.interrupt = false;
+                }
             },
-            1 => self.output = data & 0xEF,
+            1 => self.output = data & 0x7F,
             2 => self.sample_address = 0xC000 | (data as u16) << 6,
             3 => self.sample_length = (data as u16) << 4 | 0x01,
             _ => panic!("Invalid register for DMC"),
@@ -121,31 +113,219 @@ impl Dmc {
         self.bus = Some(bus);
     }
 
+    /// Handle a write to $4015. Clears the interrupt flag and starts or stops sample playback
     pub fn set_enable(&mut self, e: bool) {
-        self.enabled = e;
+        self.interrupt = false;
 
-        if self.enabled {
-            self.start_cycle();
+        if !e {
+            self.remaining_bytes = 0;
         }
+        else if self.remaining_bytes == 0 {
+            self.restart();
+        }
+    }
+
+    /// Bytes remaining in the current sample
+    pub fn status(&self) -> bool {
+        self.remaining_bytes > 0
+    }
+
+    /// DMC interrupt flag
+    pub fn irq(&self) -> bool {
+        self.interrupt
     }
 
-    fn start_cycle(&mut self) {
+    fn restart(&mut self) {
         self.current_addr = self.sample_address;
         self.remaining_bytes = self.sample_length;
+    }
 
-        self.bits_remaining = 8;
+    fn fill_buffer(&mut self) {
+        if self.sample_buffer.is_some() || self.remaining_bytes == 0 {
+            return;
+        }
 
-        if self.sample_buffer.is_some() {
-            self.shift = self.sample_buffer.unwrap();
-            self.sample_buffer = None;
-            self.silence = false;
+        // The CPU is stalled while the sample byte is fetched
+        if let Some(ref bus) = self.bus {
+            self.sample_buffer = Some(bus.borrow().read_byte(self.current_addr));
+            bus.borrow_mut().stall(FETCH_STALL_CYCLES);
         }
-        else {
-            self.silence = true;
+
+        // Advance sample address. Wrap around to $8000 if needed
+        self.current_addr = if self.current_addr == 0xFFFF { 0x8000 } else { self.current_addr + 1 };
+        self.remaining_bytes -= 1;
+
+        if self.remaining_bytes == 0 {
+            if self.loop_enabled {
+                self.restart();
+            }
+            else if self.irq_enabled {
+                self.interrupt = true;
+            }
         }
     }
 
-    pub fn status(&self) -> bool {
-        self.remaining_bytes > 0
+    fn clock_output(&mut self) {
+        if !self.silence {
+            if bit_is_set!(self.shift, 0) {
+                if self.output <= 125 {
+                    self.output += 2;
+                }
+            }
+            else if self.output >= 2 {
+                self.output -= 2;
+            }
+        }
+
+        self.shift >>= 1;
+        self.bits_remaining = self.bits_remaining.saturating_sub(1);
+
+        // Start a new output cycle
+        if self.bits_remaining == 0 {
+            self.bits_remaining = 8;
+
+            match self.sample_buffer.take() {
+                Some(sample) => {
+                    self.shift = sample;
+                    self.silence = false;
+                },
+                None => self.silence = true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct SampleBus {
+        stall_cycles: usize,
+    }
+
+    impl IoAccess for SampleBus {
+        fn read_byte(&self, _addr: u16) -> u8 {
+            0xFF
+        }
+        fn write_byte(&mut self, _addr: u16, _value: u8) {}
+        fn stall(&mut self, cycles: u8) {
+            self.stall_cycles += cycles as usize;
+        }
+    }
+
+    /// DMC playing a 17 byte sample at $C000 at the highest rate
+    fn init_dmc(flags: u8) -> Dmc {
+        let mut dmc = Dmc::default();
+        dmc.load_bus(Rc::new(RefCell::new(SampleBus::default())));
+
+        dmc.write_byte(0, flags | 0x0F);
+        dmc.write_byte(2, 0x00);
+        dmc.write_byte(3, 0x01);
+        dmc.set_enable(true);
+
+        dmc
+    }
+
+    /// Run until the sample has been fully read, returning the number of APU cycles taken
+    fn run_sample(dmc: &mut Dmc) -> usize {
+        let mut cycles = 0;
+        while dmc.status() {
+            dmc.tick();
+            cycles += 1;
+        }
+
+        cycles
+    }
+
+    #[test]
+    fn irq_at_sample_end() {
+        let mut dmc = init_dmc(0x80);
+        assert!(dmc.status());
+
+        // The first two bytes are read immediately, as the output unit takes the first byte on its first clock. Each
+        // following byte is read when the output unit empties the buffer (8 bits at 27 APU cycles each)
+        assert_eq!(run_sample(&mut dmc), 2 + 15 * 8 * 27);
+        assert!(dmc.irq());
+
+        // Writing $4015 clears the flag
+        dmc.set_enable(true);
+        assert!(!dmc.irq());
+        assert!(dmc.status());
+    }
+
+    #[test]
+    fn sample_fetch_stalls_cpu() {
+        let bus = Rc::new(RefCell::new(SampleBus::default()));
+
+        let mut dmc = init_dmc(0x00);
+        dmc.load_bus(bus.clone());
+        run_sample(&mut dmc);
+
+        assert_eq!(bus.borrow().stall_cycles, 17 * FETCH_STALL_CYCLES as usize);
+    }
+
+    #[test]
+    fn no_irq_when_disabled() {
+        let mut dmc = init_dmc(0x00);
+        run_sample(&mut dmc);
+        assert!(!dmc.irq());
+    }
+
+    #[test]
+    fn clearing_irq_enable_acknowledges() {
+        let mut dmc = init_dmc(0x80);
+        run_sample(&mut dmc);
+        assert!(dmc.irq());
+
+        dmc.write_byte(0, 0x0F);
+        assert!(!dmc.irq());
+    }
+
+    #[test]
+    fn looping() {
+        let mut dmc = init_dmc(0x40);
+
+        for _ in 0..10_000 {
+            dmc.tick();
+            assert!(dmc.status());
+        }
+
+        assert!(!dmc.irq());
+    }
+
+    #[test]
+    fn disable_stops_sample() {
+        let mut dmc = init_dmc(0x80);
+        dmc.tick();
+
+        dmc.set_enable(false);
+        assert!(!dmc.status());
+        assert!(!dmc.irq());
+    }
+
+    #[test]
+    fn output_unit() {
+        let mut dmc = init_dmc(0x00);
+        dmc.write_byte(1, 0xC0);
+        assert_eq!(dmc.output(), 0x40);
+
+        // The first clock starts the output cycle. Every sample bit is set, so the output climbs by 2 per bit until it
+        // saturates
+        for _ in 0..(27 * 9) {
+            dmc.tick();
+        }
+        assert_eq!(dmc.output(), 0x40 + 2 * 8);
+
+        for _ in 0..(27 * 8 * 8) {
+            dmc.tick();
+        }
+        assert_eq!(dmc.output(), 126);
+
+        // Direct load
+        dmc.write_byte(1, 0x01);
+        assert_eq!(dmc.output(), 0x01);
     }
 }
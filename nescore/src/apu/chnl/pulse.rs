@@ -70,6 +70,7 @@ pub struct Pulse {
     sweep_shift: u8,               // Value for the sweep unit barrel shifter
     sweep_reload: bool,            // Whether the sweep unit was written to since the last sweep clock
     sweep_add_mode: NegateAddMode, // Used to change the negate add between the two pulse channels
+    period_gate: bool,             // Whether periods outside the sweep unit's range silence the channel
 
     lenctr: LengthCounter,         // Length counter unit
     envelope: Envelope,            // Envelope unit
@@ -87,6 +88,7 @@ impl Default for Pulse {
             sweep_shift: 0,
             sweep_reload: false,
             sweep_add_mode: NegateAddMode::OnesComplement,
+            period_gate: true,
 
             lenctr: LengthCounter::default(),
             envelope: Envelope::default(),
@@ -113,6 +115,12 @@ impl Pulse {
         self
     }
 
+    /// Do not silence the channel for periods below 8 or above $7FE. For pulses without a sweep unit
+    pub fn without_period_gate(mut self) -> Self {
+        self.period_gate = false;
+        self
+    }
+
     pub fn clock_sweep(&mut self) {
         let event = self.sweep_divider.tick();
 
@@ -137,7 +145,7 @@ impl Pulse {
     fn should_output(&self) -> bool {
         // The timer is not less than 8
         let period = self.timer.period();
-        let gate0 = period >= 8 && period < 0x7FF;
+        let gate0 = !self.period_gate || (period >= 8 && period < 0x7FF);
         // At the high portion of the waveform
         let gate1 = self.waveform.output() != 0;
         // The length counter is not silencing the channel
@@ -0,0 +1,163 @@
+//
+// apu/filter.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::apu::{Sample, APU_OUTPUT_RATE};
+
+use std::f32::consts::PI;
+
+/// A first-order filter stage, with its cutoff frequency in Hz
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterStage {
+    HighPass(f32),
+    LowPass(f32),
+}
+
+/// Analogue output stage applied to the mixer output
+/// https://wiki.nesdev.com/w/index.php/APU_Mixer
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum AudioFilter {
+    /// NES: 90 Hz and 440 Hz high-pass filters followed by a 14 kHz low-pass
+    #[default]
+    Nes,
+    /// Famicom: 37 Hz high-pass filter followed by a 14 kHz low-pass
+    Famicom,
+    /// Raw mixer output
+    Bypass,
+    /// Stages applied in order
+    Custom(Vec<FilterStage>),
+}
+
+impl AudioFilter {
+    /// Filter stages applied in order
+    pub fn stages(&self) -> Vec<FilterStage> {
+        match self {
+            AudioFilter::Nes => vec![FilterStage::HighPass(90.0), FilterStage::HighPass(440.0), FilterStage::LowPass(14_000.0)],
+            AudioFilter::Famicom => vec![FilterStage::HighPass(37.0), FilterStage::LowPass(14_000.0)],
+            AudioFilter::Bypass => vec![],
+            AudioFilter::Custom(stages) => stages.clone(),
+        }
+    }
+}
+
+/// Single pole RC filter
+#[derive(Debug, Clone)]
+struct Filter {
+    stage: FilterStage,
+    alpha: f32,
+    prev_input: Sample,
+    prev_output: Sample,
+}
+
+impl Filter {
+    fn new(stage: FilterStage, sample_rate: f32) -> Self {
+        let dt = 1.0 / sample_rate;
+        let rc = |cutoff: f32| 1.0 / (2.0 * PI * cutoff);
+
+        let alpha = match stage {
+            FilterStage::HighPass(cutoff) => rc(cutoff) / (rc(cutoff) + dt),
+            FilterStage::LowPass(cutoff) => dt / (rc(cutoff) + dt),
+        };
+
+        Filter {
+            stage,
+            alpha,
+            prev_input: 0.0,
+            prev_output: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: Sample) -> Sample {
+        let output = match self.stage {
+            FilterStage::HighPass(_) => self.alpha * (self.prev_output + input - self.prev_input),
+            FilterStage::LowPass(_) => self.prev_output + self.alpha * (input - self.prev_output),
+        };
+
+        self.prev_input = input;
+        self.prev_output = output;
+
+        output
+    }
+}
+
+/// Chain of filters run at the APU output rate
+#[derive(Debug, Clone)]
+pub struct FilterChain {
+    filters: Vec<Filter>,
+}
+
+impl Default for FilterChain {
+    fn default() -> Self {
+        FilterChain::new(&AudioFilter::default())
+    }
+}
+
+impl FilterChain {
+    pub fn new(filter: &AudioFilter) -> Self {
+        FilterChain {
+            filters: filter.stages().into_iter().map(|stage| Filter::new(stage, APU_OUTPUT_RATE)).collect(),
+        }
+    }
+
+    pub fn process(&mut self, sample: Sample) -> Sample {
+        self.filters.iter_mut().fold(sample, |sample, filter| filter.process(sample))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, len: usize) -> Vec<Sample> {
+        (0..len).map(|n| (2.0 * PI * freq * n as f32 / APU_OUTPUT_RATE).sin()).collect()
+    }
+
+    /// Peak amplitude over the second half of the output, after the filters have settled
+    fn peak(filter: AudioFilter, input: &[Sample]) -> f32 {
+        let mut chain = FilterChain::new(&filter);
+        let output: Vec<Sample> = input.iter().map(|&s| chain.process(s)).collect();
+
+        output[output.len() / 2..].iter().fold(0.0, |peak, s| s.abs().max(peak))
+    }
+
+    #[test]
+    fn bypass() {
+        let mut chain = FilterChain::new(&AudioFilter::Bypass);
+        assert_eq!(chain.process(0.25), 0.25);
+        assert_eq!(chain.process(0.75), 0.75);
+    }
+
+    #[test]
+    fn removes_dc_offset() {
+        // Half a second of constant mixer output
+        let input = vec![0.5; APU_OUTPUT_RATE as usize / 2];
+
+        assert!(peak(AudioFilter::Nes, &input) < 0.001);
+        assert!(peak(AudioFilter::Famicom, &input) < 0.001);
+    }
+
+    #[test]
+    fn passband() {
+        let input = sine(2000.0, APU_OUTPUT_RATE as usize / 10);
+
+        assert!((peak(AudioFilter::Nes, &input) - 1.0).abs() < 0.1);
+        assert!((peak(AudioFilter::Famicom, &input) - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn famicom_keeps_more_bass() {
+        let input = sine(60.0, APU_OUTPUT_RATE as usize / 2);
+
+        assert!(peak(AudioFilter::Famicom, &input) > peak(AudioFilter::Nes, &input) * 2.0);
+    }
+
+    #[test]
+    fn low_pass() {
+        let input = sine(40_000.0, APU_OUTPUT_RATE as usize / 100);
+        let stages = AudioFilter::Custom(vec![FilterStage::LowPass(14_000.0)]);
+
+        assert!(peak(stages, &input) < 0.4);
+    }
+}
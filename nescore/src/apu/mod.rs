@@ -8,11 +8,13 @@
 // Modules
 mod apu;
 pub mod bus;
-mod chnl;
+pub(crate) mod chnl;
+mod filter;
 mod seq;
 
 // Public re-exports
-pub use apu::{Apu, Sample, APU_OUTPUT_RATE};
+pub use apu::{Apu, AudioChannel, Sample, APU_OUTPUT_RATE};
+pub use filter::{AudioFilter, FilterStage};
 
 #[cfg(feature="events")]
 pub use apu::events;
@@ -98,6 +98,12 @@ impl Clockable<SequencerEvents> for FrameSequencer {
 }
 
 impl FrameSequencer {
+    /// State of the frame IRQ flag
+    pub fn irq(&self) -> bool {
+        *self.frame_irq.borrow()
+    }
+
+    /// Read and clear the frame IRQ flag
     pub fn irq_status(&self) -> bool {
         let status = *self.frame_irq.borrow();
         *self.frame_irq.borrow_mut() = false;
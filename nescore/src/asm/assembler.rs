@@ -0,0 +1,889 @@
+//
+// asm/assembler.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::{Instruction, AddressingMode, OPCODE_TABLE};
+use crate::cart::{Cartridge, PRG_ROM_BANK_SIZE, CHR_ROM_BANK_SIZE};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Address the program is assembled at when no `.org` is given
+pub const DEFAULT_ORIGIN: u16 = 0x8000;
+
+//
+// Error types
+//
+
+/// Error assembling a program. Each variant carries the (1-based) source line number
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssemblerError {
+    UnknownInstruction(usize, String),
+    UnknownDirective(usize, String),
+    InvalidOperand(usize, String),
+    InvalidAddressingMode(usize, Instruction),
+    UndefinedSymbol(usize, String),
+    DuplicateSymbol(usize, String),
+    ValueOutOfRange(usize, i32),
+    BranchOutOfRange(usize, i32),
+    OriginMovedBackwards(usize, u16),
+    InvalidCartridgeLayout(u16, usize),
+}
+
+impl fmt::Display for AssemblerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AssemblerError::UnknownInstruction(l, ref s)   => write!(f, "Line {}: Unknown instruction `{}`", l, s),
+            AssemblerError::UnknownDirective(l, ref s)     => write!(f, "Line {}: Unknown directive `{}`", l, s),
+            AssemblerError::InvalidOperand(l, ref s)       => write!(f, "Line {}: Invalid operand `{}`", l, s),
+            AssemblerError::InvalidAddressingMode(l, i)    => write!(f, "Line {}: Addressing mode not supported by {:?}", l, i),
+            AssemblerError::UndefinedSymbol(l, ref s)      => write!(f, "Line {}: Undefined symbol `{}`", l, s),
+            AssemblerError::DuplicateSymbol(l, ref s)      => write!(f, "Line {}: Symbol `{}` is already defined", l, s),
+            AssemblerError::ValueOutOfRange(l, v)          => write!(f, "Line {}: Value {} is out of range", l, v),
+            AssemblerError::BranchOutOfRange(l, v)         => write!(f, "Line {}: Branch offset {} is out of range", l, v),
+            AssemblerError::OriginMovedBackwards(l, a)     => write!(f, "Line {}: Origin ${:04X} is behind already assembled code", l, a),
+            AssemblerError::InvalidCartridgeLayout(o, len) => write!(f, "Program at ${:04X} ({} bytes) does not fit in $8000-$FFFF", o, len),
+        }
+    }
+}
+
+impl Error for AssemblerError {}
+
+//
+// Public interface
+//
+
+/// Assemble 6502 source into a flat binary, starting at the address of the first emitted byte
+/// ```
+/// # use nescore::asm::assemble;
+/// let bin = assemble("
+///     .org $C000
+/// loop:
+///     LDA #$01
+///     JMP loop
+/// ").unwrap();
+///
+/// assert_eq!(bin, vec![0xA9, 0x01, 0x4C, 0x00, 0xC0]);
+/// ```
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssemblerError> {
+    Assembler::new(source)?.run().map(|(_, data)| data)
+}
+
+/// Assemble 6502 source into an NROM cartridge
+///
+/// The program must be located in $8000-$FFFF. Programs that fit in $C000-$FFFF produce a single 16KB PRG ROM bank,
+/// otherwise two banks are used. If the program does not cover the vectors, the reset vector points to the start of
+/// the program. `chr_rom` is padded to 8KB.
+pub fn assemble_cart(source: &str, chr_rom: &[u8]) -> Result<Cartridge, AssemblerError> {
+    let (origin, data) = Assembler::new(source)?.run()?;
+
+    let end = origin as usize + data.len();
+    if origin < 0x8000 || end > 0x10000 {
+        return Err(AssemblerError::InvalidCartridgeLayout(origin, data.len()));
+    }
+
+    let (num_banks, base) = if origin >= 0xC000 { (1, 0xC000usize) } else { (2, 0x8000usize) };
+
+    let mut prg_rom = vec![0x00u8; num_banks * PRG_ROM_BANK_SIZE];
+    let offset = origin as usize - base;
+    prg_rom[offset..offset + data.len()].copy_from_slice(&data);
+
+    // Point the reset vector at the start of the program if it was not provided
+    if end <= 0xFFFC {
+        let reset = 0xFFFC - base;
+        prg_rom[reset] = low_byte!(origin) as u8;
+        prg_rom[reset + 1] = high_byte!(origin) as u8;
+    }
+
+    let mut chr = chr_rom.to_vec();
+    chr.resize(CHR_ROM_BANK_SIZE, 0x00);
+
+    let header = [
+        0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
+        num_banks as u8,        // PRG ROM
+        0x01,                   // CHR ROM
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    let rom = [&header[..], &prg_rom[..], &chr[..]].concat();
+
+    Ok(Cartridge::from(rom).expect("Generated NROM image is valid"))
+}
+
+//
+// Source representation
+//
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(i32),
+    Symbol(String),
+    Pc,
+    Negate(Box<Expr>),
+    LowByte(Box<Expr>),
+    HighByte(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    None,
+    Accumulator,
+    Immediate(Expr),
+    Direct(Expr),
+    IndexedX(Expr),
+    IndexedY(Expr),
+    Indirect(Expr),
+    IndexedIndirect(Expr),
+    IndirectIndexed(Expr),
+}
+
+#[derive(Debug, Clone)]
+enum Statement {
+    Empty,
+    Op(Instruction, Operand),
+    Org(Expr),
+    Byte(Vec<Expr>),
+    Word(Vec<Expr>),
+    Assign(String, Expr),
+}
+
+struct Line {
+    number: usize,
+    label: Option<String>,
+    statement: Statement,
+}
+
+//
+// Assembler
+//
+
+struct Assembler {
+    lines: Vec<Line>,
+    symbols: HashMap<String, i32>,
+    modes: Vec<Option<AddressingMode>>,
+}
+
+impl Assembler {
+    fn new(source: &str) -> Result<Self, AssemblerError> {
+        let lines = source.lines()
+                          .enumerate()
+                          .map(|(i, text)| parse_line(i + 1, text))
+                          .collect::<Result<Vec<Line>, AssemblerError>>()?;
+
+        let modes = vec![None; lines.len()];
+
+        Ok(Assembler {
+            lines,
+            symbols: HashMap::new(),
+            modes,
+        })
+    }
+
+    /// Run both passes, returning the origin and the assembled bytes
+    fn run(mut self) -> Result<(u16, Vec<u8>), AssemblerError> {
+        self.first_pass()?;
+        self.second_pass()
+    }
+
+    /// Collect symbols and determine the size of each statement
+    fn first_pass(&mut self) -> Result<(), AssemblerError> {
+        let mut pc = DEFAULT_ORIGIN as i32;
+
+        for idx in 0..self.lines.len() {
+            let number = self.lines[idx].number;
+
+            if let Some(label) = self.lines[idx].label.clone() {
+                self.define(number, label, pc)?;
+            }
+
+            match self.lines[idx].statement.clone() {
+                Statement::Empty => {},
+                Statement::Assign(name, expr) => {
+                    let value = self.eval(number, &expr, pc)?;
+                    self.define(number, name, value)?;
+                },
+                Statement::Org(expr) => {
+                    pc = self.eval(number, &expr, pc)?;
+                },
+                Statement::Byte(exprs) => pc += exprs.len() as i32,
+                Statement::Word(exprs) => pc += 2 * exprs.len() as i32,
+                Statement::Op(instr, operand) => {
+                    let mode = self.select_mode(number, instr, &operand, pc)?;
+                    self.modes[idx] = Some(mode);
+                    pc += mode.operand_len() as i32 + 1;
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Emit code using the sizes determined in the first pass
+    fn second_pass(&self) -> Result<(u16, Vec<u8>), AssemblerError> {
+        let mut pc = DEFAULT_ORIGIN as i32;
+        let mut origin: Option<u16> = None;
+        let mut data: Vec<u8> = vec![];
+
+        for (line, mode) in self.lines.iter().zip(self.modes.iter()) {
+            let number = line.number;
+
+            let bytes = match line.statement {
+                Statement::Empty | Statement::Assign(..) => continue,
+                Statement::Org(ref expr) => {
+                    pc = self.eval(number, expr, pc)?;
+                    continue;
+                },
+                Statement::Byte(ref exprs) => {
+                    exprs.iter()
+                         .map(|e| self.eval(number, e, pc).and_then(|v| to_byte(number, v)))
+                         .collect::<Result<Vec<u8>, AssemblerError>>()?
+                },
+                Statement::Word(ref exprs) => {
+                    let mut bytes = vec![];
+                    for e in exprs {
+                        let word = self.eval(number, e, pc).and_then(|v| to_word(number, v))?;
+                        bytes.push(low_byte!(word) as u8);
+                        bytes.push(high_byte!(word) as u8);
+                    }
+                    bytes
+                },
+                Statement::Op(instr, ref operand) => {
+                    let mode = mode.expect("Addressing mode is selected in the first pass");
+                    self.encode(number, instr, mode, operand, pc)?
+                },
+            };
+
+            // Place the bytes at the current address
+            let base = *origin.get_or_insert(pc as u16) as i32;
+            let offset = pc - base;
+            if offset < data.len() as i32 {
+                return Err(AssemblerError::OriginMovedBackwards(number, pc as u16));
+            }
+            data.resize(offset as usize, 0x00);
+            data.extend(bytes.iter());
+
+            pc += bytes.len() as i32;
+        }
+
+        Ok((origin.unwrap_or(DEFAULT_ORIGIN), data))
+    }
+
+    fn define(&mut self, number: usize, name: String, value: i32) -> Result<(), AssemblerError> {
+        if self.symbols.contains_key(&name) {
+            return Err(AssemblerError::DuplicateSymbol(number, name));
+        }
+        self.symbols.insert(name, value);
+
+        Ok(())
+    }
+
+    fn eval(&self, number: usize, expr: &Expr, pc: i32) -> Result<i32, AssemblerError> {
+        let value = match *expr {
+            Expr::Number(n) => n,
+            Expr::Pc => pc,
+            Expr::Symbol(ref name) => {
+                *self.symbols.get(name).ok_or_else(|| AssemblerError::UndefinedSymbol(number, name.clone()))?
+            },
+            Expr::Negate(ref e) => self.eval(number, e, pc)?.wrapping_neg(),
+            Expr::LowByte(ref e) => self.eval(number, e, pc)? & 0xFF,
+            Expr::HighByte(ref e) => (self.eval(number, e, pc)? >> 8) & 0xFF,
+            Expr::Binary(op, ref lhs, ref rhs) => {
+                let lhs = self.eval(number, lhs, pc)?;
+                let rhs = self.eval(number, rhs, pc)?;
+
+                match op {
+                    BinaryOp::Add => lhs.wrapping_add(rhs),
+                    BinaryOp::Sub => lhs.wrapping_sub(rhs),
+                    BinaryOp::Mul => lhs.wrapping_mul(rhs),
+                    BinaryOp::Div => lhs.checked_div(rhs).ok_or(AssemblerError::ValueOutOfRange(number, rhs))?,
+                }
+            },
+        };
+
+        Ok(value)
+    }
+
+    /// Pick the addressing mode for an instruction. Symbols that are not yet defined are assumed to be 16-bit
+    fn select_mode(&self, number: usize, instr: Instruction, operand: &Operand, pc: i32) -> Result<AddressingMode, AssemblerError> {
+        let supports = |mode| find_opcode(instr, mode).is_some();
+        let is_zp = |e: &Expr| matches!(self.eval(number, e, pc), Ok(0..=0xFF));
+
+        let sized = |e: &Expr, zp: AddressingMode, abs: AddressingMode| {
+            if supports(zp) && (is_zp(e) || !supports(abs)) { zp } else { abs }
+        };
+
+        let mode = match *operand {
+            Operand::None => {
+                [AddressingMode::Implied, AddressingMode::Accumulator, AddressingMode::Immediate]
+                    .iter()
+                    .copied()
+                    .find(|&m| supports(m))
+                    .unwrap_or(AddressingMode::Implied)
+            },
+            Operand::Accumulator        => AddressingMode::Accumulator,
+            Operand::Immediate(_)       => AddressingMode::Immediate,
+            Operand::Indirect(_)        => AddressingMode::Indirect,
+            Operand::IndexedIndirect(_) => AddressingMode::IndexedIndirect,
+            Operand::IndirectIndexed(_) => AddressingMode::IndirectIndexed,
+            Operand::IndexedX(ref e)    => sized(e, AddressingMode::ZeroPageX, AddressingMode::AbsoluteX),
+            Operand::IndexedY(ref e)    => sized(e, AddressingMode::ZeroPageY, AddressingMode::AbsoluteY),
+            Operand::Direct(ref e) => {
+                if supports(AddressingMode::Relative) {
+                    AddressingMode::Relative
+                }
+                else {
+                    sized(e, AddressingMode::ZeroPage, AddressingMode::Absolute)
+                }
+            },
+        };
+
+        if supports(mode) {
+            Ok(mode)
+        }
+        else {
+            Err(AssemblerError::InvalidAddressingMode(number, instr))
+        }
+    }
+
+    fn encode(&self, number: usize, instr: Instruction, mode: AddressingMode, operand: &Operand, pc: i32) -> Result<Vec<u8>, AssemblerError> {
+        let opcode = find_opcode(instr, mode).ok_or(AssemblerError::InvalidAddressingMode(number, instr))?;
+        let mut bytes = vec![opcode];
+
+        let expr = match *operand {
+            Operand::None | Operand::Accumulator => None,
+            Operand::Immediate(ref e) | Operand::Direct(ref e) | Operand::IndexedX(ref e) | Operand::IndexedY(ref e) |
+            Operand::Indirect(ref e) | Operand::IndexedIndirect(ref e) | Operand::IndirectIndexed(ref e) => Some(e),
+        };
+
+        let value = match expr {
+            Some(e) => self.eval(number, e, pc)?,
+            None => 0,
+        };
+
+        match mode {
+            AddressingMode::Relative => {
+                let offset = value - (pc + 2);
+                if !(-128..=127).contains(&offset) {
+                    return Err(AssemblerError::BranchOutOfRange(number, offset));
+                }
+                bytes.push(offset as u8);
+            },
+            _ => match mode.operand_len() {
+                0 => {},
+                1 => {
+                    // Zero page addresses cannot be negative
+                    if mode != AddressingMode::Immediate && !(0..=0xFF).contains(&value) {
+                        return Err(AssemblerError::ValueOutOfRange(number, value));
+                    }
+                    bytes.push(to_byte(number, value)?);
+                },
+                _ => {
+                    if !(0..=0xFFFF).contains(&value) {
+                        return Err(AssemblerError::ValueOutOfRange(number, value));
+                    }
+                    bytes.push(low_byte!(value) as u8);
+                    bytes.push(high_byte!(value) as u8);
+                },
+            },
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// Find the opcode for the instruction and addressing mode, preferring official opcodes
+fn find_opcode(instr: Instruction, mode: AddressingMode) -> Option<u8> {
+    let matches = |official: bool| {
+        OPCODE_TABLE.iter()
+                    .position(|op| op.instr == instr && op.mode == mode && op.official == official)
+                    .map(|i| i as u8)
+    };
+
+    matches(true).or_else(|| matches(false))
+}
+
+fn to_byte(number: usize, value: i32) -> Result<u8, AssemblerError> {
+    if (-128..=0xFF).contains(&value) {
+        Ok(value as u8)
+    }
+    else {
+        Err(AssemblerError::ValueOutOfRange(number, value))
+    }
+}
+
+fn to_word(number: usize, value: i32) -> Result<u16, AssemblerError> {
+    if (-32768..=0xFFFF).contains(&value) {
+        Ok(value as u16)
+    }
+    else {
+        Err(AssemblerError::ValueOutOfRange(number, value))
+    }
+}
+
+//
+// Parsing
+//
+
+fn parse_line(number: usize, text: &str) -> Result<Line, AssemblerError> {
+    let mut rest = strip_comment(text).trim();
+    let mut label = None;
+
+    // Label definition
+    let ident_len = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+    if ident_len > 0 && rest[ident_len..].starts_with(':') {
+        label = Some(rest[..ident_len].to_string());
+        rest = rest[ident_len + 1..].trim();
+    }
+
+    // Symbol assignment
+    let ident_len = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+    if ident_len > 0 {
+        if let Some(expr) = rest[ident_len..].trim_start().strip_prefix('=') {
+            let statement = Statement::Assign(rest[..ident_len].to_string(), parse_expr(number, expr)?);
+            return Ok(Line { number, label, statement });
+        }
+    }
+
+    let (head, args) = match rest.find(char::is_whitespace) {
+        Some(idx) => (&rest[..idx], rest[idx..].trim()),
+        None => (rest, ""),
+    };
+
+    let statement = if rest.is_empty() {
+        Statement::Empty
+    }
+    else if head.starts_with('.') {
+        match head.to_lowercase().as_str() {
+            ".org"          => Statement::Org(parse_expr(number, args)?),
+            ".byte" | ".db" => Statement::Byte(parse_byte_list(number, args)?),
+            ".word" | ".dw" => Statement::Word(split_list(args).iter().map(|e| parse_expr(number, e)).collect::<Result<_, _>>()?),
+            _ => return Err(AssemblerError::UnknownDirective(number, head.to_string())),
+        }
+    }
+    else {
+        let instr = parse_mnemonic(head).ok_or_else(|| AssemblerError::UnknownInstruction(number, head.to_string()))?;
+        Statement::Op(instr, parse_operand(number, args)?)
+    };
+
+    Ok(Line { number, label, statement })
+}
+
+fn parse_mnemonic(mnemonic: &str) -> Option<Instruction> {
+    let mnemonic = mnemonic.to_uppercase();
+    OPCODE_TABLE.iter().map(|op| op.instr).find(|instr| format!("{:?}", instr) == mnemonic)
+}
+
+fn parse_operand(number: usize, text: &str) -> Result<Operand, AssemblerError> {
+    // Whitespace is not significant unless the operand is a character literal
+    let s: String = if text.contains('\'') { text.trim().to_string() } else { text.split_whitespace().collect() };
+    let upper = s.to_uppercase();
+
+    let operand = if s.is_empty() {
+        Operand::None
+    }
+    else if upper == "A" {
+        Operand::Accumulator
+    }
+    else if let Some(e) = s.strip_prefix('#') {
+        Operand::Immediate(parse_expr(number, e)?)
+    }
+    else if s.starts_with('(') {
+        if upper.ends_with(",X)") {
+            Operand::IndexedIndirect(parse_expr(number, &s[1..s.len() - 3])?)
+        }
+        else if upper.ends_with("),Y") {
+            Operand::IndirectIndexed(parse_expr(number, &s[1..s.len() - 3])?)
+        }
+        else if upper.ends_with(')') {
+            Operand::Indirect(parse_expr(number, &s[1..s.len() - 1])?)
+        }
+        else {
+            return Err(AssemblerError::InvalidOperand(number, s));
+        }
+    }
+    else if upper.ends_with(",X") {
+        Operand::IndexedX(parse_expr(number, &s[..s.len() - 2])?)
+    }
+    else if upper.ends_with(",Y") {
+        Operand::IndexedY(parse_expr(number, &s[..s.len() - 2])?)
+    }
+    else {
+        Operand::Direct(parse_expr(number, &s)?)
+    };
+
+    Ok(operand)
+}
+
+/// Parse a `.byte` argument list. String arguments expand to one byte per character
+fn parse_byte_list(number: usize, text: &str) -> Result<Vec<Expr>, AssemblerError> {
+    let mut exprs = vec![];
+
+    for arg in split_list(text) {
+        if arg.len() >= 2 && arg.starts_with('"') && arg.ends_with('"') {
+            exprs.extend(arg[1..arg.len() - 1].bytes().map(|b| Expr::Number(b as i32)));
+        }
+        else {
+            exprs.push(parse_expr(number, arg)?);
+        }
+    }
+
+    Ok(exprs)
+}
+
+/// Split a comma separated list, ignoring commas inside quotes
+fn split_list(text: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, ',') => {
+                items.push(text[start..i].trim());
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+
+    let last = text[start..].trim();
+    if !last.is_empty() || !items.is_empty() {
+        items.push(last);
+    }
+
+    items
+}
+
+fn strip_comment(text: &str) -> &str {
+    let mut quote: Option<char> = None;
+
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, ';') => return &text[..i],
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            _ => {},
+        }
+    }
+
+    text
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn parse_expr(number: usize, text: &str) -> Result<Expr, AssemblerError> {
+    let mut parser = ExprParser { chars: text.trim().chars().collect(), pos: 0 };
+    let invalid = || AssemblerError::InvalidOperand(number, text.trim().to_string());
+
+    let expr = parser.expr().ok_or_else(invalid)?;
+    if parser.pos != parser.chars.len() {
+        return Err(invalid());
+    }
+
+    Ok(expr)
+}
+
+/// Recursive descent parser for operand expressions
+///
+/// ```text
+/// expr   := term (('+' | '-') term)*
+/// term   := factor (('*' | '/') factor)*
+/// factor := ('-' | '<' | '>') factor | number | 'c' | symbol | '*'
+/// ```
+struct ExprParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expr(&mut self) -> Option<Expr> {
+        let mut lhs = self.term()?;
+
+        loop {
+            self.skip_whitespace();
+            let op = match self.peek() {
+                Some('+') => BinaryOp::Add,
+                Some('-') => BinaryOp::Sub,
+                _ => return Some(lhs),
+            };
+
+            self.pos += 1;
+            let rhs = self.term()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn term(&mut self) -> Option<Expr> {
+        let mut lhs = self.factor()?;
+
+        loop {
+            self.skip_whitespace();
+            let op = match self.peek() {
+                Some('*') => BinaryOp::Mul,
+                Some('/') => BinaryOp::Div,
+                _ => return Some(lhs),
+            };
+
+            self.pos += 1;
+            let rhs = self.factor()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn factor(&mut self) -> Option<Expr> {
+        self.skip_whitespace();
+        let c = self.peek()?;
+        self.pos += 1;
+
+        match c {
+            '-' => self.factor().map(|e| Expr::Negate(Box::new(e))),
+            '<' => self.factor().map(|e| Expr::LowByte(Box::new(e))),
+            '>' => self.factor().map(|e| Expr::HighByte(Box::new(e))),
+            '*' => Some(Expr::Pc),
+            '$' => self.number(16),
+            '%' => self.number(2),
+            '\'' => {
+                let value = self.peek()?;
+                if self.chars.get(self.pos + 1) != Some(&'\'') {
+                    return None;
+                }
+                self.pos += 2;
+                Some(Expr::Number(value as i32))
+            },
+            '0'..='9' => {
+                self.pos -= 1;
+                self.number(10)
+            },
+            c if is_ident_char(c) => {
+                let start = self.pos - 1;
+                while self.peek().is_some_and(is_ident_char) {
+                    self.pos += 1;
+                }
+                Some(Expr::Symbol(self.chars[start..self.pos].iter().collect()))
+            },
+            _ => None,
+        }
+    }
+
+    fn number(&mut self, radix: u32) -> Option<Expr> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_digit(radix)) {
+            self.pos += 1;
+        }
+
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        i32::from_str_radix(&digits, radix).ok().map(Expr::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn implied_and_accumulator() {
+        let bin = assemble("
+            NOP
+            ASL
+            LSR A
+            BRK
+        ").unwrap();
+
+        assert_eq!(bin, vec![0xEA, 0x0A, 0x4A, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn addressing_modes() {
+        let bin = assemble("
+            LDA #$10
+            LDA $10
+            LDA $10,X
+            LDX $10,Y
+            LDA $1234
+            LDA $1234,X
+            LDA $1234,Y
+            LDA ($10,X)
+            LDA ($10),Y
+            JMP ($1234)
+        ").unwrap();
+
+        assert_eq!(bin, vec![
+            0xA9, 0x10,
+            0xA5, 0x10,
+            0xB5, 0x10,
+            0xB6, 0x10,
+            0xAD, 0x34, 0x12,
+            0xBD, 0x34, 0x12,
+            0xB9, 0x34, 0x12,
+            0xA1, 0x10,
+            0xB1, 0x10,
+            0x6C, 0x34, 0x12,
+        ]);
+    }
+
+    #[test]
+    fn zero_page_address_without_zero_page_mode() {
+        // There is no LDA zp,Y
+        let bin = assemble("LDA $10,Y").unwrap();
+        assert_eq!(bin, vec![0xB9, 0x10, 0x00]);
+    }
+
+    #[test]
+    fn labels_and_branches() {
+        let bin = assemble("
+                .org $C000
+            start:
+                BNE forward
+                JMP start
+            forward:
+                BEQ start
+        ").unwrap();
+
+        assert_eq!(bin, vec![
+            0xD0, 0x03,
+            0x4C, 0x00, 0xC0,
+            0xF0, 0xF9,
+        ]);
+    }
+
+    #[test]
+    fn forward_reference_uses_absolute() {
+        let bin = assemble("
+                LDA value
+                RTS
+            value = $10
+        ");
+
+        // `value` is not known in the first pass, so it is assumed to be 16-bit
+        assert_eq!(bin.unwrap(), vec![0xAD, 0x10, 0x00, 0x60]);
+    }
+
+    #[test]
+    fn expressions() {
+        let bin = assemble("
+            PPUADDR = $2006
+                .org $8000
+            table:
+                LDA #>table
+                LDA #<table+1
+                STA PPUADDR+1
+                LDA #%1010
+                LDA #'A'
+                LDA #2*3-1
+                LDA #-1
+                JMP *
+        ").unwrap();
+
+        assert_eq!(bin, vec![
+            0xA9, 0x80,
+            0xA9, 0x01,
+            0x8D, 0x07, 0x20,
+            0xA9, 0x0A,
+            0xA9, 0x41,
+            0xA9, 0x05,
+            0xA9, 0xFF,
+            0x4C, 0x0F, 0x80,
+        ]);
+    }
+
+    #[test]
+    fn data_directives() {
+        let bin = assemble("
+                .org $8000
+                .byte $01, 2, \"AB\"
+                .word $1234, label
+            label:
+                .org $8010
+                .db $FF ; comment
+        ").unwrap();
+
+        let mut expected = vec![0x01, 0x02, 0x41, 0x42, 0x34, 0x12, 0x08, 0x80];
+        expected.resize(0x10, 0x00);
+        expected.push(0xFF);
+
+        assert_eq!(bin, expected);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(assemble("FOO"), Err(AssemblerError::UnknownInstruction(1, String::from("FOO"))));
+        assert_eq!(assemble("JMP nowhere"), Err(AssemblerError::UndefinedSymbol(1, String::from("nowhere"))));
+        assert_eq!(assemble("a:\na:"), Err(AssemblerError::DuplicateSymbol(2, String::from("a"))));
+        assert_eq!(assemble("STA #$10"), Err(AssemblerError::InvalidAddressingMode(1, Instruction::STA)));
+        assert_eq!(assemble("LDA #$100"), Err(AssemblerError::ValueOutOfRange(1, 0x100)));
+        assert_eq!(assemble(".org $9000\nNOP\n.org $8000\nNOP"), Err(AssemblerError::OriginMovedBackwards(4, 0x8000)));
+        assert_eq!(assemble(".bank 1"), Err(AssemblerError::UnknownDirective(1, String::from(".bank"))));
+
+        assert_eq!(assemble("loop:\n.org $8100\nBNE loop"), Err(AssemblerError::BranchOutOfRange(3, -0x102)));
+
+        // Arithmetic wraps instead of overflowing
+        assert_eq!(assemble("MIN = $7FFFFFFF+1\nLDA #-MIN"), Err(AssemblerError::ValueOutOfRange(2, i32::MIN)));
+    }
+
+    #[test]
+    fn nrom_cartridge() {
+        let cart = assemble_cart("
+                .org $C000
+            reset:
+                JMP reset
+        ", &[0xAA]).unwrap();
+
+        let (info, prg_rom, chr_rom, _) = cart.into_parts();
+
+        assert_eq!(info.mapper, 0);
+        assert_eq!(info.prg_rom_banks, 1);
+        assert_eq!(&prg_rom[..3], &[0x4C, 0x00, 0xC0]);
+        // Reset vector
+        assert_eq!(&prg_rom[0x3FFC..0x3FFE], &[0x00, 0xC0]);
+        assert_eq!(chr_rom.len(), CHR_ROM_BANK_SIZE);
+        assert_eq!(chr_rom[0], 0xAA);
+    }
+
+    #[test]
+    fn nrom_cartridge_with_vectors() {
+        let cart = assemble_cart("
+                .org $8000
+            reset:
+            nmi:
+                RTI
+                .org $FFFA
+                .word nmi, reset, 0
+        ", &[]).unwrap();
+
+        let (info, prg_rom, _, _) = cart.into_parts();
+
+        assert_eq!(info.prg_rom_banks, 2);
+        assert_eq!(&prg_rom[0x7FFA..], &[0x00, 0x80, 0x00, 0x80, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn nrom_cartridge_outside_prg_space() {
+        assert!(matches!(assemble_cart(".org $6000\nNOP", &[]), Err(AssemblerError::InvalidCartridgeLayout(0x6000, 1))));
+    }
+}
@@ -0,0 +1,822 @@
+//
+// asm/disasm.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::{Instruction, AddressingMode, Opcode, SymbolTable, lookup};
+use crate::cart::{CartridgeInfo, PRG_ROM_BANK_SIZE};
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Write;
+use std::ops::Range;
+
+const NMI_VECTOR: u16 = 0xFFFA;
+const RESET_VECTOR: u16 = 0xFFFC;
+const IRQ_VECTOR: u16 = 0xFFFE;
+
+/// AxROM switches the whole 32KB PRG window
+const MAPPER_AXROM: usize = 7;
+
+const BYTES_PER_DATA_LINE: usize = 8;
+
+/// Names of the memory mapped PPU, APU and IO registers
+const HARDWARE_REGISTERS: [(u16, &str); 29] = [
+    (0x2000, "PPUCTRL"),
+    (0x2001, "PPUMASK"),
+    (0x2002, "PPUSTATUS"),
+    (0x2003, "OAMADDR"),
+    (0x2004, "OAMDATA"),
+    (0x2005, "PPUSCROLL"),
+    (0x2006, "PPUADDR"),
+    (0x2007, "PPUDATA"),
+    (0x4000, "SQ1_VOL"),
+    (0x4001, "SQ1_SWEEP"),
+    (0x4002, "SQ1_LO"),
+    (0x4003, "SQ1_HI"),
+    (0x4004, "SQ2_VOL"),
+    (0x4005, "SQ2_SWEEP"),
+    (0x4006, "SQ2_LO"),
+    (0x4007, "SQ2_HI"),
+    (0x4008, "TRI_LINEAR"),
+    (0x400A, "TRI_LO"),
+    (0x400B, "TRI_HI"),
+    (0x400C, "NOISE_VOL"),
+    (0x400E, "NOISE_LO"),
+    (0x400F, "NOISE_HI"),
+    (0x4010, "DMC_FREQ"),
+    (0x4011, "DMC_RAW"),
+    (0x4012, "DMC_START"),
+    (0x4013, "DMC_LEN"),
+    (0x4014, "OAMDMA"),
+    (0x4015, "SND_CHN"),
+    (0x4016, "JOY1"),
+];
+
+/// $4017 is a read from the second controller and a write to the frame counter
+const JOY2: (u16, &str) = (0x4017, "JOY2");
+
+fn hardware_register(addr: u16) -> Option<&'static str> {
+    HARDWARE_REGISTERS.iter().chain(std::iter::once(&JOY2)).find(|(a, _)| *a == addr).map(|(_, name)| *name)
+}
+
+/// Classification of a byte in a PRG bank
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Mark {
+    Data,
+    Opcode,
+    Operand,
+}
+
+/// A PRG bank and the CPU address it is mapped at
+struct Bank<'a> {
+    data: &'a [u8],
+    base: u16,
+    mirrored: bool, // 16KB bank mirrored across $8000-$FFFF
+    marks: Vec<Mark>,
+}
+
+impl<'a> Bank<'a> {
+    fn new(data: &'a [u8], base: u16, mirrored: bool) -> Self {
+        Bank {
+            data,
+            base,
+            mirrored,
+            marks: vec![Mark::Data; data.len()],
+        }
+    }
+
+    /// Offset into the bank for the given CPU address, if the bank is mapped there
+    fn offset(&self, addr: u16) -> Option<usize> {
+        if self.mirrored {
+            if addr >= 0x8000 { Some((addr as usize) & (PRG_ROM_BANK_SIZE - 1)) } else { None }
+        }
+        else {
+            let base = self.base as usize;
+            let addr = addr as usize;
+            if addr >= base && addr < base + self.data.len() { Some(addr - base) } else { None }
+        }
+    }
+
+    fn address(&self, offset: usize) -> u16 {
+        self.base + offset as u16
+    }
+
+    fn contains_vectors(&self) -> bool {
+        self.offset(NMI_VECTOR).is_some()
+    }
+
+    fn read_word(&self, offset: usize) -> u16 {
+        ((self.data[offset + 1] as u16) << 8) | (self.data[offset] as u16)
+    }
+}
+
+/// Static disassembler state
+struct Disassembler<'a> {
+    banks: Vec<Bank<'a>>,
+    labels: BTreeMap<(usize, usize), String>,
+    symbols: Option<&'a SymbolTable>,
+    ram_labels: BTreeMap<u16, String>,  // Symbols outside of PRG ROM
+    used_names: HashSet<String>,
+    banked_targets: BTreeSet<u16>, // Jumps and calls into the switchable bank window that could not be resolved
+}
+
+/// Disassemble the PRG ROM of a cartridge into ca65 source
+///
+/// Code is traced recursively from the reset, NMI and IRQ vectors. Jump and branch targets are given labels and
+/// everything that is not reached is emitted as data. The bank switched into $8000-$BFFF cannot be known statically, so
+/// each switchable bank is traced from every jump and call the fixed bank makes into that window. Banks where no code
+/// was found are marked with a comment.
+///
+/// ```
+/// # use nescore::asm::{assemble_cart, disassemble_rom};
+/// let cart = assemble_cart("
+///     .org $C000
+/// loop:
+///     LDA $2002
+///     JMP loop
+/// ", &[]).unwrap();
+///
+/// let (info, prg_rom, _, _) = cart.into_parts();
+/// let source = disassemble_rom(&info, &prg_rom);
+///
+/// assert!(source.contains("LDA PPUSTATUS"));
+/// assert!(source.contains("JMP reset"));
+/// ```
+pub fn disassemble_rom(info: &CartridgeInfo, prg_rom: &[u8]) -> String {
+    let mut disassembler = Disassembler::new(info, prg_rom, None);
+    disassembler.trace_vectors();
+    disassembler.trace_switchable_banks();
+    disassembler.emit(info)
+}
+
+/// Disassemble the PRG ROM of a cartridge into ca65 source, naming labels and RAM addresses from the symbol table
+pub fn disassemble_rom_with_symbols(info: &CartridgeInfo, prg_rom: &[u8], symbols: &SymbolTable) -> String {
+    let mut disassembler = Disassembler::new(info, prg_rom, Some(symbols));
+    disassembler.trace_vectors();
+    disassembler.trace_switchable_banks();
+    disassembler.emit(info)
+}
+
+/// ld65 linker configuration for the output of `disassemble_rom`
+///
+/// Each bank is placed in its own memory area, so linking the assembled source produces the PRG ROM.
+/// ```text
+/// ca65 prg.s -o prg.o
+/// ld65 -C prg.cfg prg.o -o prg.bin
+/// ```
+pub fn linker_config(info: &CartridgeInfo, prg_rom: &[u8]) -> String {
+    let disassembler = Disassembler::new(info, prg_rom, None);
+    let mut out = String::new();
+
+    writeln!(out, "MEMORY {{").unwrap();
+    for (index, bank) in disassembler.banks.iter().enumerate() {
+        writeln!(out, "    PRG{}: start = ${:04X}, size = ${:04X}, type = ro, file = %O, fill = yes;", index, bank.base, bank.data.len()).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    writeln!(out, "SEGMENTS {{").unwrap();
+    for index in 0..disassembler.banks.len() {
+        writeln!(out, "    PRG{0}: load = PRG{0}, type = ro;", index).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    out
+}
+
+impl<'a> Disassembler<'a> {
+    fn new(info: &CartridgeInfo, prg_rom: &'a [u8], symbols: Option<&'a SymbolTable>) -> Self {
+        let num_banks = (prg_rom.len() / PRG_ROM_BANK_SIZE).min(info.prg_rom_banks);
+        let prg_rom = &prg_rom[..num_banks * PRG_ROM_BANK_SIZE];
+
+        let banks = if num_banks == 1 {
+            // NROM-128: The single bank is mirrored in $8000-$BFFF and $C000-$FFFF
+            vec![Bank::new(prg_rom, 0xC000, true)]
+        }
+        else if num_banks == 2 || info.mapper == MAPPER_AXROM {
+            // One or more 32KB banks mapped at $8000
+            prg_rom.chunks(PRG_ROM_BANK_SIZE * 2).map(|data| Bank::new(data, 0x8000, false)).collect()
+        }
+        else {
+            // Switchable 16KB banks at $8000 with the last bank fixed at $C000. PRG ROM smaller than a bank has none
+            let last = num_banks.saturating_sub(1);
+            prg_rom.chunks(PRG_ROM_BANK_SIZE)
+                   .enumerate()
+                   .map(|(i, data)| Bank::new(data, if i == last { 0xC000 } else { 0x8000 }, false))
+                   .collect()
+        };
+
+        // Names for RAM and other addresses outside PRG ROM. These are defined at the top of the output
+        let mut used_names = HashSet::new();
+        let mut ram_labels = BTreeMap::new();
+
+        if let Some(symbols) = symbols {
+            let mut unbanked: Vec<(u16, &str)> = symbols.unbanked_labels().filter(|&(addr, _)| addr < 0x8000).collect();
+            unbanked.sort();
+
+            for (addr, name) in unbanked {
+                if hardware_register(addr).is_none() && is_valid_name(name) && used_names.insert(name.to_string()) {
+                    ram_labels.insert(addr, name.to_string());
+                }
+            }
+        }
+
+        Disassembler {
+            banks,
+            labels: BTreeMap::new(),
+            symbols,
+            ram_labels,
+            used_names,
+            banked_targets: BTreeSet::new(),
+        }
+    }
+
+    /// The 16KB PRG ROM bank number of a location
+    fn prg_bank(&self, (bank, offset): (usize, usize)) -> usize {
+        if self.banks[bank].data.len() > PRG_ROM_BANK_SIZE {
+            bank * 2 + offset / PRG_ROM_BANK_SIZE
+        }
+        else {
+            bank
+        }
+    }
+
+    /// Add a label for a location, preferring the name from the symbol table
+    fn add_label(&mut self, location: (usize, usize), default: String) {
+        if self.labels.contains_key(&location) {
+            return;
+        }
+
+        let addr = self.banks[location.0].address(location.1);
+        let symbol = self.symbols.and_then(|symbols| symbols.label(Some(self.prg_bank(location)), addr))
+                                 .filter(|name| is_valid_name(name) && !self.used_names.contains(*name))
+                                 .map(String::from);
+
+        // Generated names may already be taken by a symbol elsewhere
+        let name = symbol.unwrap_or_else(|| {
+            if self.used_names.contains(&default) {
+                (1..).map(|i| format!("{}_{}", default, i)).find(|name| !self.used_names.contains(name)).unwrap()
+            }
+            else {
+                default
+            }
+        });
+
+        self.used_names.insert(name.clone());
+        self.labels.insert(location, name);
+    }
+
+    /// Index of the bank that is always mapped at $C000-$FFFF, if any
+    fn fixed_bank(&self) -> Option<usize> {
+        match self.banks.first() {
+            Some(first) if self.banks.len() == 1 || first.data.len() == PRG_ROM_BANK_SIZE => Some(self.banks.len() - 1),
+            _ => None,
+        }
+    }
+
+    /// Find the bank and offset of an address referenced from the given bank
+    fn resolve(&self, bank: usize, addr: u16) -> Option<(usize, usize)> {
+        self.banks[bank].offset(addr).map(|offset| (bank, offset)).or_else(|| {
+            self.fixed_bank().and_then(|fixed| self.banks[fixed].offset(addr).map(|offset| (fixed, offset)))
+        })
+    }
+
+    fn trace_vectors(&mut self) {
+        let vector_banks: Vec<usize> = (0..self.banks.len()).filter(|&i| self.banks[i].contains_vectors()).collect();
+        let suffix = vector_banks.len() > 1;
+
+        for bank in vector_banks {
+            for &(vector, name) in [(RESET_VECTOR, "reset"), (NMI_VECTOR, "nmi"), (IRQ_VECTOR, "irq")].iter() {
+                let offset = self.banks[bank].offset(vector).unwrap();
+                let addr = self.banks[bank].read_word(offset);
+
+                if let Some(target) = self.resolve(bank, addr) {
+                    let name = if suffix { format!("{}_{}", name, bank) } else { String::from(name) };
+                    // Vectors commonly share a handler. Keep the first name
+                    self.add_label(target, name);
+                    self.trace(target);
+                }
+            }
+        }
+    }
+
+    /// Trace each switchable bank from the jumps and calls into $8000-$BFFF that could not be resolved. Code found in
+    /// the switchable banks can call back into the fixed bank, so this repeats until no new targets are found
+    fn trace_switchable_banks(&mut self) {
+        let fixed = match self.fixed_bank() {
+            Some(fixed) => fixed,
+            None => return,
+        };
+
+        let mut traced = BTreeSet::new();
+
+        while let Some(addr) = self.banked_targets.difference(&traced).next().copied() {
+            traced.insert(addr);
+
+            for bank in (0..self.banks.len()).filter(|&bank| bank != fixed) {
+                if let Some(offset) = self.banks[bank].offset(addr) {
+                    // A target that is a BRK is most likely data in this bank
+                    if lookup(self.banks[bank].data[offset]).instr == Instruction::BRK {
+                        continue;
+                    }
+
+                    let name = self.label_name((bank, offset));
+                    self.add_label((bank, offset), name);
+                    self.trace((bank, offset));
+                }
+            }
+        }
+    }
+
+    /// Recursively trace code starting at the given location
+    fn trace(&mut self, start: (usize, usize)) {
+        let mut pending = vec![start];
+
+        while let Some((bank, mut offset)) = pending.pop() {
+            loop {
+                let len = self.banks[bank].data.len();
+                if offset >= len || self.banks[bank].marks[offset] != Mark::Data {
+                    break;
+                }
+
+                let opcode = lookup(self.banks[bank].data[offset]);
+
+                // Stop if the instruction runs off the end of the bank, overlaps traced code or would lock up the CPU
+                let end = offset + opcode.len;
+                if end > len || self.banks[bank].marks[offset..end].iter().any(|&m| m != Mark::Data) || opcode.instr == Instruction::JAM {
+                    break;
+                }
+
+                self.banks[bank].marks[offset] = Mark::Opcode;
+                for mark in &mut self.banks[bank].marks[offset+1..end] {
+                    *mark = Mark::Operand;
+                }
+
+                if let Some(target) = self.target(bank, offset, opcode) {
+                    if let Some(location) = self.resolve(bank, target) {
+                        let name = self.label_name(location);
+                        self.add_label(location, name);
+
+                        if is_control_flow(opcode) {
+                            pending.push(location);
+                        }
+                    }
+                    else if is_control_flow(opcode) && (0x8000..0xC000).contains(&target) {
+                        self.banked_targets.insert(target);
+                    }
+                }
+
+                if ends_flow(opcode) {
+                    break;
+                }
+
+                offset = end;
+            }
+        }
+    }
+
+    /// The address referenced by an instruction's operand, if it can be determined statically
+    fn target(&self, bank: usize, offset: usize, opcode: &Opcode) -> Option<u16> {
+        let bank = &self.banks[bank];
+
+        match opcode.mode {
+            AddressingMode::Relative => {
+                let pc = bank.address(offset).wrapping_add(2);
+                Some(pc.wrapping_add(bank.data[offset + 1] as i8 as u16))
+            },
+            AddressingMode::Absolute | AddressingMode::AbsoluteX | AddressingMode::AbsoluteY | AddressingMode::Indirect => {
+                Some(bank.read_word(offset + 1))
+            },
+            _ => None,
+        }
+    }
+
+    fn label_name(&self, (bank, offset): (usize, usize)) -> String {
+        let addr = self.banks[bank].address(offset);
+        if self.banks.len() == 1 {
+            format!("L{:04X}", addr)
+        }
+        else {
+            format!("B{}_{:04X}", bank, addr)
+        }
+    }
+
+    /// The label expression for a referenced location
+    fn label_at(&self, bank: usize, addr: u16) -> Option<String> {
+        self.resolve(bank, addr).and_then(|(bank, offset)| {
+            self.labels.get(&(bank, offset)).map(|label| {
+                // Labels in a mirrored bank are at $C000-$FFFF. Keep the original address of references to the mirror
+                let bank = &self.banks[bank];
+                if bank.mirrored && addr < bank.base {
+                    format!("{}-${:04X}", label, bank.address(offset) - addr)
+                }
+                else {
+                    label.clone()
+                }
+            })
+        })
+    }
+
+    //
+    // Output
+    //
+
+    fn emit(&self, info: &CartridgeInfo) -> String {
+        let mut out = String::new();
+
+        writeln!(out, "; PRG ROM disassembly").unwrap();
+        writeln!(out, "; Mapper {}, {} PRG ROM bank(s)", info.mapper, info.prg_rom_banks).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, ".setcpu \"6502\"").unwrap();
+        writeln!(out).unwrap();
+
+        for (addr, name) in HARDWARE_REGISTERS.iter().chain(std::iter::once(&JOY2)) {
+            writeln!(out, "{:<12}= ${:04X}", name, addr).unwrap();
+        }
+
+        if !self.ram_labels.is_empty() {
+            writeln!(out).unwrap();
+            for (addr, name) in &self.ram_labels {
+                writeln!(out, "{:<12}= ${:04X}", name, addr).unwrap();
+            }
+        }
+
+        for (index, bank) in self.banks.iter().enumerate() {
+            writeln!(out).unwrap();
+            writeln!(out, ".segment \"PRG{}\"", index).unwrap();
+            writeln!(out, ".org ${:04X}", bank.base).unwrap();
+            writeln!(out).unwrap();
+
+            if bank.marks.iter().all(|&m| m == Mark::Data) {
+                writeln!(out, "; No code was traced in this bank").unwrap();
+                writeln!(out).unwrap();
+            }
+
+            self.emit_bank(&mut out, index);
+        }
+
+        out
+    }
+
+    fn emit_bank(&self, out: &mut String, index: usize) {
+        let bank = &self.banks[index];
+        let vectors = bank.offset(NMI_VECTOR).filter(|&offset| bank.marks[offset..].iter().all(|&m| m == Mark::Data));
+
+        let mut offset = 0;
+        let mut data: Vec<u8> = vec![];
+
+        while offset < bank.data.len() {
+            let label = self.labels.get(&(index, offset));
+
+            // Flush pending data before a label, the vectors or code
+            if !data.is_empty() && (label.is_some() || Some(offset) == vectors || bank.marks[offset] != Mark::Data || data.len() == BYTES_PER_DATA_LINE) {
+                emit_data(out, &data);
+                data.clear();
+            }
+
+            if let Some(label) = label {
+                writeln!(out, "{}:", label).unwrap();
+            }
+
+            if Some(offset) == vectors {
+                let words: Vec<String> = (0..3).map(|i| self.format_address(index, bank.read_word(offset + i * 2))).collect();
+                writeln!(out, "    .word {}", words.join(", ")).unwrap();
+                self.emit_inner_labels(out, index, offset + 1..offset + 6);
+                offset += 6;
+            }
+            else if bank.marks[offset] == Mark::Opcode {
+                let opcode = lookup(bank.data[offset]);
+                let bytes = &bank.data[offset..offset + opcode.len];
+                let text = self.format_instruction(index, offset, opcode, bytes);
+                let addr = format!("; ${:04X}", bank.address(offset));
+
+                if opcode.official && opcode.instr != Instruction::BRK {
+                    writeln!(out, "    {:<24}{}", text, addr).unwrap();
+                }
+                else {
+                    // Emit bytes directly so the instruction length is preserved when reassembling
+                    let bytes: Vec<String> = bytes.iter().map(|b| format!("${:02X}", b)).collect();
+                    writeln!(out, "    {:<24}{} {}", format!(".byte {}", bytes.join(", ")), addr, text).unwrap();
+                }
+
+                self.emit_inner_labels(out, index, offset + 1..offset + opcode.len);
+                offset += opcode.len;
+            }
+            else {
+                data.push(bank.data[offset]);
+                offset += 1;
+            }
+        }
+
+        if !data.is_empty() {
+            emit_data(out, &data);
+        }
+    }
+
+    /// Labels inside an instruction's operand or the vectors cannot start a line, so they are defined as constants
+    fn emit_inner_labels(&self, out: &mut String, index: usize, offsets: Range<usize>) {
+        for offset in offsets {
+            if let Some(label) = self.labels.get(&(index, offset)) {
+                writeln!(out, "{} = ${:04X}", label, self.banks[index].address(offset)).unwrap();
+            }
+        }
+    }
+
+    fn format_address(&self, bank: usize, addr: u16) -> String {
+        self.label_at(bank, addr)
+                                 .or_else(|| hardware_register(addr).map(String::from))
+                                 .or_else(|| self.ram_labels.get(&addr).cloned())
+                                 .unwrap_or_else(|| format!("${:04X}", addr))
+    }
+
+    fn format_instruction(&self, bank: usize, offset: usize, opcode: &Opcode, bytes: &[u8]) -> String {
+        let instr = opcode.instr;
+
+        match opcode.mode {
+            AddressingMode::Accumulator     => format!("{} A", instr),
+            AddressingMode::Implied         => format!("{}", instr),
+            AddressingMode::Immediate       => format!("{} #${:02X}", instr, bytes[1]),
+            AddressingMode::ZeroPage        => format!("{} {}", instr, self.format_zeropage(bytes[1])),
+            AddressingMode::ZeroPageX       => format!("{} {},X", instr, self.format_zeropage(bytes[1])),
+            AddressingMode::ZeroPageY       => format!("{} {},Y", instr, self.format_zeropage(bytes[1])),
+            AddressingMode::IndexedIndirect => format!("{} ({},X)", instr, self.format_zeropage(bytes[1])),
+            AddressingMode::IndirectIndexed => format!("{} ({}),Y", instr, self.format_zeropage(bytes[1])),
+            AddressingMode::Relative        => {
+                let target = self.target(bank, offset, opcode).unwrap();
+                format!("{} {}", instr, self.format_address(bank, target))
+            },
+            AddressingMode::Absolute        => format!("{} {}", instr, self.format_absolute(bank, bytes)),
+            AddressingMode::AbsoluteX       => format!("{} {},X", instr, self.format_absolute(bank, bytes)),
+            AddressingMode::AbsoluteY       => format!("{} {},Y", instr, self.format_absolute(bank, bytes)),
+            AddressingMode::Indirect        => format!("{} ({})", instr, self.format_absolute(bank, bytes)),
+        }
+    }
+
+    fn format_zeropage(&self, addr: u8) -> String {
+        self.ram_labels.get(&(addr as u16)).cloned().unwrap_or_else(|| format!("${:02X}", addr))
+    }
+
+    fn format_absolute(&self, bank: usize, bytes: &[u8]) -> String {
+        let addr = ((bytes[2] as u16) << 8) | (bytes[1] as u16);
+        if addr < 0x100 {
+            // Force absolute addressing so ca65 does not shorten the instruction to zero page
+            let name = self.ram_labels.get(&addr).cloned().unwrap_or_else(|| format!("${:04X}", addr));
+            format!("a:{}", name)
+        }
+        else {
+            self.format_address(bank, addr)
+        }
+    }
+}
+
+/// Name can be used as a ca65 identifier
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().map(|c| c.is_ascii_alphabetic() || c == '_').unwrap_or(false)
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn emit_data(out: &mut String, data: &[u8]) {
+    let bytes: Vec<String> = data.iter().map(|b| format!("${:02X}", b)).collect();
+    writeln!(out, "    .byte {}", bytes.join(", ")).unwrap();
+}
+
+/// Instruction transfers control to its operand
+fn is_control_flow(opcode: &Opcode) -> bool {
+    match opcode.instr {
+        Instruction::JMP => opcode.mode == AddressingMode::Absolute,
+        Instruction::JSR => true,
+        _ => opcode.mode == AddressingMode::Relative,
+    }
+}
+
+/// Execution does not continue with the next instruction
+fn ends_flow(opcode: &Opcode) -> bool {
+    matches!(opcode.instr, Instruction::JMP | Instruction::RTS | Instruction::RTI | Instruction::BRK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::assemble_cart;
+
+    fn disassemble(source: &str) -> String {
+        let (info, prg_rom, _, _) = assemble_cart(source, &[]).unwrap().into_parts();
+        disassemble_rom(&info, &prg_rom)
+    }
+
+    fn lines(source: &str) -> Vec<String> {
+        source.lines().map(|l| l.split(';').next().unwrap().trim().to_string()).filter(|l| !l.is_empty()).collect()
+    }
+
+    #[test]
+    fn labels_for_jumps_and_branches() {
+        let out = lines(&disassemble("
+                .org $C000
+                LDX #$00
+            loop:
+                DEX
+                BNE loop
+                JSR sub
+                JMP $C000
+            sub:
+                RTS
+        "));
+
+        let start = out.iter().position(|l| l == "reset:").unwrap();
+        assert_eq!(&out[start..start + 9], &[
+            "reset:", "LDX #$00",
+            "LC002:", "DEX", "BNE LC002",
+            "JSR LC00B",
+            "JMP reset",
+            "LC00B:", "RTS",
+        ]);
+    }
+
+    #[test]
+    fn untraced_bytes_are_data() {
+        let out = lines(&disassemble("
+                .org $C000
+                LDA table,X
+                RTS
+            table:
+                .byte $01, $02, $03
+        "));
+
+        assert!(out.contains(&String::from("LDA LC004,X")));
+        let table = out.iter().position(|l| l == "LC004:").unwrap();
+        assert_eq!(out[table + 1], ".byte $01, $02, $03, $00, $00, $00, $00, $00");
+    }
+
+    #[test]
+    fn hardware_registers() {
+        let out = lines(&disassemble("
+                .org $C000
+                LDA $2002
+                STA $4014
+                STA $2008
+                .byte $AD, $10, $00 ; LDA $0010
+                RTS
+        "));
+
+        assert!(out.contains(&String::from("PPUCTRL     = $2000")));
+        assert!(out.contains(&String::from("LDA PPUSTATUS")));
+        assert!(out.contains(&String::from("STA OAMDMA")));
+        assert!(out.contains(&String::from("STA $2008")));
+        assert!(out.contains(&String::from("LDA a:$0010")));
+    }
+
+    #[test]
+    fn vectors() {
+        let out = lines(&disassemble("
+                .org $8000
+            reset:
+                JMP reset
+            nmi:
+                RTI
+                .org $FFFA
+                .word nmi, reset, nmi
+        "));
+
+        assert!(out.contains(&String::from(".org $8000")));
+        assert!(out.contains(&String::from(".word nmi, reset, nmi")));
+    }
+
+    #[test]
+    fn mirrored_references_keep_their_address() {
+        let out = lines(&disassemble("
+                .org $C000
+                JMP $8003
+                LDA $8001
+                LDA $FFFC
+                RTS
+        "));
+
+        let start = out.iter().position(|l| l == "reset:").unwrap();
+        assert_eq!(&out[start..start + 7], &[
+            "reset:", "JMP LC003-$4000",
+            "LC001 = $C001",
+            "LC003:", "LDA LC001-$4000",
+            "LDA LFFFC", "RTS",
+        ]);
+
+        // Labels inside the vectors are defined after them
+        let vectors = out.iter().position(|l| l.starts_with(".word")).unwrap();
+        assert_eq!(out[vectors + 1], "LFFFC = $FFFC");
+    }
+
+    #[test]
+    fn ld65_config() {
+        let (info, prg_rom, _, _) = assemble_cart(".org $C000\nRTS", &[]).unwrap().into_parts();
+
+        assert_eq!(linker_config(&info, &prg_rom), "\
+MEMORY {
+    PRG0: start = $C000, size = $4000, type = ro, file = %O, fill = yes;
+}
+SEGMENTS {
+    PRG0: load = PRG0, type = ro;
+}
+");
+    }
+
+    #[test]
+    fn unofficial_opcodes_emitted_as_bytes() {
+        let out = disassemble("
+                .org $C000
+                .byte $A7, $10
+                RTS
+        ");
+
+        assert!(out.lines().any(|l| l.trim().starts_with(".byte $A7, $10") && l.contains("LAX $10")));
+    }
+
+    #[test]
+    fn symbols() {
+        let (info, prg_rom, _, _) = assemble_cart("
+                .org $C000
+                LDA $10
+                STA $0300
+                JSR $C009
+                RTS
+                RTS
+        ", &[]).unwrap().into_parts();
+
+        let mut symbols = SymbolTable::from_nl("$C000#Start#\n$C009#UpdatePlayer#\n", Some(0)).unwrap();
+        symbols.merge(SymbolTable::from_nl("$0010#frame#\n$0300#player_x#\n$2000#ppu#\n", None).unwrap());
+
+        let out = lines(&disassemble_rom_with_symbols(&info, &prg_rom, &symbols));
+
+        let start = out.iter().position(|l| l == "Start:").unwrap();
+        assert_eq!(&out[start..start + 7], &["Start:", "LDA frame", "STA player_x", "JSR UpdatePlayer", "RTS", "UpdatePlayer:", "RTS"]);
+        assert!(out.contains(&String::from("frame       = $0010")));
+        assert!(out.contains(&String::from("player_x    = $0300")));
+        // Hardware register names take priority
+        assert!(!out.contains(&String::from("ppu         = $2000")));
+    }
+
+    #[test]
+    fn symbols_do_not_collide_with_generated_names() {
+        let (info, prg_rom, _, _) = assemble_cart("
+                .org $C000
+                JSR $C007
+                JSR $C008
+                RTS
+                RTS
+                RTS
+        ", &[]).unwrap().into_parts();
+
+        // The reset handler and $C008 are not named by the symbols, but their generated names are
+        let mut symbols = SymbolTable::from_nl("$C007#LC008#\n", Some(0)).unwrap();
+        symbols.merge(SymbolTable::from_nl("$0010#reset#\n", None).unwrap());
+
+        let out = lines(&disassemble_rom_with_symbols(&info, &prg_rom, &symbols));
+
+        let start = out.iter().position(|l| l == "reset_1:").unwrap();
+        assert_eq!(&out[start..start + 9], &["reset_1:", "JSR LC008", "JSR LC008_1", "RTS", "LC008:", "RTS", "LC008_1:", "RTS", ".byte $00, $00, $00, $00, $00, $00, $00, $00"]);
+        assert!(out.contains(&String::from("reset       = $0010")));
+    }
+
+    #[test]
+    fn switchable_banks() {
+        let mut prg_rom = vec![0u8; PRG_ROM_BANK_SIZE * 4];
+        // Fixed bank: JSR $8000 ; JMP $C000
+        let fixed = PRG_ROM_BANK_SIZE * 3;
+        prg_rom[fixed..fixed + 6].copy_from_slice(&[0x20, 0x00, 0x80, 0x4C, 0x00, 0xC0]);
+        prg_rom[fixed + 0x3FFA..].copy_from_slice(&[0x00, 0xC0, 0x00, 0xC0, 0x00, 0xC0]);
+        // Bank 1: LDA #$01 ; JMP $C003
+        prg_rom[PRG_ROM_BANK_SIZE..PRG_ROM_BANK_SIZE + 5].copy_from_slice(&[0xA9, 0x01, 0x4C, 0x03, 0xC0]);
+
+        let mut header = vec![0x4E, 0x45, 0x53, 0x1A, 0x04, 0x00, 0x20, 0x00];
+        header.resize(16, 0);
+        let info = CartridgeInfo::from(&header).unwrap();
+
+        let source = disassemble_rom(&info, &prg_rom);
+        let out = lines(&source);
+
+        // The bank mapped at $8000 is unknown so the call is left unresolved
+        assert!(out.contains(&String::from("JSR $8000")));
+        assert!(out.contains(&String::from("JMP reset")));
+        assert_eq!(out.iter().filter(|l| l.starts_with(".segment")).count(), 4);
+        assert!(out.contains(&String::from(".org $C000")));
+
+        // Each switchable bank is traced from the call. Banks 0 and 2 only have data at $8000
+        let start = out.iter().position(|l| l == "B1_8000:").unwrap();
+        assert_eq!(&out[start..start + 3], &["B1_8000:", "LDA #$01", "JMP B3_C003"]);
+        assert!(out.contains(&String::from("B3_C003:")));
+        assert!(!out.contains(&String::from("B0_8000:")));
+        assert_eq!(source.matches("; No code was traced in this bank").count(), 2);
+    }
+
+    #[test]
+    fn prg_rom_smaller_than_a_bank() {
+        let mut header = vec![0x4E, 0x45, 0x53, 0x1A, 0x04, 0x00, 0x20, 0x00];
+        header.resize(16, 0);
+        let info = CartridgeInfo::from(&header).unwrap();
+
+        let prg_rom = vec![0u8; kb!(8)];
+        let out = lines(&disassemble_rom(&info, &prg_rom));
+
+        assert!(!out.iter().any(|l| l.starts_with(".segment")));
+        assert!(!linker_config(&info, &prg_rom).contains("PRG0"));
+    }
+}
@@ -0,0 +1,355 @@
+//
+// asm/opcode.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::{Instruction, AddressingMode, InstructionCategory};
+
+/// Static description of a single opcode
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Opcode {
+    pub instr: Instruction,             // Instruction mnemonic
+    pub mode: AddressingMode,           // Addressing mode
+    pub len: usize,                     // Length of the instruction in bytes, including the opcode
+    pub cycles: usize,                  // Base cycle count
+    pub page_cross: bool,               // An extra cycle is taken when the effective address crosses a page
+    pub official: bool,                 // Documented opcode
+    pub category: InstructionCategory,  // How the instruction accesses the bus
+}
+
+impl Opcode {
+    /// Number of operand bytes following the opcode
+    pub fn operand_len(&self) -> usize {
+        self.len - 1
+    }
+}
+
+macro_rules! op {
+    ($instr:ident, $mode:ident, $cycles:expr, $page_cross:expr, $official:expr, $category:ident) => {
+        Opcode {
+            instr: Instruction::$instr,
+            mode: AddressingMode::$mode,
+            len: AddressingMode::$mode.operand_len() + 1,
+            cycles: $cycles,
+            page_cross: $page_cross,
+            official: $official,
+            category: InstructionCategory::$category,
+        }
+    };
+}
+
+/// 6502 opcode table, indexed by opcode
+/// http://wiki.nesdev.com/w/index.php/CPU_unofficial_opcodes
+///
+/// Columns: instruction, addressing mode, base cycles, page cross penalty, official, bus access category
+pub const OPCODE_TABLE: [Opcode; 256] = [
+    /* $00 */ op!(BRK, Immediate,       7, false, true,  Jump),
+    /* $01 */ op!(ORA, IndexedIndirect, 6, false, true,  Read),
+    /* $02 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $03 */ op!(SLO, IndexedIndirect, 8, false, false, ReadModifyWrite),
+    /* $04 */ op!(NOP, ZeroPage,        3, false, false, Read),
+    /* $05 */ op!(ORA, ZeroPage,        3, false, true,  Read),
+    /* $06 */ op!(ASL, ZeroPage,        5, false, true,  ReadModifyWrite),
+    /* $07 */ op!(SLO, ZeroPage,        5, false, false, ReadModifyWrite),
+    /* $08 */ op!(PHP, Implied,         3, false, true,  Stack),
+    /* $09 */ op!(ORA, Immediate,       2, false, true,  Read),
+    /* $0A */ op!(ASL, Accumulator,     2, false, true,  Implied),
+    /* $0B */ op!(ANC, Immediate,       2, false, false, Read),
+    /* $0C */ op!(NOP, Absolute,        4, false, false, Read),
+    /* $0D */ op!(ORA, Absolute,        4, false, true,  Read),
+    /* $0E */ op!(ASL, Absolute,        6, false, true,  ReadModifyWrite),
+    /* $0F */ op!(SLO, Absolute,        6, false, false, ReadModifyWrite),
+    /* $10 */ op!(BPL, Relative,        2, true,  true,  Branch),
+    /* $11 */ op!(ORA, IndirectIndexed, 5, true,  true,  Read),
+    /* $12 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $13 */ op!(SLO, IndirectIndexed, 8, false, false, ReadModifyWrite),
+    /* $14 */ op!(NOP, ZeroPageX,       4, false, false, Read),
+    /* $15 */ op!(ORA, ZeroPageX,       4, false, true,  Read),
+    /* $16 */ op!(ASL, ZeroPageX,       6, false, true,  ReadModifyWrite),
+    /* $17 */ op!(SLO, ZeroPageX,       6, false, false, ReadModifyWrite),
+    /* $18 */ op!(CLC, Implied,         2, false, true,  Implied),
+    /* $19 */ op!(ORA, AbsoluteY,       4, true,  true,  Read),
+    /* $1A */ op!(NOP, Implied,         2, false, false, Implied),
+    /* $1B */ op!(SLO, AbsoluteY,       7, false, false, ReadModifyWrite),
+    /* $1C */ op!(NOP, AbsoluteX,       4, true,  false, Read),
+    /* $1D */ op!(ORA, AbsoluteX,       4, true,  true,  Read),
+    /* $1E */ op!(ASL, AbsoluteX,       7, false, true,  ReadModifyWrite),
+    /* $1F */ op!(SLO, AbsoluteX,       7, false, false, ReadModifyWrite),
+    /* $20 */ op!(JSR, Absolute,        6, false, true,  Jump),
+    /* $21 */ op!(AND, IndexedIndirect, 6, false, true,  Read),
+    /* $22 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $23 */ op!(RLA, IndexedIndirect, 8, false, false, ReadModifyWrite),
+    /* $24 */ op!(BIT, ZeroPage,        3, false, true,  Read),
+    /* $25 */ op!(AND, ZeroPage,        3, false, true,  Read),
+    /* $26 */ op!(ROL, ZeroPage,        5, false, true,  ReadModifyWrite),
+    /* $27 */ op!(RLA, ZeroPage,        5, false, false, ReadModifyWrite),
+    /* $28 */ op!(PLP, Implied,         4, false, true,  Stack),
+    /* $29 */ op!(AND, Immediate,       2, false, true,  Read),
+    /* $2A */ op!(ROL, Accumulator,     2, false, true,  Implied),
+    /* $2B */ op!(ANC, Immediate,       2, false, false, Read),
+    /* $2C */ op!(BIT, Absolute,        4, false, true,  Read),
+    /* $2D */ op!(AND, Absolute,        4, false, true,  Read),
+    /* $2E */ op!(ROL, Absolute,        6, false, true,  ReadModifyWrite),
+    /* $2F */ op!(RLA, Absolute,        6, false, false, ReadModifyWrite),
+    /* $30 */ op!(BMI, Relative,        2, true,  true,  Branch),
+    /* $31 */ op!(AND, IndirectIndexed, 5, true,  true,  Read),
+    /* $32 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $33 */ op!(RLA, IndirectIndexed, 8, false, false, ReadModifyWrite),
+    /* $34 */ op!(NOP, ZeroPageX,       4, false, false, Read),
+    /* $35 */ op!(AND, ZeroPageX,       4, false, true,  Read),
+    /* $36 */ op!(ROL, ZeroPageX,       6, false, true,  ReadModifyWrite),
+    /* $37 */ op!(RLA, ZeroPageX,       6, false, false, ReadModifyWrite),
+    /* $38 */ op!(SEC, Implied,         2, false, true,  Implied),
+    /* $39 */ op!(AND, AbsoluteY,       4, true,  true,  Read),
+    /* $3A */ op!(NOP, Implied,         2, false, false, Implied),
+    /* $3B */ op!(RLA, AbsoluteY,       7, false, false, ReadModifyWrite),
+    /* $3C */ op!(NOP, AbsoluteX,       4, true,  false, Read),
+    /* $3D */ op!(AND, AbsoluteX,       4, true,  true,  Read),
+    /* $3E */ op!(ROL, AbsoluteX,       7, false, true,  ReadModifyWrite),
+    /* $3F */ op!(RLA, AbsoluteX,       7, false, false, ReadModifyWrite),
+    /* $40 */ op!(RTI, Implied,         6, false, true,  Jump),
+    /* $41 */ op!(EOR, IndexedIndirect, 6, false, true,  Read),
+    /* $42 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $43 */ op!(SRE, IndexedIndirect, 8, false, false, ReadModifyWrite),
+    /* $44 */ op!(NOP, ZeroPage,        3, false, false, Read),
+    /* $45 */ op!(EOR, ZeroPage,        3, false, true,  Read),
+    /* $46 */ op!(LSR, ZeroPage,        5, false, true,  ReadModifyWrite),
+    /* $47 */ op!(SRE, ZeroPage,        5, false, false, ReadModifyWrite),
+    /* $48 */ op!(PHA, Implied,         3, false, true,  Stack),
+    /* $49 */ op!(EOR, Immediate,       2, false, true,  Read),
+    /* $4A */ op!(LSR, Accumulator,     2, false, true,  Implied),
+    /* $4B */ op!(ALR, Immediate,       2, false, false, Read),
+    /* $4C */ op!(JMP, Absolute,        3, false, true,  Jump),
+    /* $4D */ op!(EOR, Absolute,        4, false, true,  Read),
+    /* $4E */ op!(LSR, Absolute,        6, false, true,  ReadModifyWrite),
+    /* $4F */ op!(SRE, Absolute,        6, false, false, ReadModifyWrite),
+    /* $50 */ op!(BVC, Relative,        2, true,  true,  Branch),
+    /* $51 */ op!(EOR, IndirectIndexed, 5, true,  true,  Read),
+    /* $52 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $53 */ op!(SRE, IndirectIndexed, 8, false, false, ReadModifyWrite),
+    /* $54 */ op!(NOP, ZeroPageX,       4, false, false, Read),
+    /* $55 */ op!(EOR, ZeroPageX,       4, false, true,  Read),
+    /* $56 */ op!(LSR, ZeroPageX,       6, false, true,  ReadModifyWrite),
+    /* $57 */ op!(SRE, ZeroPageX,       6, false, false, ReadModifyWrite),
+    /* $58 */ op!(CLI, Implied,         2, false, true,  Implied),
+    /* $59 */ op!(EOR, AbsoluteY,       4, true,  true,  Read),
+    /* $5A */ op!(NOP, Implied,         2, false, false, Implied),
+    /* $5B */ op!(SRE, AbsoluteY,       7, false, false, ReadModifyWrite),
+    /* $5C */ op!(NOP, AbsoluteX,       4, true,  false, Read),
+    /* $5D */ op!(EOR, AbsoluteX,       4, true,  true,  Read),
+    /* $5E */ op!(LSR, AbsoluteX,       7, false, true,  ReadModifyWrite),
+    /* $5F */ op!(SRE, AbsoluteX,       7, false, false, ReadModifyWrite),
+    /* $60 */ op!(RTS, Implied,         6, false, true,  Jump),
+    /* $61 */ op!(ADC, IndexedIndirect, 6, false, true,  Read),
+    /* $62 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $63 */ op!(RRA, IndexedIndirect, 8, false, false, ReadModifyWrite),
+    /* $64 */ op!(NOP, ZeroPage,        3, false, false, Read),
+    /* $65 */ op!(ADC, ZeroPage,        3, false, true,  Read),
+    /* $66 */ op!(ROR, ZeroPage,        5, false, true,  ReadModifyWrite),
+    /* $67 */ op!(RRA, ZeroPage,        5, false, false, ReadModifyWrite),
+    /* $68 */ op!(PLA, Implied,         4, false, true,  Stack),
+    /* $69 */ op!(ADC, Immediate,       2, false, true,  Read),
+    /* $6A */ op!(ROR, Accumulator,     2, false, true,  Implied),
+    /* $6B */ op!(ARR, Immediate,       2, false, false, Read),
+    /* $6C */ op!(JMP, Indirect,        5, false, true,  Jump),
+    /* $6D */ op!(ADC, Absolute,        4, false, true,  Read),
+    /* $6E */ op!(ROR, Absolute,        6, false, true,  ReadModifyWrite),
+    /* $6F */ op!(RRA, Absolute,        6, false, false, ReadModifyWrite),
+    /* $70 */ op!(BVS, Relative,        2, true,  true,  Branch),
+    /* $71 */ op!(ADC, IndirectIndexed, 5, true,  true,  Read),
+    /* $72 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $73 */ op!(RRA, IndirectIndexed, 8, false, false, ReadModifyWrite),
+    /* $74 */ op!(NOP, ZeroPageX,       4, false, false, Read),
+    /* $75 */ op!(ADC, ZeroPageX,       4, false, true,  Read),
+    /* $76 */ op!(ROR, ZeroPageX,       6, false, true,  ReadModifyWrite),
+    /* $77 */ op!(RRA, ZeroPageX,       6, false, false, ReadModifyWrite),
+    /* $78 */ op!(SEI, Implied,         2, false, true,  Implied),
+    /* $79 */ op!(ADC, AbsoluteY,       4, true,  true,  Read),
+    /* $7A */ op!(NOP, Implied,         2, false, false, Implied),
+    /* $7B */ op!(RRA, AbsoluteY,       7, false, false, ReadModifyWrite),
+    /* $7C */ op!(NOP, AbsoluteX,       4, true,  false, Read),
+    /* $7D */ op!(ADC, AbsoluteX,       4, true,  true,  Read),
+    /* $7E */ op!(ROR, AbsoluteX,       7, false, true,  ReadModifyWrite),
+    /* $7F */ op!(RRA, AbsoluteX,       7, false, false, ReadModifyWrite),
+    /* $80 */ op!(NOP, Immediate,       2, false, false, Read),
+    /* $81 */ op!(STA, IndexedIndirect, 6, false, true,  Write),
+    /* $82 */ op!(NOP, Immediate,       2, false, false, Read),
+    /* $83 */ op!(SAX, IndexedIndirect, 6, false, false, Write),
+    /* $84 */ op!(STY, ZeroPage,        3, false, true,  Write),
+    /* $85 */ op!(STA, ZeroPage,        3, false, true,  Write),
+    /* $86 */ op!(STX, ZeroPage,        3, false, true,  Write),
+    /* $87 */ op!(SAX, ZeroPage,        3, false, false, Write),
+    /* $88 */ op!(DEY, Implied,         2, false, true,  Implied),
+    /* $89 */ op!(NOP, Immediate,       2, false, false, Read),
+    /* $8A */ op!(TXA, Implied,         2, false, true,  Implied),
+    /* $8B */ op!(XAA, Immediate,       2, false, false, Read),
+    /* $8C */ op!(STY, Absolute,        4, false, true,  Write),
+    /* $8D */ op!(STA, Absolute,        4, false, true,  Write),
+    /* $8E */ op!(STX, Absolute,        4, false, true,  Write),
+    /* $8F */ op!(SAX, Absolute,        4, false, false, Write),
+    /* $90 */ op!(BCC, Relative,        2, true,  true,  Branch),
+    /* $91 */ op!(STA, IndirectIndexed, 6, false, true,  Write),
+    /* $92 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $93 */ op!(AHX, IndirectIndexed, 6, false, false, Write),
+    /* $94 */ op!(STY, ZeroPageX,       4, false, true,  Write),
+    /* $95 */ op!(STA, ZeroPageX,       4, false, true,  Write),
+    /* $96 */ op!(STX, ZeroPageY,       4, false, true,  Write),
+    /* $97 */ op!(SAX, ZeroPageY,       4, false, false, Write),
+    /* $98 */ op!(TYA, Implied,         2, false, true,  Implied),
+    /* $99 */ op!(STA, AbsoluteY,       5, false, true,  Write),
+    /* $9A */ op!(TXS, Implied,         2, false, true,  Implied),
+    /* $9B */ op!(TAS, AbsoluteY,       5, false, false, Write),
+    /* $9C */ op!(SHY, AbsoluteX,       5, false, false, Write),
+    /* $9D */ op!(STA, AbsoluteX,       5, false, true,  Write),
+    /* $9E */ op!(SHX, AbsoluteY,       5, false, false, Write),
+    /* $9F */ op!(AHX, AbsoluteY,       5, false, false, Write),
+    /* $A0 */ op!(LDY, Immediate,       2, false, true,  Read),
+    /* $A1 */ op!(LDA, IndexedIndirect, 6, false, true,  Read),
+    /* $A2 */ op!(LDX, Immediate,       2, false, true,  Read),
+    /* $A3 */ op!(LAX, IndexedIndirect, 6, false, false, Read),
+    /* $A4 */ op!(LDY, ZeroPage,        3, false, true,  Read),
+    /* $A5 */ op!(LDA, ZeroPage,        3, false, true,  Read),
+    /* $A6 */ op!(LDX, ZeroPage,        3, false, true,  Read),
+    /* $A7 */ op!(LAX, ZeroPage,        3, false, false, Read),
+    /* $A8 */ op!(TAY, Implied,         2, false, true,  Implied),
+    /* $A9 */ op!(LDA, Immediate,       2, false, true,  Read),
+    /* $AA */ op!(TAX, Implied,         2, false, true,  Implied),
+    /* $AB */ op!(LAX, Immediate,       2, false, false, Read),
+    /* $AC */ op!(LDY, Absolute,        4, false, true,  Read),
+    /* $AD */ op!(LDA, Absolute,        4, false, true,  Read),
+    /* $AE */ op!(LDX, Absolute,        4, false, true,  Read),
+    /* $AF */ op!(LAX, Absolute,        4, false, false, Read),
+    /* $B0 */ op!(BCS, Relative,        2, true,  true,  Branch),
+    /* $B1 */ op!(LDA, IndirectIndexed, 5, true,  true,  Read),
+    /* $B2 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $B3 */ op!(LAX, IndirectIndexed, 5, true,  false, Read),
+    /* $B4 */ op!(LDY, ZeroPageX,       4, false, true,  Read),
+    /* $B5 */ op!(LDA, ZeroPageX,       4, false, true,  Read),
+    /* $B6 */ op!(LDX, ZeroPageY,       4, false, true,  Read),
+    /* $B7 */ op!(LAX, ZeroPageY,       4, false, false, Read),
+    /* $B8 */ op!(CLV, Implied,         2, false, true,  Implied),
+    /* $B9 */ op!(LDA, AbsoluteY,       4, true,  true,  Read),
+    /* $BA */ op!(TSX, Implied,         2, false, true,  Implied),
+    /* $BB */ op!(LAS, AbsoluteY,       4, true,  false, Read),
+    /* $BC */ op!(LDY, AbsoluteX,       4, true,  true,  Read),
+    /* $BD */ op!(LDA, AbsoluteX,       4, true,  true,  Read),
+    /* $BE */ op!(LDX, AbsoluteY,       4, true,  true,  Read),
+    /* $BF */ op!(LAX, AbsoluteY,       4, true,  false, Read),
+    /* $C0 */ op!(CPY, Immediate,       2, false, true,  Read),
+    /* $C1 */ op!(CMP, IndexedIndirect, 6, false, true,  Read),
+    /* $C2 */ op!(NOP, Immediate,       2, false, false, Read),
+    /* $C3 */ op!(DCP, IndexedIndirect, 8, false, false, ReadModifyWrite),
+    /* $C4 */ op!(CPY, ZeroPage,        3, false, true,  Read),
+    /* $C5 */ op!(CMP, ZeroPage,        3, false, true,  Read),
+    /* $C6 */ op!(DEC, ZeroPage,        5, false, true,  ReadModifyWrite),
+    /* $C7 */ op!(DCP, ZeroPage,        5, false, false, ReadModifyWrite),
+    /* $C8 */ op!(INY, Implied,         2, false, true,  Implied),
+    /* $C9 */ op!(CMP, Immediate,       2, false, true,  Read),
+    /* $CA */ op!(DEX, Implied,         2, false, true,  Implied),
+    /* $CB */ op!(AXS, Immediate,       2, false, false, Read),
+    /* $CC */ op!(CPY, Absolute,        4, false, true,  Read),
+    /* $CD */ op!(CMP, Absolute,        4, false, true,  Read),
+    /* $CE */ op!(DEC, Absolute,        6, false, true,  ReadModifyWrite),
+    /* $CF */ op!(DCP, Absolute,        6, false, false, ReadModifyWrite),
+    /* $D0 */ op!(BNE, Relative,        2, true,  true,  Branch),
+    /* $D1 */ op!(CMP, IndirectIndexed, 5, true,  true,  Read),
+    /* $D2 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $D3 */ op!(DCP, IndirectIndexed, 8, false, false, ReadModifyWrite),
+    /* $D4 */ op!(NOP, ZeroPageX,       4, false, false, Read),
+    /* $D5 */ op!(CMP, ZeroPageX,       4, false, true,  Read),
+    /* $D6 */ op!(DEC, ZeroPageX,       6, false, true,  ReadModifyWrite),
+    /* $D7 */ op!(DCP, ZeroPageX,       6, false, false, ReadModifyWrite),
+    /* $D8 */ op!(CLD, Implied,         2, false, true,  Implied),
+    /* $D9 */ op!(CMP, AbsoluteY,       4, true,  true,  Read),
+    /* $DA */ op!(NOP, Implied,         2, false, false, Implied),
+    /* $DB */ op!(DCP, AbsoluteY,       7, false, false, ReadModifyWrite),
+    /* $DC */ op!(NOP, AbsoluteX,       4, true,  false, Read),
+    /* $DD */ op!(CMP, AbsoluteX,       4, true,  true,  Read),
+    /* $DE */ op!(DEC, AbsoluteX,       7, false, true,  ReadModifyWrite),
+    /* $DF */ op!(DCP, AbsoluteX,       7, false, false, ReadModifyWrite),
+    /* $E0 */ op!(CPX, Immediate,       2, false, true,  Read),
+    /* $E1 */ op!(SBC, IndexedIndirect, 6, false, true,  Read),
+    /* $E2 */ op!(NOP, Immediate,       2, false, false, Read),
+    /* $E3 */ op!(ISB, IndexedIndirect, 8, false, false, ReadModifyWrite),
+    /* $E4 */ op!(CPX, ZeroPage,        3, false, true,  Read),
+    /* $E5 */ op!(SBC, ZeroPage,        3, false, true,  Read),
+    /* $E6 */ op!(INC, ZeroPage,        5, false, true,  ReadModifyWrite),
+    /* $E7 */ op!(ISB, ZeroPage,        5, false, false, ReadModifyWrite),
+    /* $E8 */ op!(INX, Implied,         2, false, true,  Implied),
+    /* $E9 */ op!(SBC, Immediate,       2, false, true,  Read),
+    /* $EA */ op!(NOP, Implied,         2, false, true,  Implied),
+    /* $EB */ op!(SBC, Immediate,       2, false, false, Read),
+    /* $EC */ op!(CPX, Absolute,        4, false, true,  Read),
+    /* $ED */ op!(SBC, Absolute,        4, false, true,  Read),
+    /* $EE */ op!(INC, Absolute,        6, false, true,  ReadModifyWrite),
+    /* $EF */ op!(ISB, Absolute,        6, false, false, ReadModifyWrite),
+    /* $F0 */ op!(BEQ, Relative,        2, true,  true,  Branch),
+    /* $F1 */ op!(SBC, IndirectIndexed, 5, true,  true,  Read),
+    /* $F2 */ op!(JAM, Implied,         2, false, false, Implied),
+    /* $F3 */ op!(ISB, IndirectIndexed, 8, false, false, ReadModifyWrite),
+    /* $F4 */ op!(NOP, ZeroPageX,       4, false, false, Read),
+    /* $F5 */ op!(SBC, ZeroPageX,       4, false, true,  Read),
+    /* $F6 */ op!(INC, ZeroPageX,       6, false, true,  ReadModifyWrite),
+    /* $F7 */ op!(ISB, ZeroPageX,       6, false, false, ReadModifyWrite),
+    /* $F8 */ op!(SED, Implied,         2, false, true,  Implied),
+    /* $F9 */ op!(SBC, AbsoluteY,       4, true,  true,  Read),
+    /* $FA */ op!(NOP, Implied,         2, false, false, Implied),
+    /* $FB */ op!(ISB, AbsoluteY,       7, false, false, ReadModifyWrite),
+    /* $FC */ op!(NOP, AbsoluteX,       4, true,  false, Read),
+    /* $FD */ op!(SBC, AbsoluteX,       4, true,  true,  Read),
+    /* $FE */ op!(INC, AbsoluteX,       7, false, true,  ReadModifyWrite),
+    /* $FF */ op!(ISB, AbsoluteX,       7, false, false, ReadModifyWrite),
+];
+
+/// Lookup the table entry for the given opcode
+pub fn lookup(opcode: u8) -> &'static Opcode {
+    &OPCODE_TABLE[opcode as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instruction_length() {
+        assert_eq!(lookup(0xEA).len, 1);
+        assert_eq!(lookup(0xA9).len, 2);
+        assert_eq!(lookup(0xAD).len, 3);
+        // BRK is followed by an unused byte
+        assert_eq!(lookup(0x00).len, 2);
+    }
+
+    #[test]
+    fn page_cross_penalty_only_on_reads_and_branches() {
+        for opcode in OPCODE_TABLE.iter().filter(|op| op.page_cross) {
+            assert!(matches!(opcode.category, InstructionCategory::Read | InstructionCategory::Branch),
+                    "{:?} {:?}", opcode.instr, opcode.mode);
+        }
+    }
+
+    #[test]
+    fn official_opcodes() {
+        let count = OPCODE_TABLE.iter().filter(|op| op.official).count();
+        assert_eq!(count, 151);
+    }
+
+    #[test]
+    fn unofficial_opcodes() {
+        assert!(!lookup(0xEB).official);
+        assert!(!lookup(0x1A).official);
+        assert!(!lookup(0xA7).official);
+        assert!(lookup(0xEA).official);
+    }
+
+    #[test]
+    fn cycle_counts() {
+        assert_eq!(lookup(0x00).cycles, 7); // BRK
+        assert_eq!(lookup(0x20).cycles, 6); // JSR
+        assert_eq!(lookup(0x6C).cycles, 5); // JMP ($nnnn)
+        assert_eq!(lookup(0x9D).cycles, 5); // STA $nnnn,X
+        assert_eq!(lookup(0xBD).cycles, 4); // LDA $nnnn,X
+        assert_eq!(lookup(0xFE).cycles, 7); // INC $nnnn,X
+        assert_eq!(lookup(0xD3).cycles, 8); // DCP ($nn),Y
+    }
+}
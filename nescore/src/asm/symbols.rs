@@ -0,0 +1,441 @@
+//
+// asm/symbols.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use crate::cart::PRG_ROM_BANK_SIZE;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Size of the iNES header preceding PRG ROM in the linker output
+const INES_HEADER_SIZE: usize = 16;
+
+//
+// Error types
+//
+
+/// Error loading a symbol file
+#[derive(Debug)]
+pub enum SymbolError {
+    ReadFail(io::Error),
+    UnknownFormat(String),
+    InvalidLine(usize, String),
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SymbolError::ReadFail(ref e)        => write!(f, "Failed to read symbol file: {}", e),
+            SymbolError::UnknownFormat(ref p)   => write!(f, "Unknown symbol file format: {}", p),
+            SymbolError::InvalidLine(l, ref s)  => write!(f, "Line {}: Invalid entry `{}`", l, s),
+        }
+    }
+}
+
+impl Error for SymbolError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            SymbolError::ReadFail(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+//
+// Symbol table
+//
+
+/// Location in a source file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLine {
+    pub file: String,
+    pub line: usize,
+}
+
+impl fmt::Display for SourceLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Labels and source lines keyed by PRG bank and CPU address
+///
+/// Banks are numbered in 16KB PRG ROM units. Entries without a bank (RAM, registers or symbols from files that do
+/// not record banks) match an address in any bank.
+#[derive(Default, Debug)]
+pub struct SymbolTable {
+    labels: HashMap<u16, Vec<(Option<usize>, String)>>,
+    lines: HashMap<u16, Vec<(Option<usize>, SourceLine)>>,
+}
+
+impl SymbolTable {
+    /// Load a symbol file, detecting the format from the file name
+    ///
+    /// ca65/ld65 debug files end in `.dbg`. FCEUX name lists end in `.nl`, where `<rom>.<bank>.nl` holds the labels
+    /// of a PRG bank (in hex) and `<rom>.ram.nl` holds RAM labels.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, SymbolError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(SymbolError::ReadFail)?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("dbg") => SymbolTable::from_dbg(&text),
+            Some("nl") => {
+                // Bank number is the second extension. i.e. `game.nes.1.nl`
+                let bank = path.file_stem()
+                               .map(Path::new)
+                               .and_then(|stem| stem.extension())
+                               .and_then(|e| e.to_str())
+                               .and_then(|e| usize::from_str_radix(e, 16).ok());
+                SymbolTable::from_nl(&text, bank)
+            },
+            _ => Err(SymbolError::UnknownFormat(path.display().to_string())),
+        }
+    }
+
+    /// Parse an FCEUX name list. Lines have the form `$C4F2#UpdatePlayer#Comment`
+    pub fn from_nl(text: &str, bank: Option<usize>) -> Result<Self, SymbolError> {
+        let mut table = SymbolTable::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid = || SymbolError::InvalidLine(i + 1, line.to_string());
+
+            let mut fields = line.split('#');
+            let addr = fields.next().ok_or_else(invalid)?;
+            let name = fields.next().filter(|name| !name.is_empty()).ok_or_else(invalid)?;
+
+            // The address may be followed by an array size. i.e. `$0300/40`
+            let addr = addr.split('/').next().unwrap().trim_start_matches('$');
+            let addr = u16::from_str_radix(addr, 16).map_err(|_| invalid())?;
+
+            let bank = if addr >= 0x8000 { bank } else { None };
+            table.insert_label(bank, addr, name);
+        }
+
+        Ok(table)
+    }
+
+    /// Parse a ca65/ld65 debug info file (`ld65 --dbgfile`)
+    pub fn from_dbg(text: &str) -> Result<Self, SymbolError> {
+        let mut files: HashMap<usize, String> = HashMap::new();
+        let mut segs: HashMap<usize, (u16, Option<usize>)> = HashMap::new();
+        let mut spans: HashMap<usize, (usize, u16)> = HashMap::new();
+        let mut lines: Vec<(usize, usize, Vec<usize>)> = vec![];
+        let mut syms: Vec<(String, u16, Option<usize>)> = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid = || SymbolError::InvalidLine(i + 1, line.to_string());
+
+            let (kind, fields) = match line.find(char::is_whitespace) {
+                Some(idx) => (&line[..idx], parse_dbg_fields(&line[idx..])),
+                None => (line, HashMap::new()),
+            };
+
+            let id = || fields.get("id").and_then(|v| parse_dbg_number(v)).ok_or_else(invalid);
+
+            match kind {
+                "file" => {
+                    let name = fields.get("name").ok_or_else(invalid)?;
+                    files.insert(id()?, name.to_string());
+                },
+                "seg" => {
+                    let start = fields.get("start").and_then(|v| parse_dbg_number(v)).ok_or_else(invalid)?;
+                    // Segments written to the ROM image at an offset past the header are PRG ROM
+                    let bank = fields.get("ooffs")
+                                     .and_then(|v| parse_dbg_number(v))
+                                     .filter(|&ooffs| ooffs >= INES_HEADER_SIZE && start >= 0x8000)
+                                     .map(|ooffs| (ooffs - INES_HEADER_SIZE) / PRG_ROM_BANK_SIZE);
+                    segs.insert(id()?, (start as u16, bank));
+                },
+                "span" => {
+                    let seg = fields.get("seg").and_then(|v| parse_dbg_number(v)).ok_or_else(invalid)?;
+                    let start = fields.get("start").and_then(|v| parse_dbg_number(v)).ok_or_else(invalid)?;
+                    spans.insert(id()?, (seg, start as u16));
+                },
+                "line" => {
+                    // Skip macro expansion lines, they point into the macro definition
+                    if fields.get("type").map(|t| *t == "2").unwrap_or(false) {
+                        continue;
+                    }
+
+                    let file = fields.get("file").and_then(|v| parse_dbg_number(v)).ok_or_else(invalid)?;
+                    let number = fields.get("line").and_then(|v| parse_dbg_number(v)).ok_or_else(invalid)?;
+                    let line_spans = fields.get("span").map(|v| v.split('+').filter_map(parse_dbg_number).collect()).unwrap_or_default();
+                    lines.push((file, number, line_spans));
+                },
+                "sym" => {
+                    if fields.get("type").map(|t| *t != "lab").unwrap_or(true) {
+                        continue;
+                    }
+
+                    let name = fields.get("name").ok_or_else(invalid)?;
+                    let val = fields.get("val").and_then(|v| parse_dbg_number(v)).ok_or_else(invalid)?;
+                    let seg = fields.get("seg").and_then(|v| parse_dbg_number(v));
+                    syms.push((name.to_string(), val as u16, seg));
+                },
+                _ => {},
+            }
+        }
+
+        let mut table = SymbolTable::default();
+
+        for (name, addr, seg) in syms {
+            let bank = seg.and_then(|seg| segs.get(&seg)).and_then(|&(_, bank)| bank);
+            table.insert_label(bank, addr, &name);
+        }
+
+        for (file, number, line_spans) in lines {
+            let file = match files.get(&file) {
+                Some(file) => file,
+                None => continue,
+            };
+
+            for span in line_spans {
+                if let Some(&(seg, start)) = spans.get(&span) {
+                    if let Some(&(seg_start, bank)) = segs.get(&seg) {
+                        let source = SourceLine { file: file.clone(), line: number };
+                        table.insert_line(bank, seg_start.wrapping_add(start), source);
+                    }
+                }
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Add a label. The first label at a location is kept
+    pub fn insert_label(&mut self, bank: Option<usize>, addr: u16, name: &str) {
+        let entries = self.labels.entry(addr).or_default();
+        if !entries.iter().any(|(b, _)| *b == bank) {
+            entries.push((bank, name.to_string()));
+        }
+    }
+
+    /// Add a source line. The first line at a location is kept
+    pub fn insert_line(&mut self, bank: Option<usize>, addr: u16, source: SourceLine) {
+        let entries = self.lines.entry(addr).or_default();
+        if !entries.iter().any(|(b, _)| *b == bank) {
+            entries.push((bank, source));
+        }
+    }
+
+    /// Add the entries of another table
+    pub fn merge(&mut self, other: SymbolTable) {
+        for (addr, entries) in other.labels {
+            for (bank, name) in entries {
+                self.insert_label(bank, addr, &name);
+            }
+        }
+        for (addr, entries) in other.lines {
+            for (bank, source) in entries {
+                self.insert_line(bank, addr, source);
+            }
+        }
+    }
+
+    /// Label at an address
+    ///
+    /// When the bank is not known, a label is only returned if it is unambiguous.
+    pub fn label(&self, bank: Option<usize>, addr: u16) -> Option<&str> {
+        self.labels.get(&addr).and_then(|entries| find_entry(entries, bank)).map(String::as_str)
+    }
+
+    /// Source line that generated the code at an address
+    pub fn source_line(&self, bank: Option<usize>, addr: u16) -> Option<&SourceLine> {
+        self.lines.get(&addr).and_then(|entries| find_entry(entries, bank))
+    }
+
+    /// All labels that are not in a PRG bank
+    pub fn unbanked_labels(&self) -> impl Iterator<Item=(u16, &str)> {
+        self.labels.iter().flat_map(|(&addr, entries)| {
+            entries.iter().filter(|(bank, _)| bank.is_none()).map(move |(_, name)| (addr, name.as_str()))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty() && self.lines.is_empty()
+    }
+}
+
+fn find_entry<T>(entries: &[(Option<usize>, T)], bank: Option<usize>) -> Option<&T> {
+    let exact = entries.iter().find(|(b, _)| *b == bank).map(|(_, v)| v);
+
+    exact.or_else(|| {
+        match bank {
+            // Fall back to unbanked entries
+            Some(_) => entries.iter().find(|(b, _)| b.is_none()).map(|(_, v)| v),
+            // Bank unknown. Use the entry only if there is exactly one
+            None => if entries.len() == 1 { Some(&entries[0].1) } else { None },
+        }
+    })
+}
+
+/// Split `key=value,key="value"` pairs. Quoted values may contain commas
+fn parse_dbg_fields(text: &str) -> HashMap<&str, &str> {
+    let mut fields = HashMap::new();
+    let text = text.trim();
+
+    let mut rest = text;
+    while !rest.is_empty() {
+        let eq = match rest.find('=') {
+            Some(eq) => eq,
+            None => break,
+        };
+
+        let key = &rest[..eq];
+        let value_start = &rest[eq + 1..];
+
+        let (value, remaining) = if let Some(quoted) = value_start.strip_prefix('"') {
+            match quoted.find('"') {
+                Some(end) => (&quoted[..end], quoted[end + 1..].trim_start_matches(',')),
+                None => (quoted, ""),
+            }
+        }
+        else {
+            match value_start.find(',') {
+                Some(end) => (&value_start[..end], &value_start[end + 1..]),
+                None => (value_start, ""),
+            }
+        };
+
+        fields.insert(key.trim(), value);
+        rest = remaining;
+    }
+
+    fields
+}
+
+fn parse_dbg_number(value: &str) -> Option<usize> {
+    if let Some(hex) = value.strip_prefix("0x") {
+        usize::from_str_radix(hex, 16).ok()
+    }
+    else {
+        value.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DBG: &str = "version\tmajor=2,minor=0
+info\tcsym=0,file=2,lib=0,line=3,mod=1,scope=1,seg=3,span=3,sym=4,type=4
+file\tid=0,name=\"main.s\",size=1583,mtime=0x5F3B3E5C,mod=0
+file\tid=1,name=\"player, sprites.s\",size=200,mtime=0x5F3B3E5C,mod=0
+line\tid=0,file=0,line=12,span=0
+line\tid=1,file=1,line=40,span=1
+line\tid=2,file=0,line=3,type=2,span=2
+mod\tid=0,name=\"main.o\",file=0
+seg\tid=0,name=\"BSS\",start=0x000300,size=0x0010,addrsize=absolute,type=rw
+seg\tid=1,name=\"CODE\",start=0x00C000,size=0x0100,addrsize=absolute,type=ro,oname=\"game.nes\",ooffs=16400
+seg\tid=2,name=\"BANK0\",start=0x008000,size=0x0100,addrsize=absolute,type=ro,oname=\"game.nes\",ooffs=16
+span\tid=0,seg=1,start=0,size=3,type=0
+span\tid=1,seg=1,start=0x20,size=3
+span\tid=2,seg=2,start=4,size=1
+scope\tid=0,name=\"\",mod=0,size=67,span=0+1
+sym\tid=0,name=\"reset\",addrsize=absolute,scope=0,def=11,ref=14,val=0xC000,seg=1,type=lab
+sym\tid=1,name=\"UpdatePlayer\",addrsize=absolute,scope=0,def=11,val=0xC020,seg=1,type=lab
+sym\tid=2,name=\"player_x\",addrsize=absolute,scope=0,def=11,val=0x300,seg=0,type=lab
+sym\tid=3,name=\"SPEED\",addrsize=zeropage,scope=0,def=11,val=0x3,type=equ
+sym\tid=4,name=\"LoadLevel\",addrsize=absolute,scope=0,def=11,val=0x8000,seg=2,type=lab
+";
+
+    #[test]
+    fn dbg_labels() {
+        let table = SymbolTable::from_dbg(DBG).unwrap();
+
+        assert_eq!(table.label(Some(1), 0xC000), Some("reset"));
+        assert_eq!(table.label(None, 0xC020), Some("UpdatePlayer"));
+        assert_eq!(table.label(Some(0), 0x8000), Some("LoadLevel"));
+        assert_eq!(table.label(Some(2), 0x8000), None);
+        assert_eq!(table.label(Some(1), 0x0300), Some("player_x"));
+        // Equates are not labels
+        assert_eq!(table.label(None, 0x0003), None);
+    }
+
+    #[test]
+    fn dbg_lines() {
+        let table = SymbolTable::from_dbg(DBG).unwrap();
+
+        assert_eq!(table.source_line(Some(1), 0xC000), Some(&SourceLine { file: String::from("main.s"), line: 12 }));
+        assert_eq!(table.source_line(None, 0xC020).unwrap().to_string(), "player, sprites.s:40");
+        // Macro lines are skipped
+        assert_eq!(table.source_line(Some(0), 0x8004), None);
+    }
+
+    #[test]
+    fn nl_labels() {
+        let table = SymbolTable::from_nl("$C4F2#UpdatePlayer#Move the player\n\n$0300/10#buffer#\n", Some(3)).unwrap();
+
+        assert_eq!(table.label(Some(3), 0xC4F2), Some("UpdatePlayer"));
+        assert_eq!(table.label(Some(1), 0xC4F2), None);
+        assert_eq!(table.label(Some(3), 0x0300), Some("buffer"));
+    }
+
+    #[test]
+    fn nl_invalid_line() {
+        assert!(matches!(SymbolTable::from_nl("$C4F2#UpdatePlayer#\nC4F2", None), Err(SymbolError::InvalidLine(2, _))));
+        assert!(matches!(SymbolTable::from_nl("$XYZ#foo#", None), Err(SymbolError::InvalidLine(1, _))));
+    }
+
+    #[test]
+    fn ambiguous_without_bank() {
+        let mut table = SymbolTable::default();
+        table.insert_label(Some(0), 0x8000, "BankZero");
+        table.insert_label(Some(1), 0x8000, "BankOne");
+
+        assert_eq!(table.label(None, 0x8000), None);
+        assert_eq!(table.label(Some(1), 0x8000), Some("BankOne"));
+    }
+
+    #[test]
+    fn disassemble_with_symbols() {
+        use crate::asm::{Instruction, AddressingMode, disassemble_with_symbols};
+
+        let table = SymbolTable::from_nl("$C4F2#UpdatePlayer#\n$C000#loop#\n$0010#frame#", None).unwrap();
+
+        assert_eq!(disassemble_with_symbols(Instruction::JSR, AddressingMode::Absolute, &[0xF2, 0xC4], 0xC010, &table, |_| None), "JSR UpdatePlayer");
+        assert_eq!(disassemble_with_symbols(Instruction::BNE, AddressingMode::Relative, &[0xFC], 0xC002, &table, |_| None), "BNE loop");
+        assert_eq!(disassemble_with_symbols(Instruction::LDA, AddressingMode::ZeroPageX, &[0x10], 0xC000, &table, |_| None), "LDA frame,X");
+        assert_eq!(disassemble_with_symbols(Instruction::STA, AddressingMode::Absolute, &[0x00, 0x20], 0xC000, &table, |_| None), "STA $2000");
+    }
+
+    #[test]
+    fn disassemble_with_banked_symbols() {
+        use crate::asm::{Instruction, AddressingMode, disassemble_with_symbols};
+
+        let mut table = SymbolTable::default();
+        table.insert_label(Some(0), 0x8000, "BankZero");
+        table.insert_label(Some(1), 0x8000, "BankOne");
+
+        let jsr = |bank| disassemble_with_symbols(Instruction::JSR, AddressingMode::Absolute, &[0x00, 0x80], 0xC000, &table, |_| bank);
+
+        assert_eq!(jsr(Some(0)), "JSR BankZero");
+        assert_eq!(jsr(Some(1)), "JSR BankOne");
+        assert_eq!(jsr(None), "JSR $8000");
+    }
+
+    #[test]
+    fn merge() {
+        let mut table = SymbolTable::from_nl("$0300#buffer#", None).unwrap();
+        table.merge(SymbolTable::from_nl("$C000#reset#", Some(0)).unwrap());
+
+        assert_eq!(table.label(None, 0x0300), Some("buffer"));
+        assert_eq!(table.label(Some(0), 0xC000), Some("reset"));
+    }
+}
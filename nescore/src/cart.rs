@@ -302,7 +302,7 @@ impl CartridgeLoader {
     }
 }
 
-fn load_file(path: &str) -> Result<Vec<u8>, io::Error> {
+pub(crate) fn load_file(path: &str) -> Result<Vec<u8>, io::Error> {
     match File::open(path) {
         Ok(ref mut file) => {
             let mut buffer: Vec<u8> = Vec::new();
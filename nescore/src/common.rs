@@ -27,8 +27,21 @@ pub trait IoAccess {
     fn read_byte(&self, addr: u16) -> u8 { 0 }
     #[allow(unused)]
     fn write_byte(&mut self, addr: u16, data: u8) {}
+    /// Read without side effects. Used for inspection
+    fn peek_byte(&self, addr: u16) -> u8 {
+        self.read_byte(addr)
+    }
+    /// An address is placed on the bus without a read or write
+    #[allow(unused)]
+    fn set_address(&mut self, addr: u16) {}
     #[allow(unused)]
     fn raise_interrupt(&mut self, interrupt_type: Interrupt){}
+    /// Halt the CPU for a number of cycles while another device uses the bus
+    #[allow(unused)]
+    fn stall(&mut self, cycles: u8) {}
+    /// 16KB PRG ROM bank mapped at an address, if known
+    #[allow(unused)]
+    fn prg_bank(&self, addr: u16) -> Option<usize> { None }
 }
 
 pub type IoAccessRef = Rc<RefCell<dyn IoAccess>>;
@@ -7,6 +7,7 @@
 
 use crate::common::{IoAccess, IoAccessRef};
 use crate::mapper::Mapper;
+use crate::vgm::AudioLogRef;
 
 const INTERNAL_RAM_SIZE: usize = 0x800;
 
@@ -16,6 +17,7 @@ pub struct CpuIoBus {
     apu: IoAccessRef,
     joy: IoAccessRef,
     mapper: Mapper,
+    audio_log: AudioLogRef,
 }
 
 fn mirror_address(addr: u16, base: u16, count: u16) -> u16 {
@@ -30,6 +32,24 @@ impl CpuIoBus {
             apu,
             joy,
             mapper,
+            audio_log: AudioLogRef::default(),
+        }
+    }
+
+    /// Share a log that records sound register writes while it is set
+    pub fn set_audio_log(&mut self, audio_log: AudioLogRef) {
+        self.audio_log = audio_log;
+    }
+
+    fn log_audio_write(&self, addr: u16, data: u8) {
+        if let Some(ref mut log) = *self.audio_log.borrow_mut() {
+            // Starting the DMC. Capture the sample it will play
+            if addr == 0x4015 && bit_is_set!(data, 4) {
+                let mapper = self.mapper.borrow();
+                log.capture_dpcm(|addr| mapper.read(addr));
+            }
+
+            log.write(addr, data);
         }
     }
 }
@@ -48,6 +68,18 @@ impl IoAccess for CpuIoBus {
     }
 
     fn write_byte(&mut self, addr: u16, data: u8) {
+        if self.audio_log.borrow().is_some() {
+            let register = match addr {
+                0x4000..=0x4017 => Some(addr),
+                0x4020..=0xFFFF => self.mapper.borrow().audio_register(addr),
+                _ => None,
+            };
+
+            if let Some(register) = register {
+                self.log_audio_write(register, data);
+            }
+        }
+
         match addr {
             0x0000..=0x1FFF => self.ram[mirror_address(addr, 0x0000, INTERNAL_RAM_SIZE as u16) as usize] = data,
             0x2000..=0x3FFF => {
@@ -75,6 +107,10 @@ impl IoAccess for CpuIoBus {
             0x4020..=0xFFFF => self.mapper.borrow_mut().write(addr, data),
         }
     }
+
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        self.mapper.borrow().prg_bank(addr)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,251 @@
+//
+// cpu/callstack.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Maximum number of stack anomalies kept
+const MAX_ANOMALIES: usize = 64;
+
+/// How a call stack frame was entered
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FrameKind {
+    Jsr,
+    Nmi,
+    Irq,
+    Brk,
+}
+
+impl FrameKind {
+    fn is_interrupt(&self) -> bool {
+        *self != FrameKind::Jsr
+    }
+}
+
+impl fmt::Display for FrameKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FrameKind::Jsr => write!(f, "JSR"),
+            FrameKind::Nmi => write!(f, "NMI"),
+            FrameKind::Irq => write!(f, "IRQ"),
+            FrameKind::Brk => write!(f, "BRK"),
+        }
+    }
+}
+
+/// An entry in the shadow call stack
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StackFrame {
+    pub kind: FrameKind,
+    pub caller: u16,      // Address of the JSR/BRK instruction, or the instruction that was interrupted
+    pub target: u16,      // Address of the called routine or interrupt handler
+    pub return_addr: u16, // Address execution is expected to return to
+    pub sp: u8,           // Stack pointer after the return address was pushed
+    pub cycle: u64,       // CPU cycle the frame was entered
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ${:04X} from ${:04X} (SP={:02X}, cycle {})", self.kind, self.target, self.caller, self.sp, self.cycle)
+    }
+}
+
+/// Stack manipulation that does not match the shadow call stack
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AnomalyKind {
+    /// Return without a matching call. i.e. A pushed address used as an RTS trampoline
+    UnmatchedReturn { target: u16 },
+    /// The return address on the stack was changed after the call
+    ReturnAddressModified { expected: u16, actual: u16 },
+    /// RTS from an interrupt handler or RTI from a subroutine
+    WrongReturn(FrameKind),
+    /// Frames were discarded by changing the stack pointer
+    Unwound(usize),
+}
+
+/// A stack anomaly and where it occurred
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StackAnomaly {
+    pub kind: AnomalyKind,
+    pub addr: u16,  // Address of the instruction that caused the anomaly
+    pub cycle: u64,
+}
+
+impl fmt::Display for StackAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "${:04X} (cycle {}): ", self.addr, self.cycle)?;
+        match self.kind {
+            AnomalyKind::UnmatchedReturn { target } => write!(f, "Return to ${:04X} without a matching call", target),
+            AnomalyKind::ReturnAddressModified { expected, actual } => write!(f, "Returned to ${:04X}, expected ${:04X}", actual, expected),
+            AnomalyKind::WrongReturn(kind) => write!(f, "Mismatched return from {} frame", kind),
+            AnomalyKind::Unwound(n) => write!(f, "{} frame(s) discarded by stack manipulation", n),
+        }
+    }
+}
+
+/// Shadow call stack built from calls, returns and interrupts
+#[derive(Clone, Debug, Default)]
+pub struct CallStack {
+    frames: Vec<StackFrame>,
+    anomalies: VecDeque<StackAnomaly>,
+}
+
+impl CallStack {
+    /// Frames from the outermost to the innermost call
+    pub fn frames(&self) -> &[StackFrame] {
+        &self.frames
+    }
+
+    /// Most recent stack anomalies, oldest first
+    pub fn anomalies(&self) -> impl Iterator<Item=&StackAnomaly> {
+        self.anomalies.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.anomalies.clear();
+    }
+
+    /// Record a call or interrupt entry
+    pub(crate) fn enter(&mut self, frame: StackFrame) {
+        // Frames at or below the new stack pointer can no longer be returned to
+        let stale = self.frames.iter().rev().take_while(|f| f.sp <= frame.sp).count();
+        if stale > 0 {
+            self.frames.truncate(self.frames.len() - stale);
+            self.flag(AnomalyKind::Unwound(stale), frame.caller, frame.cycle);
+        }
+
+        self.frames.push(frame);
+    }
+
+    /// Record a return. `sp` is the stack pointer before the return address was pulled
+    pub(crate) fn leave(&mut self, interrupt: bool, addr: u16, sp: u8, target: u16, cycle: u64) {
+        // Discard frames that were already removed from the stack. i.e. Pulled with PLA or reset with TXS
+        let unwound = self.frames.iter().rev().take_while(|f| f.sp < sp).count();
+        if unwound > 0 {
+            self.frames.truncate(self.frames.len() - unwound);
+            self.flag(AnomalyKind::Unwound(unwound), addr, cycle);
+        }
+
+        match self.frames.last().copied() {
+            Some(frame) if frame.sp == sp => {
+                self.frames.pop();
+
+                if frame.kind.is_interrupt() != interrupt {
+                    self.flag(AnomalyKind::WrongReturn(frame.kind), addr, cycle);
+                }
+                else if frame.return_addr != target {
+                    self.flag(AnomalyKind::ReturnAddressModified { expected: frame.return_addr, actual: target }, addr, cycle);
+                }
+            },
+            _ => {
+                // Something was pushed on top of the current frame and returned to
+                self.flag(AnomalyKind::UnmatchedReturn { target }, addr, cycle);
+            },
+        }
+    }
+
+    fn flag(&mut self, kind: AnomalyKind, addr: u16, cycle: u64) {
+        if self.anomalies.len() == MAX_ANOMALIES {
+            self.anomalies.pop_front();
+        }
+        self.anomalies.push_back(StackAnomaly { kind, addr, cycle });
+    }
+}
+
+impl fmt::Display for CallStack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (depth, frame) in self.frames.iter().rev().enumerate() {
+            writeln!(f, "#{} {}", depth, frame)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(kind: FrameKind, target: u16, return_addr: u16, sp: u8) -> StackFrame {
+        StackFrame { kind, caller: return_addr.wrapping_sub(3), target, return_addr, sp, cycle: 0 }
+    }
+
+    #[test]
+    fn call_and_return() {
+        let mut stack = CallStack::default();
+        stack.enter(frame(FrameKind::Jsr, 0xC100, 0xC003, 0xFB));
+        stack.enter(frame(FrameKind::Nmi, 0xD000, 0xC105, 0xF8));
+
+        assert_eq!(stack.frames().len(), 2);
+
+        stack.leave(true, 0xD010, 0xF8, 0xC105, 10);
+        stack.leave(false, 0xC110, 0xFB, 0xC003, 20);
+
+        assert!(stack.frames().is_empty());
+        assert_eq!(stack.anomalies().count(), 0);
+    }
+
+    #[test]
+    fn rts_trampoline() {
+        let mut stack = CallStack::default();
+        stack.enter(frame(FrameKind::Jsr, 0xC100, 0xC003, 0xFB));
+
+        // Push an address and RTS to it
+        stack.leave(false, 0xC120, 0xF9, 0xC200, 5);
+
+        assert_eq!(stack.frames().len(), 1);
+        assert_eq!(stack.anomalies().next().unwrap().kind, AnomalyKind::UnmatchedReturn { target: 0xC200 });
+
+        // The trampoline target returns to the original caller
+        stack.leave(false, 0xC210, 0xFB, 0xC003, 10);
+        assert!(stack.frames().is_empty());
+        assert_eq!(stack.anomalies().count(), 1);
+    }
+
+    #[test]
+    fn modified_return_address() {
+        let mut stack = CallStack::default();
+        stack.enter(frame(FrameKind::Jsr, 0xC100, 0xC003, 0xFB));
+        stack.leave(false, 0xC110, 0xFB, 0xC005, 5);
+
+        assert!(stack.frames().is_empty());
+        assert_eq!(stack.anomalies().next().unwrap().kind, AnomalyKind::ReturnAddressModified { expected: 0xC003, actual: 0xC005 });
+    }
+
+    #[test]
+    fn wrong_return_instruction() {
+        let mut stack = CallStack::default();
+        stack.enter(frame(FrameKind::Irq, 0xC100, 0xC003, 0xFA));
+        stack.leave(false, 0xC110, 0xFA, 0xC003, 5);
+
+        assert_eq!(stack.anomalies().next().unwrap().kind, AnomalyKind::WrongReturn(FrameKind::Irq));
+    }
+
+    #[test]
+    fn stack_reset_discards_frames() {
+        let mut stack = CallStack::default();
+        stack.enter(frame(FrameKind::Jsr, 0xC100, 0xC003, 0xFB));
+        stack.enter(frame(FrameKind::Nmi, 0xD000, 0xC105, 0xF8));
+
+        // NMI handler resets the stack pointer and calls back into the main loop
+        stack.enter(frame(FrameKind::Jsr, 0xC100, 0xD010, 0xFB));
+
+        assert_eq!(stack.frames().len(), 1);
+        assert_eq!(stack.frames()[0].caller, 0xD00D);
+        assert_eq!(stack.anomalies().next().unwrap().kind, AnomalyKind::Unwound(2));
+    }
+
+    #[test]
+    fn anomalies_are_bounded() {
+        let mut stack = CallStack::default();
+        for i in 0..(MAX_ANOMALIES + 10) {
+            stack.leave(false, i as u16, 0xFD, 0xC000, 0);
+        }
+
+        assert_eq!(stack.anomalies().count(), MAX_ANOMALIES);
+        assert_eq!(stack.anomalies().next().unwrap().addr, 10);
+    }
+}
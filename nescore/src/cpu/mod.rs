@@ -7,11 +7,13 @@
 
 // Modules
 mod cpu;
+mod callstack;
 pub mod bus;
 pub mod memorymap;
 
 // Public re-exports
 pub use cpu::Cpu;
+pub use callstack::{CallStack, StackFrame, FrameKind, StackAnomaly, AnomalyKind};
 
 #[cfg(feature="events")]
 pub use cpu::events;
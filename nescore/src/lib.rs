@@ -18,23 +18,33 @@ mod joy;
 #[cfg(feature = "events")]
 pub mod log;
 pub mod cart;
+pub mod nsf;
+pub mod vgm;
 pub mod asm;
 pub mod utils;
 
 // Public re-exports
 pub use nes::Nes;
 pub use cart::{Cartridge, CartridgeLoader};
+pub use nsf::{Nsf, NsfError};
 pub use joy::{Controller, Button};
+pub use ppu::{Palette, PaletteError};
 
 /// NES system specifications and associated types
 pub mod specs {
     pub use super::ppu::{DISPLAY_WIDTH, DISPLAY_HEIGHT};
     pub use super::nes::PixelFormat;
 
-    pub use super::apu::{Sample, APU_OUTPUT_RATE};
+    pub use super::apu::{Sample, APU_OUTPUT_RATE, AudioChannel, AudioFilter, FilterStage};
     pub type SampleBuffer = Vec<super::apu::Sample>;
 }
 
+/// Debugging and inspection types
+pub mod debug {
+    pub use super::cpu::{CallStack, StackFrame, FrameKind, StackAnomaly, AnomalyKind};
+    pub use super::ppu::debug::{DebugImage, SpriteInfo, NametableOverlays, NAMETABLE_VIEW_WIDTH, NAMETABLE_VIEW_HEIGHT};
+}
+
 #[cfg(feature="events")]
 pub mod events {
     pub use super::cpu::events::*;
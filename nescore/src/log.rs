@@ -6,7 +6,7 @@
 //
 
 use crate::events::CpuEvent;
-use crate::asm;
+use crate::asm::{self, SymbolTable};
 
 pub fn console(event: CpuEvent) {
     match event {
@@ -19,3 +19,25 @@ pub fn console(event: CpuEvent) {
         },
     }
 }
+
+/// Log to console using labels and source lines from the symbol table
+pub fn console_with_symbols(event: CpuEvent, symbols: &SymbolTable) {
+    match event {
+        CpuEvent::Instruction(data) => {
+            let bank = data.prg_bank(data.addr);
+
+            if let Some(label) = symbols.label(bank, data.addr) {
+                println!("{}:", label);
+            }
+
+            let source = symbols.source_line(bank, data.addr).map(|s| format!(" | {}", s)).unwrap_or_default();
+
+            println!("${:04X} | {} | {:<24} | A={:02X}, X={:02X}, Y={:02X}, P={:02X}, SP={:04X}{}",
+                data.addr,
+                asm::operands(&data.opcode_data[..], data.mode.operand_len()),
+                asm::disassemble_with_symbols(data.instr, data.mode, &data.opcode_data[1..], data.addr, symbols, |addr| data.prg_bank(addr)),
+                data.a, data.x, data.y, data.p, data.sp,
+                source);
+        },
+    }
+}
@@ -0,0 +1,151 @@
+//
+// mapper/audio/mmc5.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::ExpansionAudio;
+use crate::apu::Sample;
+use crate::apu::chnl::{Pulse, SoundChannel, LengthCounterUnit, EnvelopeUnit};
+use crate::common::{Clockable, IoAccess};
+
+/// CPU cycles between envelope and length counter clocks (~240 Hz)
+const FRAME_PERIOD: u16 = 7457;
+
+/// MMC5 sound: two pulse channels, without sweep units, and an 8-bit PCM channel
+/// https://wiki.nesdev.com/w/index.php/MMC5_audio
+pub struct Mmc5Audio {
+    pulse1: Pulse,
+    pulse2: Pulse,
+    pcm: u8,
+
+    frame_counter: u16,
+    odd_cycle: bool,
+}
+
+impl Default for Mmc5Audio {
+    fn default() -> Self {
+        Mmc5Audio {
+            // The MMC5 pulses have no sweep unit, so nothing mutes them at high or low periods
+            pulse1: Pulse::default().without_period_gate(),
+            pulse2: Pulse::default().without_period_gate(),
+            pcm: 0,
+
+            frame_counter: 0,
+            odd_cycle: false,
+        }
+    }
+}
+
+impl ExpansionAudio for Mmc5Audio {
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x5000..=0x5003 => self.pulse1.write_byte(addr - 0x5000, value),
+            0x5004..=0x5007 => self.pulse2.write_byte(addr - 0x5004, value),
+            // PCM write mode. Zero is ignored. Read mode, which captures PRG reads, is not supported
+            0x5011 if value != 0 => self.pcm = value,
+            0x5015 => {
+                self.pulse1.enable_length(bit_is_set!(value, 0));
+                self.pulse2.enable_length(bit_is_set!(value, 1));
+            },
+            _ => {},
+        }
+    }
+
+    fn cpu_tick(&mut self) {
+        // Pulse timers are clocked every other CPU cycle, like the APU's
+        self.odd_cycle = !self.odd_cycle;
+        if self.odd_cycle {
+            self.pulse1.tick();
+            self.pulse2.tick();
+        }
+
+        // Envelopes and length counters are clocked by a fixed rate timer instead of a frame sequencer
+        self.frame_counter += 1;
+        if self.frame_counter >= FRAME_PERIOD {
+            self.frame_counter = 0;
+
+            self.pulse1.clock_envelope();
+            self.pulse2.clock_envelope();
+            self.pulse1.clock_length();
+            self.pulse2.clock_length();
+        }
+    }
+
+    fn output(&self) -> Sample {
+        // The pulses and PCM use the same nonlinear mixing as the APU pulse and DMC channels
+        let pulse = (self.pulse1.output() + self.pulse2.output()) as f32;
+        let pulse_out = if pulse > 0.0 { 95.52 / (8128.0 / pulse + 100.0) } else { 0.0 };
+
+        let pcm = (self.pcm >> 1) as f32;
+        let pcm_out = if pcm > 0.0 { 163.67 / (24329.0 / pcm + 100.0) } else { 0.0 };
+
+        pulse_out + pcm_out
+    }
+}
+
+impl Mmc5Audio {
+    /// Value of the status register, $5015
+    pub fn status(&self) -> u8 {
+        (self.pulse1.length_status() as u8) | (self.pulse2.length_status() as u8) << 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pulse_length() {
+        let mut mmc5 = Mmc5Audio::default();
+
+        mmc5.write(0x5015, 0x03);
+        // Constant volume 15, period 0x100, length index 1 (254)
+        mmc5.write(0x5000, 0x1F);
+        mmc5.write(0x5002, 0x00);
+        mmc5.write(0x5003, 0x09);
+
+        assert_eq!(mmc5.status(), 0x01);
+
+        let mut heard = false;
+        for _ in 0..0x1000 {
+            mmc5.cpu_tick();
+            heard |= mmc5.output() > 0.0;
+        }
+        assert!(heard);
+
+        mmc5.write(0x5015, 0x00);
+        assert_eq!(mmc5.status(), 0x00);
+    }
+
+    #[test]
+    fn pulse_low_period() {
+        let mut mmc5 = Mmc5Audio::default();
+
+        mmc5.write(0x5015, 0x01);
+        // Constant volume 15, period 4, which would be silenced by an APU pulse
+        mmc5.write(0x5000, 0x1F);
+        mmc5.write(0x5002, 0x04);
+        mmc5.write(0x5003, 0x08);
+
+        let mut heard = false;
+        for _ in 0..0x100 {
+            mmc5.cpu_tick();
+            heard |= mmc5.output() > 0.0;
+        }
+        assert!(heard);
+    }
+
+    #[test]
+    fn pcm() {
+        let mut mmc5 = Mmc5Audio::default();
+
+        mmc5.write(0x5011, 0x80);
+        let level = mmc5.output();
+        assert!(level > 0.0);
+
+        // Zero writes are ignored
+        mmc5.write(0x5011, 0x00);
+        assert_eq!(mmc5.output(), level);
+    }
+}
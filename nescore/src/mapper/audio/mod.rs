@@ -0,0 +1,30 @@
+//
+// mapper/audio/mod.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+mod vrc6;
+mod sunsoft5b;
+mod mmc5;
+mod vrc7;
+
+pub use vrc6::Vrc6Audio;
+pub use sunsoft5b::Sunsoft5bAudio;
+pub use vrc7::Vrc7Audio;
+pub use mmc5::Mmc5Audio;
+
+use crate::apu::Sample;
+
+/// A sound chip on the cartridge, mixed with the APU output
+///
+/// Chips are clocked every CPU cycle and output samples scaled to the APU mixer's range, so a chip at its normal
+/// level sits alongside the APU channels the way it does on hardware.
+pub trait ExpansionAudio {
+    /// Write to a sound register, at the chip's canonical CPU address
+    fn write(&mut self, addr: u16, value: u8);
+    /// Clock the chip for one CPU cycle
+    fn cpu_tick(&mut self);
+    /// Current output level
+    fn output(&self) -> Sample;
+}
@@ -0,0 +1,285 @@
+//
+// mapper/audio/sunsoft5b.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::ExpansionAudio;
+use crate::apu::Sample;
+
+/// Output of a channel at full volume. Roughly an APU pulse at full volume
+const OUTPUT_SCALE: f32 = 0.15;
+/// CPU cycles per tone, noise and envelope clock
+const PRESCALER: u8 = 16;
+
+/// Square wave tone generator
+#[derive(Default)]
+struct Tone {
+    period: u16,
+    counter: u16,
+    output: bool,
+}
+
+impl Tone {
+    fn tick(&mut self) {
+        self.counter += 1;
+
+        if self.counter >= self.period {
+            self.counter = 0;
+            self.output = !self.output;
+        }
+    }
+}
+
+/// Envelope generator
+#[derive(Default)]
+struct Envelope {
+    period: u16,
+    counter: u16,
+
+    shape: u8,
+    step: u8,
+    attack: bool,
+    hold: Option<u8>, // Volume held at the end of a non-repeating envelope
+}
+
+impl Envelope {
+    fn set_shape(&mut self, shape: u8) {
+        self.shape = shape & 0x0F;
+        self.step = 0;
+        self.attack = bit_is_set!(shape, 2);
+        self.hold = None;
+    }
+
+    fn tick(&mut self) {
+        self.counter += 1;
+
+        if self.counter < self.period {
+            return;
+        }
+
+        self.counter = 0;
+
+        if self.hold.is_some() {
+            return;
+        }
+
+        self.step += 1;
+
+        if self.step == 16 {
+            let cont = bit_is_set!(self.shape, 3);
+            let alternate = bit_is_set!(self.shape, 1);
+            let hold = bit_is_set!(self.shape, 0);
+
+            if !cont {
+                self.hold = Some(0);
+            }
+            else if hold {
+                self.hold = Some(if self.attack != alternate { 15 } else { 0 });
+            }
+            else {
+                if alternate {
+                    self.attack = !self.attack;
+                }
+                self.step = 0;
+            }
+        }
+    }
+
+    fn volume(&self) -> u8 {
+        match self.hold {
+            Some(volume) => volume,
+            None => if self.attack { self.step } else { 15 - self.step },
+        }
+    }
+}
+
+/// Sunsoft 5B sound: an AY-3-8910 compatible PSG with three tone channels, a noise generator and an envelope
+/// https://wiki.nesdev.com/w/index.php/Sunsoft_5B_audio
+pub struct Sunsoft5bAudio {
+    register: u8,
+
+    tones: [Tone; 3],
+    volumes: [u8; 3], // Bit 4 selects the envelope
+    mixer: u8,        // Tone disable in bits 0-2, noise disable in bits 3-5
+
+    noise_period: u8,
+    noise_counter: u8,
+    noise_lfsr: u32,
+    noise_half: bool,
+
+    envelope: Envelope,
+
+    prescaler: u8,
+    levels: [f32; 16],
+}
+
+impl Default for Sunsoft5bAudio {
+    fn default() -> Self {
+        // Volume steps are 3 dB apart
+        let mut levels = [0f32; 16];
+        for (v, level) in levels.iter_mut().enumerate().skip(1) {
+            *level = 10f32.powf((v as f32 - 15.0) * 3.0 / 20.0) * OUTPUT_SCALE;
+        }
+
+        Sunsoft5bAudio {
+            register: 0,
+
+            tones: Default::default(),
+            volumes: [0; 3],
+            mixer: 0,
+
+            noise_period: 0,
+            noise_counter: 0,
+            noise_lfsr: 1,
+            noise_half: false,
+
+            envelope: Envelope::default(),
+
+            prescaler: 0,
+            levels,
+        }
+    }
+}
+
+impl ExpansionAudio for Sunsoft5bAudio {
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xC000..=0xDFFF => self.register = value & 0x0F,
+            0xE000..=0xFFFF => self.write_register(value),
+            _ => {},
+        }
+    }
+
+    fn cpu_tick(&mut self) {
+        self.prescaler += 1;
+        if self.prescaler < PRESCALER {
+            return;
+        }
+        self.prescaler = 0;
+
+        for tone in self.tones.iter_mut() {
+            tone.tick();
+        }
+
+        // Noise is clocked at half the rate of the tone channels
+        self.noise_half = !self.noise_half;
+        if self.noise_half {
+            self.noise_counter += 1;
+
+            if self.noise_counter >= self.noise_period {
+                self.noise_counter = 0;
+
+                let feedback = (self.noise_lfsr ^ (self.noise_lfsr >> 3)) & 0x01;
+                self.noise_lfsr = (self.noise_lfsr >> 1) | (feedback << 16);
+            }
+        }
+
+        self.envelope.tick();
+    }
+
+    fn output(&self) -> Sample {
+        let noise = bit_is_set!(self.noise_lfsr, 0);
+
+        self.tones.iter().zip(self.volumes.iter()).enumerate().map(|(i, (tone, &volume))| {
+            let tone_on = tone.output || bit_is_set!(self.mixer, i);
+            let noise_on = noise || bit_is_set!(self.mixer, i + 3);
+
+            if tone_on && noise_on {
+                let volume = if bit_is_set!(volume, 4) { self.envelope.volume() } else { volume & 0x0F };
+                self.levels[volume as usize]
+            }
+            else {
+                0.0
+            }
+        }).sum()
+    }
+}
+
+impl Sunsoft5bAudio {
+    fn write_register(&mut self, value: u8) {
+        match self.register {
+            0x00 | 0x02 | 0x04 => {
+                let tone = &mut self.tones[(self.register / 2) as usize];
+                tone.period = (tone.period & 0x0F00) | value as u16;
+            },
+            0x01 | 0x03 | 0x05 => {
+                let tone = &mut self.tones[(self.register / 2) as usize];
+                tone.period = (tone.period & 0x00FF) | ((value as u16 & 0x0F) << 8);
+            },
+            0x06 => self.noise_period = value & 0x1F,
+            0x07 => self.mixer = value,
+            0x08..=0x0A => self.volumes[(self.register - 0x08) as usize] = value & 0x1F,
+            0x0B => self.envelope.period = (self.envelope.period & 0xFF00) | value as u16,
+            0x0C => self.envelope.period = (self.envelope.period & 0x00FF) | (value as u16) << 8,
+            0x0D => self.envelope.set_shape(value),
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_register(chip: &mut Sunsoft5bAudio, register: u8, value: u8) {
+        chip.write(0xC000, register);
+        chip.write(0xE000, value);
+    }
+
+    #[test]
+    fn tone_period() {
+        let mut chip = Sunsoft5bAudio::default();
+
+        // Channel A: period 4, full volume, tone only
+        write_register(&mut chip, 0x00, 0x04);
+        write_register(&mut chip, 0x07, 0xFE);
+        write_register(&mut chip, 0x08, 0x0F);
+
+        // The output toggles every 16 * period CPU cycles
+        let mut transitions = 0;
+        let mut last = chip.output();
+        for _ in 0..(16 * 4 * 10) {
+            chip.cpu_tick();
+
+            let output = chip.output();
+            if output != last {
+                transitions += 1;
+            }
+            last = output;
+        }
+
+        assert_eq!(transitions, 10);
+        assert!((chip.output() - OUTPUT_SCALE).abs() < 0.0001 || chip.output() == 0.0);
+    }
+
+    #[test]
+    fn disabled_channel_outputs_volume() {
+        let mut chip = Sunsoft5bAudio::default();
+
+        // Tone and noise disabled on every channel
+        write_register(&mut chip, 0x07, 0x3F);
+        write_register(&mut chip, 0x09, 0x0F);
+
+        assert!((chip.output() - OUTPUT_SCALE).abs() < 0.0001);
+    }
+
+    #[test]
+    fn envelope_decay() {
+        let mut chip = Sunsoft5bAudio::default();
+
+        write_register(&mut chip, 0x07, 0x3F);
+        write_register(&mut chip, 0x08, 0x10);
+        // Period 1, decay once and hold at zero
+        write_register(&mut chip, 0x0B, 0x01);
+        write_register(&mut chip, 0x0D, 0x00);
+
+        assert!((chip.output() - OUTPUT_SCALE).abs() < 0.0001);
+
+        for _ in 0..(16 * 16) {
+            chip.cpu_tick();
+        }
+
+        assert_eq!(chip.output(), 0.0);
+    }
+}
@@ -0,0 +1,223 @@
+//
+// mapper/audio/vrc6.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::ExpansionAudio;
+use crate::apu::Sample;
+
+/// Output of one unit of VRC6 volume. A VRC6 pulse at full volume matches an APU pulse at full volume
+const OUTPUT_SCALE: f32 = 95.52 / (8128.0 / 15.0 + 100.0) / 15.0;
+
+/// VRC6 pulse channel
+#[derive(Default)]
+struct Pulse {
+    volume: u8,
+    duty: u8,
+    mode: bool, // Ignore duty and output volume constantly
+    enabled: bool,
+    period: u16,
+
+    counter: u16,
+    step: u8,
+}
+
+impl Pulse {
+    fn write(&mut self, reg: u16, value: u8) {
+        match reg {
+            0 => {
+                self.mode = bit_is_set!(value, 7);
+                self.duty = bit_group!(value, 0x07, 4);
+                self.volume = value & 0x0F;
+            },
+            1 => self.period = (self.period & 0x0F00) | value as u16,
+            2 => {
+                self.period = (self.period & 0x00FF) | ((value as u16 & 0x0F) << 8);
+                self.enabled = bit_is_set!(value, 7);
+
+                if !self.enabled {
+                    // Disabling resets the duty cycle
+                    self.step = 15;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn tick(&mut self, shift: u8) {
+        if !self.enabled {
+            return;
+        }
+
+        if self.counter == 0 {
+            self.counter = self.period >> shift;
+            self.step = self.step.wrapping_sub(1) & 0x0F;
+        }
+        else {
+            self.counter -= 1;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if self.enabled && (self.mode || self.step <= self.duty) {
+            self.volume
+        }
+        else {
+            0
+        }
+    }
+}
+
+/// VRC6 sawtooth channel
+#[derive(Default)]
+struct Sawtooth {
+    rate: u8,
+    enabled: bool,
+    period: u16,
+
+    counter: u16,
+    step: u8,
+    accumulator: u8,
+}
+
+impl Sawtooth {
+    fn write(&mut self, reg: u16, value: u8) {
+        match reg {
+            0 => self.rate = value & 0x3F,
+            1 => self.period = (self.period & 0x0F00) | value as u16,
+            2 => {
+                self.period = (self.period & 0x00FF) | ((value as u16 & 0x0F) << 8);
+                self.enabled = bit_is_set!(value, 7);
+
+                if !self.enabled {
+                    self.step = 0;
+                    self.accumulator = 0;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn tick(&mut self, shift: u8) {
+        if !self.enabled {
+            return;
+        }
+
+        if self.counter == 0 {
+            self.counter = self.period >> shift;
+
+            // The rate is added every second clock. The accumulator is reset on the 14th
+            self.step += 1;
+            if self.step == 14 {
+                self.step = 0;
+                self.accumulator = 0;
+            }
+            else if self.step & 0x01 == 0 {
+                self.accumulator = self.accumulator.wrapping_add(self.rate);
+            }
+        }
+        else {
+            self.counter -= 1;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        self.accumulator >> 3
+    }
+}
+
+/// Konami VRC6 sound: two pulse channels and a sawtooth
+/// https://wiki.nesdev.com/w/index.php/VRC6_audio
+#[derive(Default)]
+pub struct Vrc6Audio {
+    pulse1: Pulse,
+    pulse2: Pulse,
+    sawtooth: Sawtooth,
+
+    halt: bool,
+    shift: u8, // Frequency scaling applied to all channel periods
+}
+
+impl ExpansionAudio for Vrc6Audio {
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x9000..=0x9002 => self.pulse1.write(addr - 0x9000, value),
+            0x9003 => {
+                self.halt = bit_is_set!(value, 0);
+                self.shift = if bit_is_set!(value, 2) { 8 } else if bit_is_set!(value, 1) { 4 } else { 0 };
+            },
+            0xA000..=0xA002 => self.pulse2.write(addr - 0xA000, value),
+            0xB000..=0xB002 => self.sawtooth.write(addr - 0xB000, value),
+            _ => {},
+        }
+    }
+
+    fn cpu_tick(&mut self) {
+        if self.halt {
+            return;
+        }
+
+        self.pulse1.tick(self.shift);
+        self.pulse2.tick(self.shift);
+        self.sawtooth.tick(self.shift);
+    }
+
+    fn output(&self) -> Sample {
+        let sum = self.pulse1.output() + self.pulse2.output() + self.sawtooth.output();
+        sum as f32 * OUTPUT_SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pulse_duty() {
+        let mut vrc6 = Vrc6Audio::default();
+
+        // Volume 15, duty 8/16, period 0
+        vrc6.write(0x9000, 0x7F);
+        vrc6.write(0x9001, 0x00);
+        vrc6.write(0x9002, 0x80);
+
+        let high = (0..16).filter(|_| {
+            vrc6.cpu_tick();
+            vrc6.output() > 0.0
+        }).count();
+
+        assert_eq!(high, 8);
+    }
+
+    #[test]
+    fn sawtooth_ramp() {
+        let mut vrc6 = Vrc6Audio::default();
+
+        vrc6.write(0xB000, 42);
+        vrc6.write(0xB001, 0x00);
+        vrc6.write(0xB002, 0x80);
+
+        let levels: Vec<u8> = (0..14).map(|_| {
+            vrc6.cpu_tick();
+            vrc6.sawtooth.output()
+        }).collect();
+
+        assert_eq!(levels, vec![0, 5, 5, 10, 10, 15, 15, 21, 21, 26, 26, 31, 31, 0]);
+    }
+
+    #[test]
+    fn halt() {
+        let mut vrc6 = Vrc6Audio::default();
+
+        vrc6.write(0xB000, 42);
+        vrc6.write(0xB002, 0x80);
+        vrc6.write(0x9003, 0x01);
+
+        for _ in 0..10 {
+            vrc6.cpu_tick();
+        }
+
+        assert_eq!(vrc6.output(), 0.0);
+    }
+}
@@ -0,0 +1,587 @@
+//
+// mapper/audio/vrc7.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::ExpansionAudio;
+use crate::apu::Sample;
+
+use std::f32::consts::PI;
+
+/// CPU cycles per FM sample. The chip runs at twice the CPU clock and outputs a sample every 72 of its cycles
+const CYCLES_PER_SAMPLE: u8 = 36;
+/// FM sample rate
+const SAMPLE_RATE: f32 = 1_789_773.0 / CYCLES_PER_SAMPLE as f32;
+/// Output of a channel at full volume
+const OUTPUT_SCALE: f32 = 0.15;
+
+const NUM_CHANNELS: usize = 6;
+/// Entries in the sine table. Phase is tracked with 9 fractional bits below this
+const SINE_BITS: u32 = 10;
+const PHASE_BITS: u32 = 19;
+
+/// Attenuation at which an envelope is considered silent, in dB
+const MAX_ATTENUATION: f32 = 48.0;
+/// Peak phase modulation applied by a modulator at full output, in radians
+const MODULATION_INDEX: f32 = 4.0 * PI;
+
+/// Frequency multiplier, doubled to keep the 1/2 entry integral
+const MULTIPLIER: [u32; 16] = [1, 2, 4, 6, 8, 10, 12, 14, 16, 18, 20, 20, 24, 24, 30, 30];
+/// Key scale level attenuation at block 7 for the upper four bits of F-Number, in dB
+const KSL_TABLE: [f32; 16] = [
+    0.0, 18.0, 24.0, 27.75, 30.0, 32.25, 33.75, 35.25, 36.0, 37.5, 38.25, 39.0, 39.75, 40.5, 41.25, 42.0,
+];
+/// Fraction of the key scale level table applied for each KSL setting (0, 1.5, 3 and 6 dB per octave)
+const KSL_SHIFT: [f32; 4] = [0.0, 0.25, 0.5, 1.0];
+
+/// Tremolo depth in dB and rate in Hz
+const AM_DEPTH: f32 = 4.8;
+const AM_RATE: f32 = 3.7;
+/// Vibrato depth as a fraction of the frequency (13.75 cents) and rate in Hz
+const PM_DEPTH: f32 = 0.008;
+const PM_RATE: f32 = 6.4;
+
+/// Built-in instruments 1-15. Instrument 0 is the custom instrument in registers $00-$07
+/// https://wiki.nesdev.com/w/index.php/VRC7_audio
+const PATCHES: [[u8; 8]; 15] = [
+    [0x03, 0x21, 0x05, 0x06, 0xE8, 0x81, 0x42, 0x27], // Buzzy bell
+    [0x13, 0x41, 0x14, 0x0D, 0xD8, 0xF6, 0x23, 0x12], // Guitar
+    [0x11, 0x11, 0x08, 0x08, 0xFA, 0xB2, 0x20, 0x12], // Wurly
+    [0x31, 0x61, 0x0C, 0x07, 0xA8, 0x64, 0x61, 0x27], // Flute
+    [0x32, 0x21, 0x1E, 0x06, 0xE1, 0x76, 0x01, 0x28], // Clarinet
+    [0x02, 0x01, 0x06, 0x00, 0xA3, 0xE2, 0xF4, 0xF4], // Synth
+    [0x21, 0x61, 0x1D, 0x07, 0x82, 0x81, 0x11, 0x07], // Trumpet
+    [0x23, 0x21, 0x22, 0x17, 0xA2, 0x72, 0x01, 0x17], // Organ
+    [0x35, 0x11, 0x25, 0x00, 0x40, 0x73, 0x72, 0x01], // Bells
+    [0xB5, 0x01, 0x0F, 0x0F, 0xA8, 0xA5, 0x51, 0x02], // Vibes
+    [0x17, 0xC1, 0x24, 0x07, 0xF8, 0xF8, 0x22, 0x12], // Vibraphone
+    [0x71, 0x23, 0x11, 0x06, 0x65, 0x74, 0x18, 0x16], // Tutti
+    [0x01, 0x02, 0xD3, 0x05, 0xC9, 0x95, 0x03, 0x02], // Fretless
+    [0x61, 0x63, 0x0C, 0x00, 0x94, 0xC0, 0x33, 0xF6], // Synth bass
+    [0x21, 0x72, 0x0D, 0x00, 0xC1, 0xD5, 0x56, 0x06], // Sweep
+];
+
+/// Operator parameters decoded from an instrument
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct OperatorPatch {
+    am: bool,
+    vibrato: bool,
+    sustained: bool, // Envelope holds at the sustain level until key off
+    ksr: bool,
+    multiplier: u8,
+    ksl: u8,
+    rectified: bool, // Negative half of the sine wave is silenced
+    attack: u8,
+    decay: u8,
+    sustain: u8,
+    release: u8,
+}
+
+/// An instrument: modulator and carrier parameters, the modulator's total level and feedback
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Patch {
+    modulator: OperatorPatch,
+    carrier: OperatorPatch,
+    total_level: u8,
+    feedback: u8,
+}
+
+impl From<&[u8; 8]> for Patch {
+    fn from(data: &[u8; 8]) -> Self {
+        let operator = |flags: u8, ksl: u8, rectified: bool, rates: u8, levels: u8| OperatorPatch {
+            am: bit_is_set!(flags, 7),
+            vibrato: bit_is_set!(flags, 6),
+            sustained: bit_is_set!(flags, 5),
+            ksr: bit_is_set!(flags, 4),
+            multiplier: flags & 0x0F,
+            ksl,
+            rectified,
+            attack: rates >> 4,
+            decay: rates & 0x0F,
+            sustain: levels >> 4,
+            release: levels & 0x0F,
+        };
+
+        Patch {
+            modulator: operator(data[0], data[2] >> 6, bit_is_set!(data[3], 3), data[4], data[6]),
+            carrier: operator(data[1], data[3] >> 6, bit_is_set!(data[3], 4), data[5], data[7]),
+            total_level: data[2] & 0x3F,
+            feedback: data[3] & 0x07,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EnvelopeState {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Phase generator and envelope generator for one operator
+#[derive(Debug, Clone, Copy)]
+struct Operator {
+    phase: u32,
+    state: EnvelopeState,
+    attenuation: f32, // Envelope attenuation in dB
+}
+
+impl Default for Operator {
+    fn default() -> Self {
+        Operator {
+            phase: 0,
+            state: EnvelopeState::Release,
+            attenuation: MAX_ATTENUATION,
+        }
+    }
+}
+
+impl Operator {
+    fn key_on(&mut self) {
+        self.phase = 0;
+        self.state = EnvelopeState::Attack;
+    }
+
+    fn key_off(&mut self) {
+        self.state = EnvelopeState::Release;
+    }
+
+    /// Advance the envelope by one sample. `release` is the rate used after key off
+    fn clock_envelope(&mut self, patch: &OperatorPatch, rks: u8, release: u8) {
+        match self.state {
+            EnvelopeState::Attack => {
+                match helpers::attack_factor(patch.attack, rks) {
+                    Some(factor) => self.attenuation *= factor,
+                    None => self.attenuation = 0.0,
+                }
+
+                if self.attenuation < 0.1 {
+                    self.attenuation = 0.0;
+                    self.state = EnvelopeState::Decay;
+                }
+            },
+            EnvelopeState::Decay => {
+                let sustain_level = patch.sustain as f32 * 3.0;
+
+                self.attenuation += helpers::decay_step(patch.decay, rks);
+                if self.attenuation >= sustain_level {
+                    self.attenuation = sustain_level;
+                    self.state = EnvelopeState::Sustain;
+                }
+            },
+            EnvelopeState::Sustain => {
+                // Percussive instruments keep decaying at the release rate
+                if !patch.sustained {
+                    self.attenuation += helpers::decay_step(patch.release, rks);
+                }
+            },
+            EnvelopeState::Release => {
+                self.attenuation += helpers::decay_step(release, rks);
+            },
+        }
+
+        self.attenuation = self.attenuation.min(MAX_ATTENUATION);
+    }
+}
+
+/// FM channel: a modulator operator feeding a carrier
+#[derive(Debug, Clone, Copy, Default)]
+struct Channel {
+    fnum: u16, // 9-bit F-Number
+    block: u8,
+    key: bool,
+    sustain: bool,
+    instrument: u8,
+    volume: u8,
+
+    modulator: Operator,
+    carrier: Operator,
+    feedback: [f32; 2], // Last two modulator outputs
+    output: f32,
+}
+
+impl Channel {
+    /// Key scale rate base: block and the F-Number MSB
+    fn key_scale(&self) -> u8 {
+        (self.block << 1) | (self.fnum >> 8) as u8
+    }
+
+    fn set_key(&mut self, key: bool) {
+        if key && !self.key {
+            self.modulator.key_on();
+            self.carrier.key_on();
+        }
+        else if !key && self.key {
+            self.modulator.key_off();
+            self.carrier.key_off();
+        }
+
+        self.key = key;
+    }
+}
+
+/// Konami VRC7 sound: a six channel FM synthesizer derived from the YM2413 (OPLL)
+/// https://wiki.nesdev.com/w/index.php/VRC7_audio
+pub struct Vrc7Audio {
+    register: u8,
+    custom: [u8; 8],
+    channels: [Channel; NUM_CHANNELS],
+
+    patches: [Patch; 16],
+    sine: Vec<f32>,
+
+    am_phase: f32, // Tremolo oscillator phase in [0, 1)
+    pm_phase: f32, // Vibrato oscillator phase in [0, 1)
+    cycles: u8,
+    output: Sample,
+}
+
+impl Default for Vrc7Audio {
+    fn default() -> Self {
+        let mut patches = [Patch::default(); 16];
+        for (patch, data) in patches.iter_mut().skip(1).zip(PATCHES.iter()) {
+            *patch = Patch::from(data);
+        }
+
+        Vrc7Audio {
+            register: 0,
+            custom: [0; 8],
+            channels: [Channel::default(); NUM_CHANNELS],
+
+            patches,
+            sine: (0..(1 << SINE_BITS)).map(|i| (2.0 * PI * i as f32 / (1 << SINE_BITS) as f32).sin()).collect(),
+
+            am_phase: 0.0,
+            pm_phase: 0.0,
+            cycles: 0,
+            output: 0.0,
+        }
+    }
+}
+
+impl ExpansionAudio for Vrc7Audio {
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x9010 => self.register = value,
+            0x9030 => self.write_register(value),
+            _ => {},
+        }
+    }
+
+    fn cpu_tick(&mut self) {
+        self.cycles += 1;
+
+        if self.cycles == CYCLES_PER_SAMPLE {
+            self.cycles = 0;
+            self.output = self.generate();
+        }
+    }
+
+    fn output(&self) -> Sample {
+        self.output
+    }
+}
+
+impl Vrc7Audio {
+    /// Silence all channels and clear the registers
+    pub fn reset(&mut self) {
+        self.custom = [0; 8];
+        self.patches[0] = Patch::default();
+        self.channels = [Channel::default(); NUM_CHANNELS];
+        self.output = 0.0;
+    }
+
+    fn write_register(&mut self, value: u8) {
+        let channel = (self.register & 0x0F) as usize;
+
+        match self.register {
+            0x00..=0x07 => {
+                self.custom[self.register as usize] = value;
+                self.patches[0] = Patch::from(&self.custom);
+            },
+            0x10..=0x15 => {
+                self.channels[channel].fnum = (self.channels[channel].fnum & 0x100) | value as u16;
+            },
+            0x20..=0x25 => {
+                let ch = &mut self.channels[channel];
+                ch.fnum = (ch.fnum & 0xFF) | ((value as u16 & 0x01) << 8);
+                ch.block = bit_group!(value, 0x07, 1);
+                ch.sustain = bit_is_set!(value, 5);
+                ch.set_key(bit_is_set!(value, 4));
+            },
+            0x30..=0x35 => {
+                self.channels[channel].instrument = value >> 4;
+                self.channels[channel].volume = value & 0x0F;
+            },
+            _ => {},
+        }
+    }
+
+    /// Advance the tremolo and vibrato oscillators by one sample. Returns the attenuation in dB and the pitch offset
+    fn clock_lfo(&mut self) -> (f32, f32) {
+        // The phases wrap so the oscillator rates stay exact however long the chip runs
+        self.am_phase = (self.am_phase + AM_RATE / SAMPLE_RATE).fract();
+        self.pm_phase = (self.pm_phase + PM_RATE / SAMPLE_RATE).fract();
+
+        let am = AM_DEPTH * 0.5 * (1.0 - (2.0 * PI * self.am_phase).cos());
+        let pm = PM_DEPTH * (2.0 * PI * self.pm_phase).sin();
+
+        (am, pm)
+    }
+
+    /// Produce one FM sample
+    fn generate(&mut self) -> Sample {
+        let (am, pm) = self.clock_lfo();
+
+        let mut output = 0.0;
+
+        for i in 0..NUM_CHANNELS {
+            let patch = self.patches[self.channels[i].instrument as usize];
+            let ch = &mut self.channels[i];
+
+            let key_scale = ch.key_scale();
+            let ksl = helpers::key_scale_level(ch.fnum, ch.block);
+
+            // Release rate after key off. The sustain flag overrides the instrument
+            let release = |op: &OperatorPatch| if ch.sustain { 5 } else if op.sustained { op.release } else { 7 };
+            let (mod_release, car_release) = (release(&patch.modulator), release(&patch.carrier));
+
+            ch.modulator.clock_envelope(&patch.modulator, helpers::rks(key_scale, patch.modulator.ksr), mod_release);
+            ch.carrier.clock_envelope(&patch.carrier, helpers::rks(key_scale, patch.carrier.ksr), car_release);
+
+            // Modulator, with self feedback
+            let feedback = if patch.feedback == 0 {
+                0.0
+            }
+            else {
+                (ch.feedback[0] + ch.feedback[1]) / 2.0 * PI * 2f32.powi(patch.feedback as i32 - 5)
+            };
+
+            let mod_attenuation = ch.modulator.attenuation
+                                + patch.total_level as f32 * 0.75
+                                + ksl * KSL_SHIFT[patch.modulator.ksl as usize]
+                                + if patch.modulator.am { am } else { 0.0 };
+
+            let mod_out = helpers::operator(&self.sine, &mut ch.modulator, &patch.modulator, ch.fnum, ch.block, pm, feedback, mod_attenuation);
+            ch.feedback = [ch.feedback[1], mod_out];
+
+            // Carrier
+            let car_attenuation = ch.carrier.attenuation
+                                + ch.volume as f32 * 3.0
+                                + ksl * KSL_SHIFT[patch.carrier.ksl as usize]
+                                + if patch.carrier.am { am } else { 0.0 };
+
+            ch.output = helpers::operator(&self.sine, &mut ch.carrier, &patch.carrier, ch.fnum, ch.block, pm, mod_out * MODULATION_INDEX, car_attenuation);
+
+            output += ch.output;
+        }
+
+        output * OUTPUT_SCALE
+    }
+}
+
+mod helpers {
+    use super::*;
+
+    /// Effective key scale rate offset
+    pub fn rks(key_scale: u8, ksr: bool) -> u8 {
+        if ksr { key_scale } else { key_scale >> 2 }
+    }
+
+    /// Rate doubles every 4 steps of the effective rate, with linear steps in between
+    fn rate_scale(rate: u8, rks: u8) -> Option<f32> {
+        if rate == 0 {
+            return None;
+        }
+
+        let effective = (rate * 4 + rks).min(63);
+        Some(2f32.powi((effective >> 2) as i32 - 1) * (1.0 + (effective & 0x03) as f32 / 4.0))
+    }
+
+    /// Per-sample attenuation multiplier during attack. None if the attack is instant
+    pub fn attack_factor(rate: u8, rks: u8) -> Option<f32> {
+        if rate == 15 {
+            return None;
+        }
+
+        match rate_scale(rate, rks) {
+            Some(scale) => {
+                // Time to attack from silence to full volume, in samples
+                let samples = 2.826 / scale * SAMPLE_RATE;
+                Some((0.1 / MAX_ATTENUATION).powf(1.0 / samples))
+            },
+            // Zero attack rate never starts
+            None => Some(1.0),
+        }
+    }
+
+    /// Attenuation added per sample during decay and release, in dB
+    pub fn decay_step(rate: u8, rks: u8) -> f32 {
+        match rate_scale(rate, rks) {
+            Some(scale) => 96.0 / (39.28 / scale * SAMPLE_RATE),
+            None => 0.0,
+        }
+    }
+
+    /// Key scale level attenuation before applying the KSL setting, in dB
+    pub fn key_scale_level(fnum: u16, block: u8) -> f32 {
+        (KSL_TABLE[(fnum >> 5) as usize & 0x0F] - 6.0 * (7 - block) as f32).max(0.0)
+    }
+
+    /// Advance an operator's phase and compute its output
+    #[allow(clippy::too_many_arguments)]
+    pub fn operator(sine: &[f32], op: &mut Operator, patch: &OperatorPatch, fnum: u16, block: u8, pm: f32, modulation: f32, attenuation: f32) -> f32 {
+        let fnum = if patch.vibrato { fnum as f32 * (1.0 + pm) } else { fnum as f32 };
+        let increment = (fnum * MULTIPLIER[patch.multiplier as usize] as f32 * (1 << block) as f32 / 2.0) as u32;
+        op.phase = (op.phase + increment) & ((1 << PHASE_BITS) - 1);
+
+        if attenuation >= MAX_ATTENUATION * 2.0 {
+            return 0.0;
+        }
+
+        // Phase modulation in units of the sine table
+        let offset = (modulation / (2.0 * PI) * (1 << SINE_BITS) as f32) as i32;
+        let index = ((op.phase >> (PHASE_BITS - SINE_BITS)) as i32 + offset) as usize & ((1 << SINE_BITS) - 1);
+
+        let wave = sine[index];
+        let wave = if patch.rectified && wave < 0.0 { 0.0 } else { wave };
+
+        wave * 10f32.powf(-attenuation / 20.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_register(chip: &mut Vrc7Audio, register: u8, value: u8) {
+        chip.write(0x9010, register);
+        chip.write(0x9030, value);
+    }
+
+    /// Run for a number of FM samples, collecting the output
+    fn run(chip: &mut Vrc7Audio, samples: usize) -> Vec<Sample> {
+        (0..samples).map(|_| {
+            for _ in 0..CYCLES_PER_SAMPLE {
+                chip.cpu_tick();
+            }
+            chip.output()
+        }).collect()
+    }
+
+    /// Custom instrument producing a pure sine: silent modulator, instant attack, no decay
+    fn sine_instrument(chip: &mut Vrc7Audio) {
+        let patch = [0x21, 0x21, 0x3F, 0x00, 0xF0, 0xF0, 0x0F, 0x0F];
+        for (i, &value) in patch.iter().enumerate() {
+            write_register(chip, i as u8, value);
+        }
+    }
+
+    #[test]
+    fn patch_decode() {
+        let patch = Patch::from(&PATCHES[0]);
+
+        assert!(patch.carrier.sustained);
+        assert_eq!(patch.modulator.multiplier, 3);
+        assert_eq!(patch.total_level, 5);
+        assert_eq!(patch.feedback, 6);
+        assert_eq!(patch.modulator.attack, 0x0E);
+        assert_eq!(patch.carrier.release, 0x07);
+    }
+
+    #[test]
+    fn custom_instrument_frequency() {
+        let mut chip = Vrc7Audio::default();
+        sine_instrument(&mut chip);
+
+        // A440: F-Number 288, block 4
+        write_register(&mut chip, 0x30, 0x00);
+        write_register(&mut chip, 0x10, 0x20);
+        write_register(&mut chip, 0x20, 0x19);
+
+        let output = run(&mut chip, SAMPLE_RATE as usize);
+        let crossings = output.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+
+        assert!((435..=440).contains(&crossings), "Crossings: {}", crossings);
+        assert!(output.iter().fold(0.0f32, |peak, s| peak.max(s.abs())) > OUTPUT_SCALE * 0.9);
+    }
+
+    #[test]
+    fn key_off_releases() {
+        let mut chip = Vrc7Audio::default();
+
+        // Built-in instrument 3 at full volume
+        write_register(&mut chip, 0x31, 0x30);
+        write_register(&mut chip, 0x11, 0x20);
+        write_register(&mut chip, 0x21, 0x19);
+
+        let peak = |samples: &[Sample]| samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+        assert!(peak(&run(&mut chip, 2000)) > 0.01);
+
+        write_register(&mut chip, 0x21, 0x09);
+        run(&mut chip, SAMPLE_RATE as usize);
+
+        assert!(peak(&run(&mut chip, 1000)) < 0.001);
+    }
+
+    #[test]
+    fn lfo_after_long_playback() {
+        let mut chip = Vrc7Audio::default();
+
+        // 11 minutes
+        for _ in 0..(SAMPLE_RATE * 660.0) as usize {
+            chip.clock_lfo();
+        }
+
+        // Count vibrato cycles over the next 10 seconds
+        let mut cycles = 0;
+        let mut prev = chip.clock_lfo().1;
+        let mut am_range = (f32::MAX, f32::MIN);
+
+        for _ in 0..(SAMPLE_RATE * 10.0) as usize {
+            let (am, pm) = chip.clock_lfo();
+            if prev < 0.0 && pm >= 0.0 {
+                cycles += 1;
+            }
+
+            prev = pm;
+            am_range = (am_range.0.min(am), am_range.1.max(am));
+        }
+
+        assert_eq!(cycles, 64);
+        assert!(am_range.0 < 0.01 && am_range.1 > AM_DEPTH - 0.01, "AM range: {:?}", am_range);
+    }
+
+    #[test]
+    fn volume() {
+        let peak_at = |volume: u8| {
+            let mut chip = Vrc7Audio::default();
+            sine_instrument(&mut chip);
+
+            write_register(&mut chip, 0x30, volume);
+            write_register(&mut chip, 0x10, 0x20);
+            write_register(&mut chip, 0x20, 0x19);
+
+            run(&mut chip, 2000).iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
+        };
+
+        // 3 dB per step
+        let ratio = peak_at(2) / peak_at(0);
+        assert!((ratio - 10f32.powf(-6.0 / 20.0)).abs() < 0.02);
+    }
+
+    #[test]
+    fn reset() {
+        let mut chip = Vrc7Audio::default();
+        sine_instrument(&mut chip);
+        write_register(&mut chip, 0x20, 0x19);
+        run(&mut chip, 100);
+
+        chip.reset();
+        assert_eq!(chip.custom, [0; 8]);
+        assert_eq!(run(&mut chip, 100).iter().fold(0.0f32, |peak, s| peak.max(s.abs())), 0.0);
+    }
+}
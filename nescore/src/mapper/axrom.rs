@@ -49,6 +49,13 @@ impl MapperControl for Axrom {
         }
     }
 
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        match addr {
+            0x8000..=0xFFFF => Some(self.bank_select * 2 + (addr >= 0xC000) as usize),
+            _ => None,
+        }
+    }
+
     fn read_chr(&self, addr: u16) -> u8 {
         self.chr_ram[addr as usize]
     }
@@ -7,8 +7,43 @@
 
 use super::{MapperControl, Mirroring};
 use crate::cart::Cartridge;
+use crate::apu::Sample;
 
 const NAMETABLE_RAM_SIZE: usize = kb!(4);
+const NAMETABLE_SIZE: usize = kb!(1);
+/// CPU cycles PPU A12 must be low before a rising edge is detected
+const A12_LOW_CYCLES: u8 = 3;
+
+/// Detects rising edges of PPU A12, filtering edges that follow a short low period
+///
+/// MMC3 style scanline counters only see A12 rise after it has been low for a few M2 cycles. This ignores the
+/// toggling between background and sprite pattern fetches within a scanline.
+#[derive(Default)]
+struct A12Filter {
+    a12: bool,
+    low_cycles: u8,
+}
+
+impl A12Filter {
+    fn cpu_tick(&mut self) {
+        if !self.a12 {
+            self.low_cycles = self.low_cycles.saturating_add(1);
+        }
+    }
+
+    /// Update the A12 state from a PPU address. Returns true on a filtered rising edge
+    fn update(&mut self, addr: u16) -> bool {
+        let a12 = bit_is_set!(addr, 12);
+        let rising = a12 && !self.a12 && self.low_cycles >= A12_LOW_CYCLES;
+
+        if a12 {
+            self.low_cycles = 0;
+        }
+        self.a12 = a12;
+
+        rising
+    }
+}
 
 /// Holds common mapper functionality
 pub struct MapperBase<Mapper: MapperControl> {
@@ -21,6 +56,8 @@ pub struct MapperBase<Mapper: MapperControl> {
     palette_ram: [u8; 32],
     mirror_v: bool,
     four_screen: bool,
+
+    a12: A12Filter,
 }
 
 impl<Mapper: MapperControl + From<Cartridge>> From<Cartridge> for MapperBase<Mapper> {
@@ -36,6 +73,8 @@ impl<Mapper: MapperControl + From<Cartridge>> From<Cartridge> for MapperBase<Map
             palette_ram: [0; 32],
             mirror_v,
             four_screen,
+
+            a12: A12Filter::default(),
         }
     }
 }
@@ -58,8 +97,8 @@ impl<Mapper: MapperControl> MapperControl for MapperBase<Mapper> {
     fn read_chr(&self, addr: u16) -> u8 {
         match addr {
             0x0000..=0x1FFF => self.mapper.read_chr(addr),
-            0x2000..=0x2FFF => self.nametable_buffer[self.apply_mirroring(addr)],
-            0x3000..=0x3EFF => self.nametable_buffer[self.apply_mirroring(addr - 0x1000)],
+            0x2000..=0x2FFF => self.read_nametable(addr),
+            0x3000..=0x3EFF => self.read_nametable(addr - 0x1000),
             0x3F00..=0x3F1F => self.palette_ram[(self.mirror_palette(addr) as usize) - 0x3F00],
             0x3F20..=0x3FFF => self.palette_ram[(self.mirror_palette(addr - 0x20) as usize) - 0x3F00],
             _ => panic!("Invalid address for VRAM: ${:04X}", addr),
@@ -69,14 +108,46 @@ impl<Mapper: MapperControl> MapperControl for MapperBase<Mapper> {
     fn write_chr(&mut self, addr: u16, value: u8) {
         match addr {
             0x0000..=0x1FFF => self.mapper.write_chr(addr, value),
-            0x2000..=0x2FFF => self.nametable_buffer[self.apply_mirroring(addr)] = value,
-            0x3000..=0x3EFF => self.nametable_buffer[self.apply_mirroring(addr - 0x1000)] = value,
+            0x2000..=0x2FFF => self.write_nametable(addr, value),
+            0x3000..=0x3EFF => self.write_nametable(addr - 0x1000, value),
             0x3F00..=0x3F1F => self.palette_ram[(self.mirror_palette(addr) as usize) - 0x3F00] = value & 0x3F,
             0x3F20..=0x3FFF => self.palette_ram[(self.mirror_palette(addr - 0x20) as usize) - 0x3F00] = value & 0x3F,
             _ => panic!("Invalid address for VRAM: ${:04X}", addr),
         }
     }
 
+    //------------------------------------------------------------------------------------------------------------------
+    // Bus monitoring
+    //------------------------------------------------------------------------------------------------------------------
+    fn ppu_address(&mut self, addr: u16) {
+        self.mapper.ppu_address(addr);
+
+        if self.a12.update(addr) {
+            self.mapper.a12_rising();
+        }
+    }
+
+    fn cpu_tick(&mut self) {
+        self.a12.cpu_tick();
+        self.mapper.cpu_tick();
+    }
+
+    fn irq(&self) -> bool {
+        self.mapper.irq()
+    }
+
+    fn expansion_audio(&self) -> Sample {
+        self.mapper.expansion_audio()
+    }
+
+    fn audio_register(&self, addr: u16) -> Option<u16> {
+        self.mapper.audio_register(addr)
+    }
+
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        self.mapper.prg_bank(addr)
+    }
+
     /// Return a copy of battery backed RAM
     fn get_battery_ram(&self) -> Vec<u8> {
         self.mapper.get_battery_ram()
@@ -84,13 +155,23 @@ impl<Mapper: MapperControl> MapperControl for MapperBase<Mapper> {
 }
 
 impl<Mapper: MapperControl> MapperBase<Mapper> {
-    fn apply_mirroring(&self, addr: u16) -> usize {
-        if self.four_screen {
+    fn read_nametable(&self, addr: u16) -> u8 {
+        self.nametable_buffer[helpers::vram_idx(addr, self.nametable_page(addr))]
+    }
+
+    fn write_nametable(&mut self, addr: u16, value: u8) {
+        self.nametable_buffer[helpers::vram_idx(addr, self.nametable_page(addr))] = value;
+    }
+
+    /// Determine the nametable RAM page backing the nametable slot containing `addr`
+    fn nametable_page(&self, addr: u16) -> usize {
+        let slot = ((addr >> 10) & 0x03) as usize;
+
+        match self.mapper.nametable(slot) {
+            Some(page) => page,
             // In Four Screen Mode, mirroring is disabled
-            addr as usize
-        }
-        else {
-            helpers::calc_nametable_idx(addr, self.get_mirroring_type())
+            None if self.four_screen => slot,
+            None => helpers::mirrored_page(slot, self.get_mirroring_type()),
         }
     }
 
@@ -107,36 +188,23 @@ impl<Mapper: MapperControl> MapperBase<Mapper> {
 }
 
 mod helpers {
-    use super::Mirroring;
+    use super::{Mirroring, NAMETABLE_SIZE, NAMETABLE_RAM_SIZE};
 
-    /// Determine the index into the nametable buffer
+    /// The nametable RAM page a slot is mirrored to
     /// Horizontal mirroring - A vertical arrangement of nametable buffers
     /// Vertical mirroring - A horizontal arrangement of nametable buffers
-    pub fn calc_nametable_idx(addr: u16, mirror_type: Mirroring) -> usize {
-        let idx = (addr & 0x03FF) as usize;
-
-        // Get the buffer offset
-        let offset = match mirror_type {
-            Mirroring::Vertical => {
-                match addr {
-                    0x2000..=0x23FF | 0x2800..=0x2BFF => 0,
-                    0x2400..=0x27FF | 0x2C00..=0x2FFF => kb!(1),
-                    _ => panic!("Invalid address for nametable"),
-                }
-            },
-            Mirroring::Horizontal => {
-                match addr {
-                    0x2000..=0x23FF | 0x2400..=0x27FF => 0,
-                    0x2800..=0x2BFF | 0x2C00..=0x2FFF => kb!(1),
-                    _ => panic!("Invalid address for nametable"),
-                }
-            },
-            Mirroring::OneScreenLower | Mirroring::OneScreenUpper => {
-                0
-            }
-        };
+    pub fn mirrored_page(slot: usize, mirror_type: Mirroring) -> usize {
+        match mirror_type {
+            Mirroring::Vertical => slot & 0x01,
+            Mirroring::Horizontal => slot >> 1,
+            Mirroring::OneScreenLower => 0,
+            Mirroring::OneScreenUpper => 1,
+        }
+    }
 
-        offset + idx
+    /// Index into the nametable buffer for an address in the given page
+    pub fn vram_idx(addr: u16, page: usize) -> usize {
+        (page * NAMETABLE_SIZE + (addr as usize & 0x03FF)) % NAMETABLE_RAM_SIZE
     }
 }
 
@@ -144,29 +212,58 @@ mod helpers {
 mod tests {
     use super::*;
     use crate::cart::CartridgeInfo;
+    use crate::common::{IoAccess, IoAccessRef, Clockable};
+    use crate::ppu::{Ppu, bus::PpuIoBus};
+
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    /// PPU dots per scanline
+    const CYCLES_PER_SCANLINE: usize = 341;
 
     #[test]
     fn horizontal_mirroring() {
-        assert_eq!(helpers::calc_nametable_idx(0x2000, Mirroring::Horizontal), 0);
-        assert_eq!(helpers::calc_nametable_idx(0x2400, Mirroring::Horizontal), 0);
-        assert_eq!(helpers::calc_nametable_idx(0x2800, Mirroring::Horizontal), kb!(1));
-        assert_eq!(helpers::calc_nametable_idx(0x2C00, Mirroring::Horizontal), kb!(1));
+        assert_eq!(nametable_idx(0x2000, Mirroring::Horizontal), 0);
+        assert_eq!(nametable_idx(0x2400, Mirroring::Horizontal), 0);
+        assert_eq!(nametable_idx(0x2800, Mirroring::Horizontal), kb!(1));
+        assert_eq!(nametable_idx(0x2C00, Mirroring::Horizontal), kb!(1));
     }
 
     #[test]
     fn vertical_mirroring() {
-        assert_eq!(helpers::calc_nametable_idx(0x2000, Mirroring::Vertical), 0);
-        assert_eq!(helpers::calc_nametable_idx(0x2400, Mirroring::Vertical), kb!(1));
-        assert_eq!(helpers::calc_nametable_idx(0x2800, Mirroring::Vertical), 0);
-        assert_eq!(helpers::calc_nametable_idx(0x2C00, Mirroring::Vertical), kb!(1));
+        assert_eq!(nametable_idx(0x2000, Mirroring::Vertical), 0);
+        assert_eq!(nametable_idx(0x2400, Mirroring::Vertical), kb!(1));
+        assert_eq!(nametable_idx(0x2800, Mirroring::Vertical), 0);
+        assert_eq!(nametable_idx(0x2C00, Mirroring::Vertical), kb!(1));
     }
 
     #[test]
     fn one_screen_mirroring() {
-        assert_eq!(helpers::calc_nametable_idx(0x2000, Mirroring::OneScreenLower), 0);
-        assert_eq!(helpers::calc_nametable_idx(0x2400, Mirroring::OneScreenLower), 0);
-        assert_eq!(helpers::calc_nametable_idx(0x2800, Mirroring::OneScreenLower), 0);
-        assert_eq!(helpers::calc_nametable_idx(0x2C00, Mirroring::OneScreenLower), 0);
+        assert_eq!(nametable_idx(0x2000, Mirroring::OneScreenLower), 0);
+        assert_eq!(nametable_idx(0x2400, Mirroring::OneScreenLower), 0);
+        assert_eq!(nametable_idx(0x2800, Mirroring::OneScreenLower), 0);
+        assert_eq!(nametable_idx(0x2C00, Mirroring::OneScreenLower), 0);
+
+        assert_eq!(nametable_idx(0x2000, Mirroring::OneScreenUpper), kb!(1));
+        assert_eq!(nametable_idx(0x2C00, Mirroring::OneScreenUpper), kb!(1));
+    }
+
+    #[test]
+    fn mapper_nametables() {
+        let mut mapper = init_mapper();
+        mapper.mapper.nametables = Some([1, 2, 1, 3]);
+
+        mapper.write_chr(0x2005, 0x11);
+        assert_eq!(mapper.read_chr(0x2805), 0x11);
+        assert_eq!(mapper.nametable_buffer[kb!(1) + 5], 0x11);
+
+        // Cartridge VRAM
+        mapper.write_chr(0x2410, 0x22);
+        assert_eq!(mapper.read_chr(0x3410), 0x22);
+        assert_eq!(mapper.nametable_buffer[kb!(2) + 0x10], 0x22);
+
+        mapper.write_chr(0x2C00, 0x33);
+        assert_eq!(mapper.nametable_buffer[kb!(3)], 0x33);
     }
 
     #[test]
@@ -223,8 +320,89 @@ mod tests {
         assert_eq!(mapper.read_chr(0x3F0C), 0x01);
     }
 
+    #[test]
+    fn a12_filter() {
+        let mut mapper = init_mapper();
+
+        // A12 has not been low long enough
+        mapper.ppu_address(0x0000);
+        mapper.cpu_tick();
+        mapper.cpu_tick();
+        mapper.ppu_address(0x1000);
+        assert_eq!(mapper.mapper.a12_edges, 0);
+
+        // Toggling within a scanline does not count
+        mapper.ppu_address(0x0000);
+        mapper.cpu_tick();
+        mapper.ppu_address(0x1FF0);
+        assert_eq!(mapper.mapper.a12_edges, 0);
+
+        mapper.ppu_address(0x2000);
+        for _ in 0..3 {
+            mapper.cpu_tick();
+        }
+        mapper.ppu_address(0x1000);
+        mapper.ppu_address(0x1008);
+        assert_eq!(mapper.mapper.a12_edges, 1);
+
+        // Every address is passed through
+        assert_eq!(mapper.mapper.last_address, 0x1008);
+        assert_eq!(mapper.mapper.cpu_cycles, 6);
+    }
+
+    #[test]
+    fn a12_filter_rendering() {
+        let mapper = Rc::new(RefCell::new(init_mapper()));
+        let cpu: IoAccessRef = Rc::new(RefCell::new(FakeCpu));
+
+        let mut ppu: Ppu<PpuIoBus> = Ppu::default();
+        ppu.load_bus(PpuIoBus::new(cpu, mapper.clone()));
+
+        // Background at $0000 and sprites at $1000, the usual setup for an MMC3 scanline counter
+        ppu.write_byte(0x2000, 0x08);
+        ppu.write_byte(0x2001, 0x18);
+
+        let mut dots = 0;
+        let mut run_scanline = |ppu: &mut Ppu<PpuIoBus>| {
+            let edges = mapper.borrow().mapper.a12_edges;
+
+            for _ in 0..CYCLES_PER_SCANLINE {
+                ppu.tick();
+
+                dots += 1;
+                if dots % 3 == 0 {
+                    mapper.borrow_mut().cpu_tick();
+                }
+            }
+
+            mapper.borrow().mapper.a12_edges - edges
+        };
+
+        // Skip the first pre-render scanline, as the filter starts with A12 low
+        run_scanline(&mut ppu);
+
+        // Switching to sprite fetches is one rising edge on each rendered scanline. The garbage nametable fetches
+        // between sprite slots do not hold A12 low long enough to be counted
+        for scanline in 0..262 {
+            let expected = if scanline < 240 || scanline == 261 { 1 } else { 0 };
+            assert_eq!(run_scanline(&mut ppu), expected, "scanline {}", scanline);
+
+            if expected == 1 {
+                // The last fetch of the scanline is a nametable byte
+                assert_eq!(mapper.borrow().mapper.last_address & 0xF000, 0x2000);
+            }
+        }
+    }
+
+    struct FakeCpu;
+    impl IoAccess for FakeCpu {}
+
     struct FakeMapper {
         ram: [u8; kb!(32)],
+        a12_edges: usize,
+        last_address: u16,
+        cpu_cycles: usize,
+        nametables: Option<[usize; 4]>,
     }
 
     #[allow(unused)]
@@ -237,16 +415,37 @@ mod tests {
             self.ram[addr as usize] = data;
         }
         fn write_chr(&mut self, addr: u16, value: u8) {}
+        fn ppu_address(&mut self, addr: u16) {
+            self.last_address = addr;
+        }
+        fn a12_rising(&mut self) {
+            self.a12_edges += 1;
+        }
+        fn cpu_tick(&mut self) {
+            self.cpu_cycles += 1;
+        }
+        fn nametable(&self, slot: usize) -> Option<usize> {
+            self.nametables.map(|nametables| nametables[slot])
+        }
     }
 
     impl From<Cartridge> for FakeMapper {
         fn from(_: Cartridge) -> Self {
             FakeMapper{
                 ram: [0; kb!(32)],
+                a12_edges: 0,
+                last_address: 0,
+                cpu_cycles: 0,
+                nametables: None,
             }
         }
     }
 
+    fn nametable_idx(addr: u16, mirror_type: Mirroring) -> usize {
+        let slot = ((addr >> 10) & 0x03) as usize;
+        helpers::vram_idx(addr, helpers::mirrored_page(slot, mirror_type))
+    }
+
     fn init_mapper() -> MapperBase<FakeMapper> {
         let header = init_header(1, 1);
         let info = CartridgeInfo::from(&header[..]).unwrap();
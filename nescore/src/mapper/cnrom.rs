@@ -63,6 +63,14 @@ impl MapperControl for Cnrom {
         }
     }
 
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        match addr {
+            0x8000..=0xBFFF => Some(0),
+            0xC000..=0xFFFF => Some(self.prg_rom.num_banks() - 1),
+            _ => None,
+        }
+    }
+
     fn read_chr(&self, addr: u16) -> u8 {
         if let 0x0000..=0x1FFF = addr {
             self.chr_rom.read(self.chr_rom_bank, addr as usize)
@@ -0,0 +1,317 @@
+//
+// mapper/fme7.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+
+use super::MapperControl;
+use super::mem::Memory;
+use super::audio::{ExpansionAudio, Sunsoft5bAudio};
+
+use crate::apu::Sample;
+use crate::cart::Cartridge;
+
+const PRG_RAM_SIZE: usize = kb!(8);
+
+/// Sunsoft FME-7 and 5B (Mapper 69)
+/// https://wiki.nesdev.com/w/index.php/Sunsoft_FME-7
+pub struct Fme7 {
+    prg_rom: Memory, // 8 KB banks
+    prg_ram: [u8; PRG_RAM_SIZE],
+    chr_data: Memory, // 1 KB banks
+    chr_ram: bool,
+
+    command: u8,
+
+    chr_banks: [usize; 8],
+    prg_banks: [usize; 3],
+    prg_6000: u8, // Bank at $6000. Bit 6 selects RAM, bit 7 enables it
+    nametables: [usize; 4], // CIRAM page of each nametable slot
+
+    irq_enabled: bool,
+    counter_enabled: bool,
+    counter: u16,
+    irq_pending: bool,
+
+    audio: Sunsoft5bAudio,
+}
+
+impl From<Cartridge> for Fme7 {
+    fn from(cart: Cartridge) -> Self {
+        let (_, prg_rom, chr_rom, sav_ram) = cart.into_parts();
+
+        let chr_ram = chr_rom.is_empty();
+        let chr_data = if chr_ram { vec![0; kb!(8)] } else { chr_rom };
+
+        let mut prg_ram = [0u8; PRG_RAM_SIZE];
+        for (dest, src) in prg_ram.iter_mut().zip(sav_ram.iter()) {
+            *dest = *src;
+        }
+
+        Fme7 {
+            prg_rom: Memory::new(prg_rom, kb!(8)),
+            prg_ram,
+            chr_data: Memory::new(chr_data, kb!(1)),
+            chr_ram,
+
+            command: 0,
+
+            chr_banks: [0; 8],
+            prg_banks: [0; 3],
+            prg_6000: 0,
+            nametables: [0, 1, 0, 1],
+
+            irq_enabled: false,
+            counter_enabled: false,
+            counter: 0,
+            irq_pending: false,
+
+            audio: Sunsoft5bAudio::default(),
+        }
+    }
+}
+
+impl Fme7 {
+    fn ram_selected(&self) -> bool {
+        bit_is_set!(self.prg_6000, 6)
+    }
+
+    fn ram_enabled(&self) -> bool {
+        self.ram_selected() && bit_is_set!(self.prg_6000, 7)
+    }
+
+    fn write_parameter(&mut self, value: u8) {
+        match self.command {
+            0x00..=0x07 => self.chr_banks[self.command as usize] = value as usize % self.chr_data.num_banks(),
+            0x08 => self.prg_6000 = value,
+            0x09..=0x0B => self.prg_banks[(self.command - 0x09) as usize] = (value & 0x3F) as usize % self.prg_rom.num_banks(),
+            0x0C => {
+                // Vertical, horizontal, one screen lower and one screen upper
+                self.nametables = match value & 0x03 {
+                    0 => [0, 1, 0, 1],
+                    1 => [0, 0, 1, 1],
+                    2 => [0, 0, 0, 0],
+                    3 => [1, 1, 1, 1],
+                    _ => unreachable!(),
+                };
+            },
+            0x0D => {
+                self.irq_enabled = bit_is_set!(value, 0);
+                self.counter_enabled = bit_is_set!(value, 7);
+                self.irq_pending = false;
+            },
+            0x0E => self.counter = (self.counter & 0xFF00) | value as u16,
+            0x0F => self.counter = (self.counter & 0x00FF) | (value as u16) << 8,
+            _ => {},
+        }
+    }
+}
+
+impl MapperControl for Fme7 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x6000..=0x7FFF => {
+                if self.ram_enabled() {
+                    self.prg_ram[(addr - 0x6000) as usize]
+                }
+                else if !self.ram_selected() {
+                    let bank = (self.prg_6000 & 0x3F) as usize % self.prg_rom.num_banks();
+                    self.prg_rom.read(bank, (addr - 0x6000) as usize)
+                }
+                else {
+                    0
+                }
+            },
+            0x8000..=0xDFFF => {
+                let slot = ((addr - 0x8000) / 0x2000) as usize;
+                self.prg_rom.read(self.prg_banks[slot], (addr & 0x1FFF) as usize)
+            },
+            0xE000..=0xFFFF => self.prg_rom.read_last((addr - 0xE000) as usize),
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x6000..=0x7FFF if self.ram_enabled() => self.prg_ram[(addr - 0x6000) as usize] = data,
+            0x8000..=0x9FFF => self.command = data & 0x0F,
+            0xA000..=0xBFFF => self.write_parameter(data),
+            0xC000..=0xFFFF => self.audio.write(addr, data),
+            _ => {},
+        }
+    }
+
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        match addr {
+            0x8000..=0xDFFF => Some(self.prg_banks[((addr - 0x8000) / 0x2000) as usize] / 2),
+            0xE000..=0xFFFF => Some((self.prg_rom.num_banks() - 1) / 2),
+            _ => None,
+        }
+    }
+
+    fn read_chr(&self, addr: u16) -> u8 {
+        if self.chr_ram {
+            self.chr_data.read(0, addr as usize)
+        }
+        else {
+            self.chr_data.read(self.chr_banks[(addr / 0x400) as usize], (addr % 0x400) as usize)
+        }
+    }
+
+    fn write_chr(&mut self, addr: u16, value: u8) {
+        if self.chr_ram {
+            self.chr_data.write(0, addr as usize, value);
+        }
+    }
+
+    fn nametable(&self, slot: usize) -> Option<usize> {
+        Some(self.nametables[slot])
+    }
+
+    fn cpu_tick(&mut self) {
+        if self.counter_enabled {
+            self.counter = self.counter.wrapping_sub(1);
+
+            if self.counter == 0xFFFF && self.irq_enabled {
+                self.irq_pending = true;
+            }
+        }
+
+        self.audio.cpu_tick();
+    }
+
+    fn irq(&self) -> bool {
+        self.irq_pending
+    }
+
+    fn expansion_audio(&self) -> Sample {
+        self.audio.output()
+    }
+
+    fn audio_register(&self, addr: u16) -> Option<u16> {
+        match addr {
+            0xC000..=0xFFFF => Some(addr & 0xE000),
+            _ => None,
+        }
+    }
+
+    fn get_battery_ram(&self) -> Vec<u8> {
+        self.prg_ram.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cart::CartridgeInfo;
+
+    #[test]
+    fn prg_banks() {
+        let mut fme7 = init_fme7();
+
+        write_command(&mut fme7, 0x09, 0x02);
+        write_command(&mut fme7, 0x0B, 0x04);
+        write_command(&mut fme7, 0x08, 0x05);
+
+        assert_eq!(fme7.read(0x8000), 2);
+        assert_eq!(fme7.read(0xC000), 4);
+        assert_eq!(fme7.read(0x6000), 5);
+        assert_eq!(fme7.read(0xE000), 7);
+
+        // Enable RAM at $6000
+        write_command(&mut fme7, 0x08, 0xC0);
+        fme7.write(0x6000, 0xDE);
+        assert_eq!(fme7.read(0x6000), 0xDE);
+    }
+
+    #[test]
+    fn banks_wrap() {
+        let mut fme7 = init_fme7();
+
+        write_command(&mut fme7, 0x09, 0x3A);
+        write_command(&mut fme7, 0x08, 0x0D);
+        write_command(&mut fme7, 0x00, 0xFB);
+
+        assert_eq!(fme7.read(0x8000), 2);
+        assert_eq!(fme7.read(0x6000), 5);
+        assert_eq!(fme7.read_chr(0x0000), 3);
+    }
+
+    #[test]
+    fn chr_banks() {
+        let mut fme7 = init_fme7();
+
+        write_command(&mut fme7, 0x07, 0x03);
+        assert_eq!(fme7.read_chr(0x1C00), 3);
+    }
+
+    #[test]
+    fn nametables() {
+        let mut fme7 = init_fme7();
+
+        let pages = |fme7: &Fme7| (0..4).map(|slot| fme7.nametable(slot).unwrap()).collect::<Vec<_>>();
+        assert_eq!(pages(&fme7), vec![0, 1, 0, 1]);
+
+        write_command(&mut fme7, 0x0C, 0x01);
+        assert_eq!(pages(&fme7), vec![0, 0, 1, 1]);
+
+        write_command(&mut fme7, 0x0C, 0x02);
+        assert_eq!(pages(&fme7), vec![0, 0, 0, 0]);
+
+        write_command(&mut fme7, 0x0C, 0x03);
+        assert_eq!(pages(&fme7), vec![1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn irq() {
+        let mut fme7 = init_fme7();
+
+        write_command(&mut fme7, 0x0E, 0x01);
+        write_command(&mut fme7, 0x0F, 0x00);
+        write_command(&mut fme7, 0x0D, 0x81);
+
+        fme7.cpu_tick();
+        assert!(!fme7.irq());
+        fme7.cpu_tick();
+        assert!(fme7.irq());
+
+        // Writing the IRQ control acknowledges
+        write_command(&mut fme7, 0x0D, 0x81);
+        assert!(!fme7.irq());
+    }
+
+    #[test]
+    fn audio() {
+        let mut fme7 = init_fme7();
+
+        // Channel A at full volume with tone and noise disabled
+        fme7.write(0xC000, 0x07);
+        fme7.write(0xE000, 0x3F);
+        fme7.write(0xC000, 0x08);
+        fme7.write(0xE000, 0x0F);
+
+        assert!(fme7.expansion_audio() > 0.0);
+    }
+
+    fn write_command(fme7: &mut Fme7, command: u8, value: u8) {
+        fme7.write(0x8000, command);
+        fme7.write(0xA000, value);
+    }
+
+    /// FME-7 with 64 KB of PRG ROM and 8 KB of CHR ROM. Each 8 KB PRG bank and 1 KB CHR bank is filled with its index
+    fn init_fme7() -> Fme7 {
+        let header = [
+            0x4E, 0x45, 0x53, 0x1A,
+            4, 1,
+            0x50, 0x40,
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let info = CartridgeInfo::from(&header[..]).unwrap();
+
+        let prg_rom = (0..kb!(64)).map(|i| (i / kb!(8)) as u8).collect();
+        let chr_rom = (0..kb!(8)).map(|i| (i / kb!(1)) as u8).collect();
+
+        Fme7::from(Cartridge::from_parts(info, prg_rom, chr_rom, vec![]))
+    }
+}
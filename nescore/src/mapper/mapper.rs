@@ -12,12 +12,16 @@ use super::mmc1::Mmc1;
 use super::unrom::Unrom;
 use super::cnrom::Cnrom;
 use super::axrom::Axrom;
+use super::vrc6::Vrc6;
+use super::fme7::Fme7;
+use super::vrc7::Vrc7;
 
 // use std::boxed::Box;
 use std::rc::Rc;
 use std::cell::RefCell;
 
 use crate::cart::Cartridge;
+use crate::apu::Sample;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Mirroring {
@@ -36,6 +40,35 @@ pub trait MapperControl {
 
     fn mirroring(&self) -> Option<Mirroring> { None }
 
+    /// Nametable RAM page mapped to a nametable slot (0-3 for $2000, $2400, $2800 and $2C00). Pages 0 and 1 are the
+    /// console's CIRAM, 2 and 3 are cartridge VRAM. Takes priority over `mirroring()`
+    #[allow(unused)]
+    fn nametable(&self, slot: usize) -> Option<usize> { None }
+
+    /// Called for every address the PPU places on its address bus
+    #[allow(unused)]
+    fn ppu_address(&mut self, addr: u16) {}
+
+    /// Called on a rising edge of PPU A12, after A12 was low for at least 3 CPU cycles
+    fn a12_rising(&mut self) {}
+
+    /// Called every CPU cycle (M2)
+    fn cpu_tick(&mut self) {}
+
+    /// State of the mapper's IRQ output. The CPU is interrupted while this is asserted
+    fn irq(&self) -> bool { false }
+
+    /// Output of the cartridge's sound chip, mixed with the APU
+    fn expansion_audio(&self) -> Sample { 0.0 }
+
+    /// Canonical address of the sound chip register a CPU write goes to, if any. Used to log sound register writes
+    #[allow(unused)]
+    fn audio_register(&self, addr: u16) -> Option<u16> { None }
+
+    /// 16KB PRG ROM bank mapped at a CPU address. Used to look up symbols in banked code
+    #[allow(unused)]
+    fn prg_bank(&self, addr: u16) -> Option<usize> { None }
+
     fn get_battery_ram(&self) -> Vec<u8> {
         (0x6000..0x8000).map(|addr| self.read(addr)).collect()
     }
@@ -51,6 +84,9 @@ pub fn from_cartridge(cart: Cartridge) -> Mapper {
         2 => create_mapper::<Unrom>(cart),
         3 => create_mapper::<Cnrom>(cart),
         7 => create_mapper::<Axrom>(cart),
+        24 | 26 => create_mapper::<Vrc6>(cart),
+        69 => create_mapper::<Fme7>(cart),
+        85 => create_mapper::<Vrc7>(cart),
         _ => panic!("Invalid or unimplemented mapper: #{mapper}", mapper=cart.info.mapper),
     }
 }
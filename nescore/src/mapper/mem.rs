@@ -43,6 +43,10 @@ impl Memory {
         self.read(self.num_banks - 1, index)
     }
 
+    pub fn num_banks(&self) -> usize {
+        self.num_banks
+    }
+
     pub fn set_bank_size(&mut self, new_size: usize) {
         self.bank_size = new_size;
         self.num_banks = self.mem.len() / self.bank_size;
@@ -232,6 +232,22 @@ impl MapperControl for Mmc1 {
         }
     }
 
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        if addr < 0x8000 {
+            return None;
+        }
+
+        let upper = addr >= 0xC000;
+
+        let bank = match self.prg_rom_bank_mode {
+            PrgRomBankMode::Switch32K => (self.prg_bank_selection >> 1) * 2 + upper as usize,
+            PrgRomBankMode::Switch8000 => if upper { self.prg_rom.num_banks() - 1 } else { self.prg_bank_selection },
+            PrgRomBankMode::SwitchC000 => if upper { self.prg_bank_selection } else { 0 },
+        };
+
+        Some(bank)
+    }
+
     fn read_chr(&self, addr: u16) -> u8 {
         match self.chr_bank_mode {
             ChrBankMode::Switch8K => {
@@ -14,6 +14,14 @@ mod mmc1;
 mod unrom;
 mod cnrom;
 mod axrom;
+mod vrc6;
+mod fme7;
+mod vrc7;
+mod nsf;
+
+mod vrcirq;
+mod audio;
 
 // Public re-exports
 pub use mapper::{Mapper, Mirroring, MapperControl, from_cartridge};
+pub use nsf::NsfMapper;
@@ -77,6 +77,14 @@ impl MapperControl for Nrom {
         }
     }
 
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        match addr {
+            0x8000..=0xBFFF => Some(0),
+            0xC000..=0xFFFF => Some(if self.mirror_rom { 0 } else { 1 }),
+            _ => None,
+        }
+    }
+
     fn read_chr(&self, addr: u16) -> u8 {
         self.chr_data[addr as usize]
     }
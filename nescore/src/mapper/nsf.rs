@@ -0,0 +1,520 @@
+//
+// mapper/nsf.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+
+use super::MapperControl;
+use super::mem::Memory;
+use super::audio::{ExpansionAudio, Vrc6Audio, Vrc7Audio, Sunsoft5bAudio, Mmc5Audio};
+
+use crate::apu::Sample;
+use crate::nsf::{Nsf, Region, ExpansionChips};
+
+const BANK_SIZE: usize = kb!(4);
+const PRG_RAM_SIZE: usize = kb!(8);
+const EXRAM_SIZE: usize = kb!(1);
+const CPU_CLOCK: u64 = 1_789_773;
+
+/// Address of the driver program. This area is unused by the NSF memory map
+const DRIVER_ADDR: u16 = 0x4100;
+const DRIVER_RESET: u16 = 0x4100;
+const DRIVER_IRQ: u16 = 0x414D;
+const DRIVER_NMI: u16 = 0x415E;
+
+/// Driver registers
+const REG_TRACK: u16 = 0x4180;
+const REG_REGION: u16 = 0x4181;
+const REG_PLAY_ENABLE: u16 = 0x4182;
+const REG_PLAY_ACK: u16 = 0x4183;
+/// `JMP` instructions to the tune's INIT and PLAY routines
+const INIT_TRAMPOLINE: u16 = 0x4190;
+const PLAY_TRAMPOLINE: u16 = 0x4193;
+
+/// Initializes the system like an NSF player's BIOS, calls INIT, then calls PLAY each time the play timer interrupts
+const DRIVER: [u8; 95] = [
+    // RESET ($4100)
+    0x78,                   // SEI
+    0xD8,                   // CLD
+    0xA2, 0xFF,             // LDX #$FF
+    0x9A,                   // TXS
+    0xA9, 0x00,             // LDA #$00
+    0xAA,                   // TAX
+    // Clear internal RAM ($4108)
+    0x95, 0x00,             // STA $00,X
+    0x9D, 0x00, 0x01,       // STA $0100,X
+    0x9D, 0x00, 0x02,       // STA $0200,X
+    0x9D, 0x00, 0x03,       // STA $0300,X
+    0x9D, 0x00, 0x04,       // STA $0400,X
+    0x9D, 0x00, 0x05,       // STA $0500,X
+    0x9D, 0x00, 0x06,       // STA $0600,X
+    0x9D, 0x00, 0x07,       // STA $0700,X
+    0xE8,                   // INX
+    0xD0, 0xE6,             // BNE $4108
+    // Clear the APU registers ($4122)
+    0xA2, 0x00,             // LDX #$00
+    0x9D, 0x00, 0x40,       // STA $4000,X
+    0xE8,                   // INX
+    0xE0, 0x14,             // CPX #$14
+    0xD0, 0xF8,             // BNE $4124
+    0x8D, 0x15, 0x40,       // STA $4015
+    0xA9, 0x0F,             // LDA #$0F
+    0x8D, 0x15, 0x40,       // STA $4015
+    0xA9, 0x40,             // LDA #$40
+    0x8D, 0x17, 0x40,       // STA $4017
+    // Call INIT with the track and region ($4139)
+    0xAD, 0x80, 0x41,       // LDA REG_TRACK
+    0xAE, 0x81, 0x41,       // LDX REG_REGION
+    0xA0, 0x00,             // LDY #$00
+    0x20, 0x90, 0x41,       // JSR INIT_TRAMPOLINE
+    0xA9, 0x01,             // LDA #$01
+    0x8D, 0x82, 0x41,       // STA REG_PLAY_ENABLE
+    0x58,                   // CLI
+    0x4C, 0x4A, 0x41,       // JMP $414A
+    // IRQ ($414D)
+    0x48,                   // PHA
+    0x8A,                   // TXA
+    0x48,                   // PHA
+    0x98,                   // TYA
+    0x48,                   // PHA
+    0x8D, 0x83, 0x41,       // STA REG_PLAY_ACK
+    0x20, 0x93, 0x41,       // JSR PLAY_TRAMPOLINE
+    0x68,                   // PLA
+    0xA8,                   // TAY
+    0x68,                   // PLA
+    0xAA,                   // TAX
+    0x68,                   // PLA
+    0x40,                   // RTI
+    // NMI ($415E)
+    0x40,                   // RTI
+];
+
+/// Sound chips enabled by the NSF file
+#[derive(Default)]
+struct Chips {
+    vrc6: Option<Vrc6Audio>,
+    vrc7: Option<Vrc7Audio>,
+    sunsoft5b: Option<Sunsoft5bAudio>,
+    mmc5: Option<Mmc5Audio>,
+}
+
+/// Memory map and player for NSF files
+/// https://wiki.nesdev.com/w/index.php/NSF
+///
+/// A small driver program is mapped at $4100, and the CPU vectors point into it. The driver calls INIT for the
+/// selected track and runs PLAY from an interrupt raised by the play timer.
+///
+/// FDS tunes get the FDS memory map, with RAM at $6000-$DFFF. FDS and Namco 163 sound are not supported. Tunes using
+/// them play without the chip.
+pub struct NsfMapper {
+    prg_rom: Memory, // 4 KB banks
+    num_banks: usize,
+    bankswitched: bool,
+    bank_init: [u8; 8],
+    banks: [usize; 8],
+
+    fds_image: Option<Vec<u8>>, // Tune data restored into the FDS RAM on reset
+    fds_bank_init: [u8; 2],
+    fds_banks: [usize; 2],      // Banks at $6000 and $7000 for FDS tunes
+
+    prg_ram: [u8; PRG_RAM_SIZE],
+    exram: [u8; EXRAM_SIZE],
+    multiplier: (u8, u8),
+
+    init_addr: u16,
+    play_addr: u16,
+    pal: bool,
+    track: u8,
+    num_tracks: u8,
+
+    play_period: u64, // Play timer period in CPU cycles, scaled by 1,000,000
+    play_counter: u64,
+    play_enabled: bool,
+    play_pending: bool,
+
+    chip_flags: ExpansionChips,
+    chips: Chips,
+}
+
+impl From<&Nsf> for NsfMapper {
+    fn from(nsf: &Nsf) -> Self {
+        let fds = nsf.chips.fds;
+
+        // Bank switched tunes are padded to the load address within a bank. Others are placed in the 32 KB ROM area,
+        // or the 40 KB RAM area for FDS tunes
+        let (padding, bank_init, fds_bank_init) = match nsf.bank_init {
+            // FDS tunes start with the $E000 and $F000 banks at $6000 and $7000
+            Some(banks) => ((nsf.load_addr & 0x0FFF) as usize, banks, [banks[6], banks[7]]),
+            None if fds => ((nsf.load_addr - 0x6000) as usize, [2, 3, 4, 5, 6, 7, 8, 9], [0, 1]),
+            None => ((nsf.load_addr - 0x8000) as usize, [0, 1, 2, 3, 4, 5, 6, 7], [0, 0]),
+        };
+
+        let mut prg_rom = vec![0u8; padding];
+        prg_rom.extend_from_slice(&nsf.data);
+
+        let size = std::cmp::max(prg_rom.len(), if fds { kb!(40) } else { kb!(32) });
+        prg_rom.resize(size.div_ceil(BANK_SIZE) * BANK_SIZE, 0);
+
+        let pal = nsf.region == Region::Pal;
+        let speed = if pal { nsf.pal_speed } else { nsf.ntsc_speed };
+
+        let mut mapper = NsfMapper {
+            num_banks: prg_rom.len() / BANK_SIZE,
+            fds_image: if fds { Some(prg_rom.clone()) } else { None },
+            prg_rom: Memory::new(prg_rom, BANK_SIZE),
+            bankswitched: nsf.bank_init.is_some(),
+            bank_init,
+            banks: [0; 8],
+
+            fds_bank_init,
+            fds_banks: [0; 2],
+
+            prg_ram: [0; PRG_RAM_SIZE],
+            exram: [0; EXRAM_SIZE],
+            multiplier: (0, 0),
+
+            init_addr: nsf.init_addr,
+            play_addr: nsf.play_addr,
+            pal,
+            track: nsf.start_track,
+            num_tracks: nsf.num_tracks,
+
+            play_period: speed as u64 * CPU_CLOCK,
+            play_counter: 0,
+            play_enabled: false,
+            play_pending: false,
+
+            chip_flags: nsf.chips,
+            chips: Chips::default(),
+        };
+
+        mapper.reset(nsf.start_track);
+
+        mapper
+    }
+}
+
+impl NsfMapper {
+    /// Restore the power on state for playing the given track. The CPU must be reset to run the driver
+    ///
+    /// Tracks past the end of the tune select the last track
+    pub fn reset(&mut self, track: u8) {
+        self.track = track.min(self.num_tracks.saturating_sub(1));
+
+        for (bank, init) in self.banks.iter_mut().zip(self.bank_init.iter()) {
+            *bank = *init as usize % self.num_banks;
+        }
+
+        if let Some(ref image) = self.fds_image {
+            self.prg_rom = Memory::new(image.clone(), BANK_SIZE);
+
+            for (bank, init) in self.fds_banks.iter_mut().zip(self.fds_bank_init.iter()) {
+                *bank = *init as usize % self.num_banks;
+            }
+        }
+
+        self.prg_ram = [0; PRG_RAM_SIZE];
+        self.exram = [0; EXRAM_SIZE];
+        self.multiplier = (0, 0);
+
+        self.play_counter = 0;
+        self.play_enabled = false;
+        self.play_pending = false;
+
+        let flags = self.chip_flags;
+        self.chips = Chips {
+            vrc6: if flags.vrc6 { Some(Vrc6Audio::default()) } else { None },
+            vrc7: if flags.vrc7 { Some(Vrc7Audio::default()) } else { None },
+            sunsoft5b: if flags.sunsoft5b { Some(Sunsoft5bAudio::default()) } else { None },
+            mmc5: if flags.mmc5 { Some(Mmc5Audio::default()) } else { None },
+        };
+    }
+
+    fn read_driver(&self, addr: u16) -> u8 {
+        let [init_lo, init_hi] = self.init_addr.to_le_bytes();
+        let [play_lo, play_hi] = self.play_addr.to_le_bytes();
+
+        match addr {
+            REG_TRACK => self.track,
+            REG_REGION => self.pal as u8,
+            INIT_TRAMPOLINE | PLAY_TRAMPOLINE => 0x4C,
+            0x4191 => init_lo,
+            0x4192 => init_hi,
+            0x4194 => play_lo,
+            0x4195 => play_hi,
+            _ => DRIVER.get((addr - DRIVER_ADDR) as usize).copied().unwrap_or(0),
+        }
+    }
+
+    fn read_mmc5(&self, addr: u16) -> u8 {
+        let product = self.multiplier.0 as u16 * self.multiplier.1 as u16;
+
+        match (addr, &self.chips.mmc5) {
+            (0x5015, Some(mmc5)) => mmc5.status(),
+            (0x5205, Some(_)) => product as u8,
+            (0x5206, Some(_)) => (product >> 8) as u8,
+            (0x5C00..=0x5FF5, Some(_)) => self.exram[(addr - 0x5C00) as usize],
+            _ => 0,
+        }
+    }
+
+    fn write_chips(&mut self, addr: u16, data: u8) {
+        let chips = &mut self.chips;
+
+        if let Some(ref mut vrc6) = chips.vrc6 {
+            if let 0x9000..=0x9003 | 0xA000..=0xA002 | 0xB000..=0xB002 = addr {
+                vrc6.write(addr, data);
+            }
+        }
+
+        if let Some(ref mut vrc7) = chips.vrc7 {
+            if let 0x9010 | 0x9030 = addr {
+                vrc7.write(addr, data);
+            }
+        }
+
+        if let Some(ref mut sunsoft5b) = chips.sunsoft5b {
+            if addr >= 0xC000 {
+                sunsoft5b.write(addr, data);
+            }
+        }
+
+        if let Some(ref mut mmc5) = chips.mmc5 {
+            match addr {
+                0x5000..=0x5015 => mmc5.write(addr, data),
+                0x5205 => self.multiplier.0 = data,
+                0x5206 => self.multiplier.1 = data,
+                0x5C00..=0x5FF5 => self.exram[(addr - 0x5C00) as usize] = data,
+                _ => {},
+            }
+        }
+    }
+}
+
+impl MapperControl for NsfMapper {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x4100..=0x41FF => self.read_driver(addr),
+            0x5000..=0x5FF5 => self.read_mmc5(addr),
+            0x6000..=0x7FFF if self.fds_image.is_some() => {
+                let slot = ((addr - 0x6000) as usize) / BANK_SIZE;
+                self.prg_rom.read(self.fds_banks[slot], (addr as usize) % BANK_SIZE)
+            },
+            0x6000..=0x7FFF => self.prg_ram[(addr - 0x6000) as usize],
+            // The tune's vectors are replaced by the driver's
+            0xFFFA => DRIVER_NMI as u8,
+            0xFFFB => (DRIVER_NMI >> 8) as u8,
+            0xFFFC => DRIVER_RESET as u8,
+            0xFFFD => (DRIVER_RESET >> 8) as u8,
+            0xFFFE => DRIVER_IRQ as u8,
+            0xFFFF => (DRIVER_IRQ >> 8) as u8,
+            0x8000..=0xFFF9 => {
+                let slot = ((addr - 0x8000) as usize) / BANK_SIZE;
+                self.prg_rom.read(self.banks[slot], (addr as usize) % BANK_SIZE)
+            },
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            REG_PLAY_ENABLE => self.play_enabled = bit_is_set!(data, 0),
+            REG_PLAY_ACK => self.play_pending = false,
+            0x5FF8..=0x5FFF if self.bankswitched => {
+                self.banks[(addr - 0x5FF8) as usize] = data as usize % self.num_banks;
+            },
+            0x5FF6 | 0x5FF7 if self.bankswitched && self.fds_image.is_some() => {
+                self.fds_banks[(addr - 0x5FF6) as usize] = data as usize % self.num_banks;
+            },
+            0x6000..=0xDFFF if self.fds_image.is_some() => {
+                let bank = match addr {
+                    0x6000..=0x7FFF => self.fds_banks[((addr - 0x6000) as usize) / BANK_SIZE],
+                    _ => self.banks[((addr - 0x8000) as usize) / BANK_SIZE],
+                };
+                self.prg_rom.write(bank, (addr as usize) % BANK_SIZE, data);
+            },
+            0x6000..=0x7FFF => self.prg_ram[(addr - 0x6000) as usize] = data,
+            _ => self.write_chips(addr, data),
+        }
+    }
+
+    fn read_chr(&self, _addr: u16) -> u8 {
+        0
+    }
+
+    fn write_chr(&mut self, _addr: u16, _value: u8) {}
+
+    fn cpu_tick(&mut self) {
+        if self.play_enabled {
+            // The play speed is in microseconds. Count in millionths of a CPU cycle to avoid drift
+            self.play_counter += 1_000_000;
+            if self.play_counter >= self.play_period {
+                self.play_counter -= self.play_period;
+                self.play_pending = true;
+            }
+        }
+
+        let chips = &mut self.chips;
+        if let Some(ref mut vrc6) = chips.vrc6 { vrc6.cpu_tick(); }
+        if let Some(ref mut vrc7) = chips.vrc7 { vrc7.cpu_tick(); }
+        if let Some(ref mut sunsoft5b) = chips.sunsoft5b { sunsoft5b.cpu_tick(); }
+        if let Some(ref mut mmc5) = chips.mmc5 { mmc5.cpu_tick(); }
+    }
+
+    fn irq(&self) -> bool {
+        self.play_pending
+    }
+
+    fn expansion_audio(&self) -> Sample {
+        let chips = &self.chips;
+
+        chips.vrc6.as_ref().map_or(0.0, |chip| chip.output())
+            + chips.vrc7.as_ref().map_or(0.0, |chip| chip.output())
+            + chips.sunsoft5b.as_ref().map_or(0.0, |chip| chip.output())
+            + chips.mmc5.as_ref().map_or(0.0, |chip| chip.output())
+    }
+
+    fn audio_register(&self, addr: u16) -> Option<u16> {
+        let chips = &self.chips;
+
+        match addr {
+            0x9000..=0x9003 | 0xA000..=0xA002 | 0xB000..=0xB002 if chips.vrc6.is_some() => Some(addr),
+            0x9010 | 0x9030 if chips.vrc7.is_some() => Some(addr),
+            0xC000..=0xFFFF if chips.sunsoft5b.is_some() => Some(addr & 0xE000),
+            0x5000..=0x5015 if chips.mmc5.is_some() => Some(addr),
+            _ => None,
+        }
+    }
+
+    fn get_battery_ram(&self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn driver_vectors() {
+        let mapper = init_mapper(None, 0x8000);
+
+        assert_eq!(mapper.read(0xFFFC), 0x00);
+        assert_eq!(mapper.read(0xFFFD), 0x41);
+        assert_eq!(mapper.read(DRIVER_IRQ), 0x48);
+        assert_eq!(mapper.read(DRIVER_NMI), 0x40);
+        assert_eq!(mapper.read(INIT_TRAMPOLINE), 0x4C);
+        assert_eq!(mapper.read(0x4191), 0x03);
+        assert_eq!(mapper.read(0x4195), 0x80);
+    }
+
+    #[test]
+    fn non_bankswitched_load() {
+        let mapper = init_mapper(None, 0x8100);
+
+        assert_eq!(mapper.read(0x80FF), 0x00);
+        assert_eq!(mapper.read(0x8100), 0x00);
+        assert_eq!(mapper.read(0x9100), 0x01);
+    }
+
+    #[test]
+    fn bankswitching() {
+        let mut mapper = init_mapper(Some([0, 1, 2, 3, 4, 5, 6, 7]), 0x8000);
+
+        assert_eq!(mapper.read(0x9000), 0x01);
+
+        mapper.write(0x5FF9, 0x05);
+        assert_eq!(mapper.read(0x9000), 0x05);
+
+        // Banks are restored when a track is selected
+        mapper.reset(1);
+        assert_eq!(mapper.read(0x9000), 0x01);
+        assert_eq!(mapper.read(REG_TRACK), 1);
+    }
+
+    #[test]
+    fn track_out_of_range() {
+        let mut mapper = init_mapper(None, 0x8000);
+
+        mapper.reset(5);
+        assert_eq!(mapper.read(REG_TRACK), 1);
+    }
+
+    #[test]
+    fn fds_ram() {
+        let mut mapper = init_fds_mapper(None, 0x6000);
+
+        assert_eq!(mapper.read(0x6000), 0x00);
+        assert_eq!(mapper.read(0x7000), 0x01);
+        assert_eq!(mapper.read(0x8000), 0x02);
+
+        mapper.write(0x8000, 0xDE);
+        assert_eq!(mapper.read(0x8000), 0xDE);
+
+        // The tune is reloaded when a track is selected
+        mapper.reset(0);
+        assert_eq!(mapper.read(0x8000), 0x02);
+    }
+
+    #[test]
+    fn fds_bankswitching() {
+        let mut mapper = init_fds_mapper(Some([0, 1, 2, 3, 4, 5, 6, 7]), 0x8000);
+
+        assert_eq!(mapper.read(0x6000), 0x06);
+        assert_eq!(mapper.read(0x7000), 0x07);
+
+        mapper.write(0x5FF6, 0x03);
+        assert_eq!(mapper.read(0x6000), 0x03);
+    }
+
+    #[test]
+    fn play_timer() {
+        let mut mapper = init_mapper(None, 0x8000);
+
+        mapper.cpu_tick();
+        assert!(!mapper.irq());
+
+        mapper.write(REG_PLAY_ENABLE, 0x01);
+
+        // 16639 microseconds at the NTSC clock rate
+        let cycles = (0..40_000).position(|_| {
+            mapper.cpu_tick();
+            mapper.irq()
+        });
+        assert_eq!(cycles, Some(29_780));
+
+        mapper.write(REG_PLAY_ACK, 0x00);
+        assert!(!mapper.irq());
+    }
+
+    /// An NSF with eight 4 KB banks, each filled with its index
+    fn init_mapper(bank_init: Option<[u8; 8]>, load_addr: u16) -> NsfMapper {
+        NsfMapper::from(&Nsf::from(nsf_data(bank_init, load_addr)).unwrap())
+    }
+
+    /// An FDS NSF with ten 4 KB banks, each filled with its index
+    fn init_fds_mapper(bank_init: Option<[u8; 8]>, load_addr: u16) -> NsfMapper {
+        let mut data = nsf_data(bank_init, load_addr);
+        data[0x7B] = 0x04;
+        data.extend((kb!(32)..kb!(40)).map(|i| (i / BANK_SIZE) as u8));
+
+        NsfMapper::from(&Nsf::from(data).unwrap())
+    }
+
+    fn nsf_data(bank_init: Option<[u8; 8]>, load_addr: u16) -> Vec<u8> {
+        let mut data = vec![0u8; 0x80];
+        data[..5].copy_from_slice(b"NESM\x1A");
+        data[0x06] = 2;
+        data[0x07] = 1;
+        data[0x08..0x0A].copy_from_slice(&load_addr.to_le_bytes());
+        data[0x0A..0x0C].copy_from_slice(&0x8003u16.to_le_bytes());
+        data[0x0C..0x0E].copy_from_slice(&0x8006u16.to_le_bytes());
+        if let Some(banks) = bank_init {
+            data[0x70..0x78].copy_from_slice(&banks);
+        }
+        data.extend((0..kb!(32)).map(|i| (i / BANK_SIZE) as u8));
+
+        data
+    }
+}
@@ -75,6 +75,14 @@ impl MapperControl for Unrom {
         }
     }
 
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        match addr {
+            0x8000..=0xBFFF => Some(self.rom_bank_selection),
+            0xC000..=0xFFFF => Some(self.prg_rom.num_banks() - 1),
+            _ => None,
+        }
+    }
+
     fn read_chr(&self, addr: u16) -> u8 {
         self.chr_ram[addr as usize]
     }
@@ -115,6 +123,16 @@ mod tests {
         assert_eq!(irq, 0x6001);
     }
 
+    #[test]
+    fn prg_bank() {
+        let mut unrom = init_unrom(vec![0; PRG_ROM_BANK_SIZE * 4], PRG_ROM_BANK_SIZE);
+        unrom.write(0x8000, 2);
+
+        assert_eq!(unrom.prg_bank(0x6000), None);
+        assert_eq!(unrom.prg_bank(0x8000), Some(2));
+        assert_eq!(unrom.prg_bank(0xFFFF), Some(3));
+    }
+
     fn init_unrom(data: Vec<u8>, bank_size: usize) -> Unrom {
         Unrom {
             prg_rom: Memory::new(data, bank_size),
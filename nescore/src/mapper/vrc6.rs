@@ -0,0 +1,275 @@
+//
+// mapper/vrc6.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+
+use super::{MapperControl, Mirroring};
+use super::mem::Memory;
+use super::vrcirq::VrcIrq;
+use super::audio::{ExpansionAudio, Vrc6Audio};
+
+use crate::apu::Sample;
+use crate::cart::Cartridge;
+
+const PRG_RAM_SIZE: usize = kb!(8);
+
+/// Konami VRC6 (Mapper 24 and 26)
+/// https://wiki.nesdev.com/w/index.php/VRC6
+///
+/// Only the CHR banking mode used by commercial games (1 KB banks) is supported.
+pub struct Vrc6 {
+    prg_rom: Memory, // 8 KB banks
+    prg_ram: [u8; PRG_RAM_SIZE],
+    chr_data: Memory, // 1 KB banks
+    chr_ram: bool,
+
+    swap_lines: bool, // Mapper 26 swaps address lines A0 and A1
+
+    prg_bank_16k: usize,
+    prg_bank_8k: usize,
+    chr_banks: [usize; 8],
+    prg_ram_enabled: bool,
+    mirroring: Mirroring,
+
+    irq: VrcIrq,
+    audio: Vrc6Audio,
+}
+
+impl From<Cartridge> for Vrc6 {
+    fn from(cart: Cartridge) -> Self {
+        let (info, prg_rom, chr_rom, sav_ram) = cart.into_parts();
+
+        let chr_ram = chr_rom.is_empty();
+        let chr_data = if chr_ram { vec![0; kb!(8)] } else { chr_rom };
+
+        let mut prg_ram = [0u8; PRG_RAM_SIZE];
+        for (dest, src) in prg_ram.iter_mut().zip(sav_ram.iter()) {
+            *dest = *src;
+        }
+
+        Vrc6 {
+            prg_rom: Memory::new(prg_rom, kb!(8)),
+            prg_ram,
+            chr_data: Memory::new(chr_data, kb!(1)),
+            chr_ram,
+
+            swap_lines: info.mapper == 26,
+
+            prg_bank_16k: 0,
+            prg_bank_8k: 0,
+            chr_banks: [0; 8],
+            prg_ram_enabled: false,
+            mirroring: Mirroring::Vertical,
+
+            irq: VrcIrq::default(),
+            audio: Vrc6Audio::default(),
+        }
+    }
+}
+
+impl Vrc6 {
+    /// Normalize a register address to the mapper 24 layout
+    fn register(&self, addr: u16) -> u16 {
+        if self.swap_lines {
+            (addr & 0xF000) | ((addr & 0x01) << 1) | ((addr & 0x02) >> 1)
+        }
+        else {
+            addr & 0xF003
+        }
+    }
+
+    fn write_control(&mut self, value: u8) {
+        self.mirroring = match bit_group!(value, 0x03, 2) {
+            0 => Mirroring::Vertical,
+            1 => Mirroring::Horizontal,
+            2 => Mirroring::OneScreenLower,
+            3 => Mirroring::OneScreenUpper,
+            _ => unreachable!(),
+        };
+
+        self.prg_ram_enabled = bit_is_set!(value, 7);
+    }
+
+    fn write_register(&mut self, reg: u16, data: u8) {
+        match reg {
+            0x8000..=0x8003 => self.prg_bank_16k = (data & 0x0F) as usize % (self.prg_rom.num_banks() / 2).max(1),
+            0x9000..=0x9003 | 0xA000..=0xA002 | 0xB000..=0xB002 => self.audio.write(reg, data),
+            0xB003 => self.write_control(data),
+            0xC000..=0xC003 => self.prg_bank_8k = (data & 0x1F) as usize % self.prg_rom.num_banks(),
+            0xD000..=0xD003 => self.chr_banks[(reg & 0x03) as usize] = data as usize % self.chr_data.num_banks(),
+            0xE000..=0xE003 => self.chr_banks[4 + (reg & 0x03) as usize] = data as usize % self.chr_data.num_banks(),
+            0xF000 => self.irq.write_latch(data),
+            0xF001 => self.irq.write_control(data),
+            0xF002 => self.irq.acknowledge(),
+            _ => {},
+        }
+    }
+}
+
+impl MapperControl for Vrc6 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x6000..=0x7FFF if self.prg_ram_enabled => self.prg_ram[(addr - 0x6000) as usize],
+            0x8000..=0xBFFF => self.prg_rom.read(self.prg_bank_16k * 2, (addr - 0x8000) as usize),
+            0xC000..=0xDFFF => self.prg_rom.read(self.prg_bank_8k, (addr - 0xC000) as usize),
+            0xE000..=0xFFFF => self.prg_rom.read_last((addr - 0xE000) as usize),
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x6000..=0x7FFF if self.prg_ram_enabled => self.prg_ram[(addr - 0x6000) as usize] = data,
+            0x8000..=0xFFFF => self.write_register(self.register(addr), data),
+            _ => {},
+        }
+    }
+
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        match addr {
+            0x8000..=0xBFFF => Some(self.prg_bank_16k),
+            0xC000..=0xDFFF => Some(self.prg_bank_8k / 2),
+            0xE000..=0xFFFF => Some((self.prg_rom.num_banks() - 1) / 2),
+            _ => None,
+        }
+    }
+
+    fn read_chr(&self, addr: u16) -> u8 {
+        if self.chr_ram {
+            self.chr_data.read(0, addr as usize)
+        }
+        else {
+            self.chr_data.read(self.chr_banks[(addr / 0x400) as usize], (addr % 0x400) as usize)
+        }
+    }
+
+    fn write_chr(&mut self, addr: u16, value: u8) {
+        if self.chr_ram {
+            self.chr_data.write(0, addr as usize, value);
+        }
+    }
+
+    fn mirroring(&self) -> Option<Mirroring> {
+        Some(self.mirroring)
+    }
+
+    fn cpu_tick(&mut self) {
+        self.irq.cpu_tick();
+        self.audio.cpu_tick();
+    }
+
+    fn irq(&self) -> bool {
+        self.irq.pending()
+    }
+
+    fn expansion_audio(&self) -> Sample {
+        self.audio.output()
+    }
+
+    fn audio_register(&self, addr: u16) -> Option<u16> {
+        match self.register(addr) {
+            reg @ (0x9000..=0x9003 | 0xA000..=0xA002 | 0xB000..=0xB002) => Some(reg),
+            _ => None,
+        }
+    }
+
+    fn get_battery_ram(&self) -> Vec<u8> {
+        self.prg_ram.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cart::CartridgeInfo;
+
+    #[test]
+    fn prg_banks() {
+        let mut vrc6 = init_vrc6(24);
+
+        vrc6.write(0x8000, 0x01);
+        vrc6.write(0xC000, 0x05);
+
+        assert_eq!(vrc6.read(0x8000), 2);
+        assert_eq!(vrc6.read(0xA000), 3);
+        assert_eq!(vrc6.read(0xC000), 5);
+        assert_eq!(vrc6.read(0xE000), 7);
+    }
+
+    #[test]
+    fn banks_wrap() {
+        let mut vrc6 = init_vrc6(24);
+
+        vrc6.write(0x8000, 0x05);
+        vrc6.write(0xC000, 0x1B);
+        vrc6.write(0xD000, 0xFA);
+
+        assert_eq!(vrc6.read(0x8000), 2);
+        assert_eq!(vrc6.read(0xC000), 3);
+        assert_eq!(vrc6.read_chr(0x0000), 2);
+    }
+
+    #[test]
+    fn prg_ram() {
+        let mut vrc6 = init_vrc6(24);
+
+        vrc6.write(0x7000, 0xDE);
+        assert_eq!(vrc6.read(0x7000), 0x00);
+
+        vrc6.write(0xB003, 0x80);
+        vrc6.write(0x7000, 0xDE);
+        assert_eq!(vrc6.read(0x7000), 0xDE);
+    }
+
+    #[test]
+    fn chr_banks_swapped_lines() {
+        let mut vrc6 = init_vrc6(26);
+
+        // $D001 on mapper 26 is $D002 on mapper 24
+        vrc6.write(0xD001, 0x03);
+        assert_eq!(vrc6.read_chr(0x0800), 3);
+    }
+
+    #[test]
+    fn irq() {
+        let mut vrc6 = init_vrc6(24);
+
+        vrc6.write(0xF000, 0xFF);
+        vrc6.write(0xF001, 0x06);
+        vrc6.cpu_tick();
+        assert!(vrc6.irq());
+
+        vrc6.write(0xF002, 0x00);
+        assert!(!vrc6.irq());
+    }
+
+    #[test]
+    fn audio() {
+        let mut vrc6 = init_vrc6(26);
+
+        // Pulse 1 in constant mode, at $9002 for mapper 24
+        vrc6.write(0x9000, 0x8F);
+        vrc6.write(0x9001, 0x80);
+        vrc6.cpu_tick();
+
+        assert!(vrc6.expansion_audio() > 0.0);
+    }
+
+    /// VRC6 with 64 KB of PRG ROM and 8 KB of CHR ROM. Each 8 KB PRG bank and 1 KB CHR bank is filled with its index
+    fn init_vrc6(mapper: u8) -> Vrc6 {
+        let header = [
+            0x4E, 0x45, 0x53, 0x1A,
+            4, 1,
+            (mapper & 0x0F) << 4, mapper & 0xF0,
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let info = CartridgeInfo::from(&header[..]).unwrap();
+
+        let prg_rom = (0..kb!(64)).map(|i| (i / kb!(8)) as u8).collect();
+        let chr_rom = (0..kb!(8)).map(|i| (i / kb!(1)) as u8).collect();
+
+        Vrc6::from(Cartridge::from_parts(info, prg_rom, chr_rom, vec![]))
+    }
+}
@@ -0,0 +1,303 @@
+//
+// mapper/vrc7.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+
+use super::{MapperControl, Mirroring};
+use super::mem::Memory;
+use super::vrcirq::VrcIrq;
+use super::audio::{ExpansionAudio, Vrc7Audio};
+
+use crate::apu::Sample;
+use crate::cart::Cartridge;
+
+const PRG_RAM_SIZE: usize = kb!(8);
+
+/// Konami VRC7 (Mapper 85)
+/// https://wiki.nesdev.com/w/index.php/VRC7
+///
+/// Boards connect either A4 (VRC7a) or A3 (VRC7b) to the mapper's register select line, so both are accepted.
+pub struct Vrc7 {
+    prg_rom: Memory, // 8 KB banks
+    prg_ram: [u8; PRG_RAM_SIZE],
+    chr_data: Memory, // 1 KB banks
+    num_chr_banks: usize,
+    chr_ram: bool,
+
+    prg_banks: [usize; 3],
+    chr_banks: [usize; 8],
+    prg_ram_enabled: bool,
+    mirroring: Mirroring,
+    audio_silenced: bool,
+
+    irq: VrcIrq,
+    audio: Vrc7Audio,
+}
+
+impl From<Cartridge> for Vrc7 {
+    fn from(cart: Cartridge) -> Self {
+        let (_, prg_rom, chr_rom, sav_ram) = cart.into_parts();
+
+        let chr_ram = chr_rom.is_empty();
+        let chr_data = if chr_ram { vec![0; kb!(8)] } else { chr_rom };
+        let num_chr_banks = chr_data.len() / kb!(1);
+
+        let mut prg_ram = [0u8; PRG_RAM_SIZE];
+        for (dest, src) in prg_ram.iter_mut().zip(sav_ram.iter()) {
+            *dest = *src;
+        }
+
+        Vrc7 {
+            prg_rom: Memory::new(prg_rom, kb!(8)),
+            prg_ram,
+            chr_data: Memory::new(chr_data, kb!(1)),
+            num_chr_banks,
+            chr_ram,
+
+            prg_banks: [0; 3],
+            chr_banks: [0; 8],
+            prg_ram_enabled: false,
+            mirroring: Mirroring::Vertical,
+            audio_silenced: false,
+
+            irq: VrcIrq::default(),
+            audio: Vrc7Audio::default(),
+        }
+    }
+}
+
+impl Vrc7 {
+    fn write_control(&mut self, value: u8) {
+        self.mirroring = match value & 0x03 {
+            0 => Mirroring::Vertical,
+            1 => Mirroring::Horizontal,
+            2 => Mirroring::OneScreenLower,
+            3 => Mirroring::OneScreenUpper,
+            _ => unreachable!(),
+        };
+
+        self.audio_silenced = bit_is_set!(value, 6);
+        if self.audio_silenced {
+            self.audio.reset();
+        }
+
+        self.prg_ram_enabled = bit_is_set!(value, 7);
+    }
+
+    fn chr_bank(&self, addr: u16) -> usize {
+        self.chr_banks[(addr / 0x400) as usize] % self.num_chr_banks
+    }
+
+    fn write_register(&mut self, addr: u16, data: u8) {
+        // Sound registers decode A4 and A5
+        if let 0x9010 | 0x9030 = addr & 0xF030 {
+            self.audio.write(addr & 0xF030, data);
+            return;
+        }
+
+        let high = addr & 0x0018 != 0;
+
+        match (addr & 0xF000, high) {
+            (0x8000, false) => self.prg_banks[0] = (data & 0x3F) as usize % self.prg_rom.num_banks(),
+            (0x8000, true)  => self.prg_banks[1] = (data & 0x3F) as usize % self.prg_rom.num_banks(),
+            (0x9000, false) => self.prg_banks[2] = (data & 0x3F) as usize % self.prg_rom.num_banks(),
+            (0xA000..=0xD000, _) => {
+                let idx = (((addr - 0xA000) >> 12) * 2) as usize + high as usize;
+                self.chr_banks[idx] = data as usize;
+            },
+            (0xE000, false) => self.write_control(data),
+            (0xE000, true)  => self.irq.write_latch(data),
+            (0xF000, false) => self.irq.write_control(data),
+            (0xF000, true)  => self.irq.acknowledge(),
+            _ => {},
+        }
+    }
+}
+
+impl MapperControl for Vrc7 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x6000..=0x7FFF if self.prg_ram_enabled => self.prg_ram[(addr - 0x6000) as usize],
+            0x8000..=0xDFFF => {
+                let slot = ((addr - 0x8000) / 0x2000) as usize;
+                self.prg_rom.read(self.prg_banks[slot], (addr & 0x1FFF) as usize)
+            },
+            0xE000..=0xFFFF => self.prg_rom.read_last((addr - 0xE000) as usize),
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x6000..=0x7FFF if self.prg_ram_enabled => self.prg_ram[(addr - 0x6000) as usize] = data,
+            0x8000..=0xFFFF => self.write_register(addr, data),
+            _ => {},
+        }
+    }
+
+    fn prg_bank(&self, addr: u16) -> Option<usize> {
+        match addr {
+            0x8000..=0xDFFF => Some(self.prg_banks[((addr - 0x8000) / 0x2000) as usize] / 2),
+            0xE000..=0xFFFF => Some((self.prg_rom.num_banks() - 1) / 2),
+            _ => None,
+        }
+    }
+
+    fn read_chr(&self, addr: u16) -> u8 {
+        self.chr_data.read(self.chr_bank(addr), (addr % 0x400) as usize)
+    }
+
+    fn write_chr(&mut self, addr: u16, value: u8) {
+        if self.chr_ram {
+            let bank = self.chr_bank(addr);
+            self.chr_data.write(bank, (addr % 0x400) as usize, value);
+        }
+    }
+
+    fn mirroring(&self) -> Option<Mirroring> {
+        Some(self.mirroring)
+    }
+
+    fn cpu_tick(&mut self) {
+        self.irq.cpu_tick();
+
+        if !self.audio_silenced {
+            self.audio.cpu_tick();
+        }
+    }
+
+    fn irq(&self) -> bool {
+        self.irq.pending()
+    }
+
+    fn expansion_audio(&self) -> Sample {
+        self.audio.output()
+    }
+
+    fn audio_register(&self, addr: u16) -> Option<u16> {
+        match addr & 0xF030 {
+            reg @ (0x9010 | 0x9030) => Some(reg),
+            _ => None,
+        }
+    }
+
+    fn get_battery_ram(&self) -> Vec<u8> {
+        self.prg_ram.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cart::CartridgeInfo;
+
+    #[test]
+    fn prg_banks() {
+        let mut vrc7 = init_vrc7(vec![]);
+
+        vrc7.write(0x8000, 0x01);
+        vrc7.write(0x8010, 0x02);
+        vrc7.write(0x9000, 0x03);
+
+        assert_eq!(vrc7.read(0x8000), 1);
+        assert_eq!(vrc7.read(0xA000), 2);
+        assert_eq!(vrc7.read(0xC000), 3);
+        assert_eq!(vrc7.read(0xE000), 7);
+
+        // VRC7b uses A3
+        vrc7.write(0x8008, 0x04);
+        assert_eq!(vrc7.read(0xA000), 4);
+    }
+
+    #[test]
+    fn prg_banks_wrap() {
+        // 128 KB of PRG ROM, sixteen 8 KB banks
+        let header = [
+            0x4E, 0x45, 0x53, 0x1A,
+            8, 0,
+            0x50, 0x50,
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let info = CartridgeInfo::from(&header[..]).unwrap();
+        let prg_rom = (0..kb!(128)).map(|i| (i / kb!(8)) as u8).collect();
+        let mut vrc7 = Vrc7::from(Cartridge::from_parts(info, prg_rom, vec![], vec![]));
+
+        vrc7.write(0x8000, 0x3F);
+        vrc7.write(0x9000, 0x12);
+
+        assert_eq!(vrc7.read(0x8000), 15);
+        assert_eq!(vrc7.read(0xC000), 2);
+    }
+
+    #[test]
+    fn chr_ram_banks() {
+        let mut vrc7 = init_vrc7(vec![]);
+
+        // Map the 1 KB at $0400 onto CHR RAM bank 0
+        vrc7.write(0xA010, 0x00);
+        vrc7.write_chr(0x0000, 0xDE);
+
+        assert_eq!(vrc7.read_chr(0x0400), 0xDE);
+    }
+
+    #[test]
+    fn chr_rom_banks() {
+        let mut vrc7 = init_vrc7((0..kb!(8)).map(|i| (i / kb!(1)) as u8).collect());
+
+        vrc7.write(0xD010, 0x05);
+        assert_eq!(vrc7.read_chr(0x1C00), 5);
+    }
+
+    #[test]
+    fn irq() {
+        let mut vrc7 = init_vrc7(vec![]);
+
+        vrc7.write(0xE010, 0xFF);
+        vrc7.write(0xF000, 0x06);
+        vrc7.cpu_tick();
+        assert!(vrc7.irq());
+
+        vrc7.write(0xF010, 0x00);
+        assert!(!vrc7.irq());
+    }
+
+    #[test]
+    fn audio() {
+        let mut vrc7 = init_vrc7(vec![]);
+
+        // Channel 0: instrument 1 at full volume, keyed on
+        vrc7.write(0x9010, 0x30);
+        vrc7.write(0x9030, 0x10);
+        vrc7.write(0x9010, 0x10);
+        vrc7.write(0x9030, 0x20);
+        vrc7.write(0x9010, 0x20);
+        vrc7.write(0x9030, 0x19);
+
+        let heard = (0..10_000).any(|_| {
+            vrc7.cpu_tick();
+            vrc7.expansion_audio() != 0.0
+        });
+        assert!(heard);
+
+        // Silencing resets the chip
+        vrc7.write(0xE000, 0x40);
+        assert_eq!(vrc7.expansion_audio(), 0.0);
+    }
+
+    /// VRC7 with 64 KB of PRG ROM. Each 8 KB PRG bank is filled with its index
+    fn init_vrc7(chr_rom: Vec<u8>) -> Vrc7 {
+        let header = [
+            0x4E, 0x45, 0x53, 0x1A,
+            4, (chr_rom.len() / kb!(8)) as u8,
+            0x50, 0x50,
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let info = CartridgeInfo::from(&header[..]).unwrap();
+
+        let prg_rom = (0..kb!(64)).map(|i| (i / kb!(8)) as u8).collect();
+
+        Vrc7::from(Cartridge::from_parts(info, prg_rom, chr_rom, vec![]))
+    }
+}
@@ -0,0 +1,131 @@
+//
+// mapper/vrcirq.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+
+/// CPU cycles per scanline, in thirds
+const PRESCALER_RELOAD: i16 = 341;
+
+/// IRQ counter shared by the Konami VRC4, VRC6 and VRC7
+/// https://wiki.nesdev.com/w/index.php/VRC_IRQ
+#[derive(Default)]
+pub struct VrcIrq {
+    latch: u8,
+    counter: u8,
+    prescaler: i16,
+
+    enabled: bool,
+    enable_after_ack: bool,
+    cycle_mode: bool, // Clock every CPU cycle instead of every scanline
+
+    pending: bool,
+}
+
+impl VrcIrq {
+    pub fn write_latch(&mut self, value: u8) {
+        self.latch = value;
+    }
+
+    pub fn write_control(&mut self, value: u8) {
+        self.enable_after_ack = bit_is_set!(value, 0);
+        self.enabled = bit_is_set!(value, 1);
+        self.cycle_mode = bit_is_set!(value, 2);
+
+        self.pending = false;
+
+        if self.enabled {
+            self.counter = self.latch;
+            self.prescaler = PRESCALER_RELOAD;
+        }
+    }
+
+    pub fn acknowledge(&mut self) {
+        self.pending = false;
+        self.enabled = self.enable_after_ack;
+    }
+
+    pub fn cpu_tick(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        if self.cycle_mode {
+            self.clock_counter();
+        }
+        else {
+            // Scanline mode divides the CPU clock by 113.667
+            self.prescaler -= 3;
+            if self.prescaler <= 0 {
+                self.prescaler += PRESCALER_RELOAD;
+                self.clock_counter();
+            }
+        }
+    }
+
+    pub fn pending(&self) -> bool {
+        self.pending
+    }
+
+    fn clock_counter(&mut self) {
+        if self.counter == 0xFF {
+            self.counter = self.latch;
+            self.pending = true;
+        }
+        else {
+            self.counter += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_mode() {
+        let mut irq = VrcIrq::default();
+        irq.write_latch(0xFD);
+        irq.write_control(0x07);
+
+        irq.cpu_tick();
+        irq.cpu_tick();
+        assert!(!irq.pending());
+
+        irq.cpu_tick();
+        assert!(irq.pending());
+
+        // Acknowledging keeps the counter running when E was set
+        irq.acknowledge();
+        assert!(!irq.pending());
+
+        for _ in 0..3 {
+            irq.cpu_tick();
+        }
+        assert!(irq.pending());
+    }
+
+    #[test]
+    fn scanline_mode() {
+        let mut irq = VrcIrq::default();
+        irq.write_latch(0xFF);
+        irq.write_control(0x02);
+
+        // One scanline is 341 / 3 CPU cycles
+        for _ in 0..113 {
+            irq.cpu_tick();
+        }
+        assert!(!irq.pending());
+
+        irq.cpu_tick();
+        assert!(irq.pending());
+
+        // Without E, the counter stops after acknowledgement
+        irq.acknowledge();
+        for _ in 0..1000 {
+            irq.cpu_tick();
+        }
+        assert!(!irq.pending());
+    }
+}
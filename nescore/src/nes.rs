@@ -5,19 +5,20 @@
 // @date Sep 17 2020
 //
 use crate::cart::Cartridge;
-use crate::cpu::{Cpu, bus::CpuIoBus};
+use crate::cpu::{Cpu, CallStack, bus::CpuIoBus};
 use crate::ppu::{Ppu, bus::PpuIoBus};
 use crate::apu::{Apu, bus::ApuIoBus};
 use crate::joy::Joy;
-use crate::mapper::Mapper;
+use crate::mapper::{Mapper, NsfMapper};
+use crate::nsf::Nsf;
+use crate::vgm::{AudioLog, AudioLogRef};
 use crate::common::Clockable;
 
-use crate::ppu::Pixel;
-use crate::apu::Sample;
+use crate::ppu::{Pixel, RawPixel, Palette};
+use crate::ppu::debug::{self as ppu_debug, DebugImage, SpriteInfo, NametableOverlays};
+use crate::apu::{Sample, AudioChannel, AudioFilter};
 use crate::joy::{Controller, Button};
 
-// use crate::utils::sampler::DownSampler;
-
 use crate::ppu::{DISPLAY_WIDTH, DISPLAY_HEIGHT};
 
 /// Buffer for audio data
@@ -30,12 +31,20 @@ use std::cell::RefCell;
 #[cfg(feature="events")]
 use std::sync::mpsc::{channel, Receiver};
 
-#[derive(Clone, Copy)]
+/// Video output format
+///
+/// Packed 16 and 32-bit formats are written in little-endian byte order.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum PixelFormat {
     RGB8,
     RGBA8,
     GBRA8,
     BGRA8,
+    RGB565,
+    XRGB8888,
+    ARGB1555,
+    /// Raw 9-bit PPU output: 6-bit color index and 3 emphasis bits, as a 16-bit value
+    Indexed,
 }
 
 impl PixelFormat {
@@ -45,6 +54,26 @@ impl PixelFormat {
             PixelFormat::RGBA8 => 4,
             PixelFormat::GBRA8 => 4,
             PixelFormat::BGRA8 => 4,
+            PixelFormat::RGB565 => 2,
+            PixelFormat::XRGB8888 => 4,
+            PixelFormat::ARGB1555 => 2,
+            PixelFormat::Indexed => 2,
+        }
+    }
+
+    /// Write an RGB pixel in this format. `output` must be `num_bytes()` long
+    pub(crate) fn write_rgb(&self, (r, g, b): Pixel, output: &mut [u8]) {
+        let (r16, g16, b16) = (r as u16, g as u16, b as u16);
+
+        match *self {
+            PixelFormat::RGB8 =>     output.copy_from_slice(&[r, g, b]),
+            PixelFormat::RGBA8 =>    output.copy_from_slice(&[r, g, b, 255]),
+            PixelFormat::GBRA8 =>    output.copy_from_slice(&[g, b, r, 255]),
+            PixelFormat::BGRA8 =>    output.copy_from_slice(&[b, g, r, 255]),
+            PixelFormat::RGB565 =>   output.copy_from_slice(&(((r16 >> 3) << 11) | ((g16 >> 2) << 5) | (b16 >> 3)).to_le_bytes()),
+            PixelFormat::XRGB8888 => output.copy_from_slice(&[b, g, r, 255]),
+            PixelFormat::ARGB1555 => output.copy_from_slice(&(0x8000 | ((r16 >> 3) << 10) | ((g16 >> 3) << 5) | (b16 >> 3)).to_le_bytes()),
+            PixelFormat::Indexed =>  panic!("RGB pixels cannot be converted to palette indices"),
         }
     }
 }
@@ -87,11 +116,16 @@ pub struct Nes {
     apu: Rc<RefCell<Apu>>,           // NES Audio Processing Unit
     joy: Rc<RefCell<Joy>>,           // NES Joystick
     mapper: Option<Mapper>,          // Cartridge Mapper
+    nsf: Option<Rc<RefCell<NsfMapper>>>, // NSF player, when playing an NSF file
+    audio_log: AudioLogRef,          // Sound register writes, while recording
 
     sequencer: FrameSequencer,       // Used to clock components in the right order
 
     framebuffer: Vec<u8>,
+    framebuffer_idx: usize,          // Position of the next pixel in the frame
     pixel_format: PixelFormat,       // Pixel format
+    palette: Palette,                // Converts raw PPU output to RGB
+    layer_buffers: Option<(Vec<u8>, Vec<u8>)>, // Background and sprite layers of the last frame
 }
 
 impl Default for Nes {
@@ -105,11 +139,16 @@ impl Default for Nes {
             apu: Rc::default(),
             joy: Rc::default(),
             mapper: None,
+            nsf: None,
+            audio_log: AudioLogRef::default(),
 
             sequencer: FrameSequencer::default(),
 
             framebuffer,
+            framebuffer_idx: 0,
             pixel_format,
+            palette: Palette::default(),
+            layer_buffers: None,
         }
     }
 }
@@ -140,14 +179,143 @@ impl Nes {
         self
     }
 
+    /// Builder function to load an NSF file. Playback starts from the file's first track
+    /// ```no_run
+    /// # use nescore::{Nes, Nsf};
+    /// let nsf = Nsf::from_path("/path/to/nsf").unwrap();
+    /// let mut nes = Nes::default().with_nsf(&nsf);
+    /// nes.select_track(2);
+    /// let samplebuffer = nes.run_audio(4096);
+    /// ```
+    pub fn with_nsf(mut self, nsf: &Nsf) -> Self {
+        self.insert_nsf(nsf);
+        self
+    }
+
     /// Set color output format
     pub fn pixel_format(mut self, pixel_format: PixelFormat) -> Self {
         self.pixel_format = pixel_format;
         self.framebuffer = vec![0; DISPLAY_WIDTH * DISPLAY_HEIGHT * pixel_format.num_bytes()];
 
+        if self.layer_buffers.is_some() {
+            self.layer_buffers = Some((self.framebuffer.clone(), self.framebuffer.clone()));
+        }
+
+        self
+    }
+
+    /// Set the palette used for video output
+    /// ```
+    /// # use nescore::{Nes, Palette};
+    /// let nes = Nes::default().palette(Palette::pal());
+    /// ```
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.set_palette(palette);
+        self
+    }
+
+    /// Change the palette used for video output
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Render more than 8 sprites per scanline to reduce flicker
+    /// ```
+    /// # use nescore::Nes;
+    /// let nes = Nes::default().sprite_limit(false);
+    /// ```
+    pub fn sprite_limit(self, enabled: bool) -> Self {
+        self.ppu.borrow_mut().set_sprite_limit(enabled);
+        self
+    }
+
+    /// Output the background and sprite layers as separate buffers, in addition to the composed frame
+    /// ```
+    /// # use nescore::Nes;
+    /// let mut nes = Nes::default().layer_output(true);
+    /// nes.emulate_frame();
+    /// let (background, sprites) = nes.layers().unwrap();
+    /// ```
+    pub fn layer_output(mut self, enabled: bool) -> Self {
+        self.ppu.borrow_mut().set_layer_output(enabled);
+        self.layer_buffers = if enabled { Some((self.framebuffer.clone(), self.framebuffer.clone())) } else { None };
+
+        self
+    }
+
+    /// Background and sprite layers of the last frame, in the configured pixel format. Transparent pixels are the
+    /// backdrop color
+    pub fn layers(&self) -> Option<(&[u8], &[u8])> {
+        self.layer_buffers.as_ref().map(|(background, sprites)| (&background[..], &sprites[..]))
+    }
+
+    /// Show or hide the background, regardless of PPUMASK
+    pub fn set_background_visible(&mut self, visible: bool) {
+        self.ppu.borrow_mut().set_background_visible(visible);
+    }
+
+    /// Show or hide all sprites, regardless of PPUMASK
+    pub fn set_sprites_visible(&mut self, visible: bool) {
+        self.ppu.borrow_mut().set_sprites_visible(visible);
+    }
+
+    /// Show or hide the sprite at the given OAM index. Indices of 64 and above are ignored
+    /// ```
+    /// # use nescore::Nes;
+    /// let mut nes = Nes::default();
+    /// nes.set_sprite_visible(0, false);
+    /// ```
+    pub fn set_sprite_visible(&mut self, index: usize, visible: bool) {
+        self.ppu.borrow_mut().set_sprite_visible(index, visible);
+    }
+
+    /// Set the analogue output filter applied to audio samples
+    /// ```
+    /// # use nescore::Nes;
+    /// # use nescore::specs::AudioFilter;
+    /// let nes = Nes::default().audio_filter(AudioFilter::Famicom);
+    /// ```
+    pub fn audio_filter(mut self, filter: AudioFilter) -> Self {
+        self.set_audio_filter(filter);
         self
     }
 
+    /// Change the analogue output filter applied to audio samples
+    pub fn set_audio_filter(&mut self, filter: AudioFilter) {
+        self.apu.borrow_mut().set_filter(&filter);
+    }
+
+    /// Mute or unmute an audio channel
+    /// ```
+    /// # use nescore::Nes;
+    /// # use nescore::specs::AudioChannel;
+    /// let mut nes = Nes::default();
+    /// nes.set_channel_muted(AudioChannel::Noise, true);
+    /// ```
+    pub fn set_channel_muted(&mut self, channel: AudioChannel, muted: bool) {
+        self.apu.borrow_mut().set_channel_muted(channel, muted);
+    }
+
+    /// Solo an audio channel. When any channel is soloed, only soloed channels are heard
+    pub fn set_channel_solo(&mut self, channel: AudioChannel, solo: bool) {
+        self.apu.borrow_mut().set_channel_solo(channel, solo);
+    }
+
+    /// Scale the volume of an audio channel before mixing. 1.0 is the channel's normal level
+    pub fn set_channel_volume(&mut self, channel: AudioChannel, volume: f32) {
+        self.apu.borrow_mut().set_channel_volume(channel, volume);
+    }
+
+    /// Scale the cartridge sound chip relative to the APU. 1.0 approximates the level on hardware
+    /// ```
+    /// # use nescore::Nes;
+    /// let mut nes = Nes::default();
+    /// nes.set_expansion_level(0.5);
+    /// ```
+    pub fn set_expansion_level(&mut self, level: f32) {
+        self.apu.borrow_mut().set_expansion_level(level);
+    }
+
     /// Builder function to set debug mode
     /// ```
     /// # use nescore::Nes;
@@ -166,27 +334,66 @@ impl Nes {
     /// let (videobuffer, audiobuffer) = nes.emulate_frame();
     /// ```
     ///
-    /// * `videobuffer` - A RGB8 frame buffer
+    /// * `videobuffer` - A frame buffer in the configured pixel format
     /// * `audiobuffer` - Raw APU output (This must be down sampled to host playback rate)
     pub fn emulate_frame(&mut self) -> (&[u8], SampleBuffer) {
-        // let mut framebuffer = [0x00u8; FRAME_BUFFER_SIZE];
-        let mut framebuffer_idx = 0usize;
+        let mut framebuffer = std::mem::take(&mut self.framebuffer);
+        let stride = DISPLAY_WIDTH * self.pixel_format.num_bytes();
+
+        let samplebuffer = self.emulate_frame_into(&mut framebuffer, stride);
+        self.framebuffer = framebuffer;
+
+        (&self.framebuffer, samplebuffer)
+    }
+
+    /// Run the emulator for a single frame, writing video output to a caller supplied buffer
+    ///
+    /// `stride` is the number of bytes between the start of each row. The buffer must be large enough to hold
+    /// `DISPLAY_HEIGHT` rows in the configured pixel format.
+    /// ```
+    /// # use nescore::Nes;
+    /// # use nescore::specs::{PixelFormat, DISPLAY_WIDTH, DISPLAY_HEIGHT};
+    /// let mut nes = Nes::default().pixel_format(PixelFormat::XRGB8888);
+    ///
+    /// // Render into a texture with padded rows
+    /// let stride = 1024;
+    /// let mut texture = vec![0u8; stride * DISPLAY_HEIGHT];
+    /// let audiobuffer = nes.emulate_frame_into(&mut texture, stride);
+    /// ```
+    pub fn emulate_frame_into(&mut self, buffer: &mut [u8], stride: usize) -> SampleBuffer {
+        let bytes_per_pixel = self.pixel_format.num_bytes();
+        let row_size = DISPLAY_WIDTH * bytes_per_pixel;
+
+        assert!(stride >= row_size, "Stride must be at least {} bytes", row_size);
+        assert!(buffer.len() >= stride * (DISPLAY_HEIGHT - 1) + row_size, "Buffer is too small for a frame");
 
         let mut samplebuffer: Vec<Sample> = Vec::new();
+        let mut layer_buffers = self.layer_buffers.take();
 
         if self.mapper.is_some() {
-            for _ in 0..crate::ppu::CYCLES_PER_FRAME {
+            // Frames vary in length, run until the PPU starts the next one
+            let frame = self.ppu.borrow().frame();
+
+            while self.ppu.borrow().frame() == frame {
                 // Clock the CPU, PPU and APU
                 let (pixel, sample) = self.clock_components();
 
                 if let Some(pixel) = pixel {
-                    let bytes = self.format_color_output(pixel);
-                    // TODO: This produces a clippy warning. However, `i` is not necessarily used to index the entirety
-                    // of `bytes`. There's probably a better way to do this...
-                    for i in 0..self.pixel_format.num_bytes() {
-                        self.framebuffer[framebuffer_idx] = bytes[i];
-                        framebuffer_idx = (framebuffer_idx + 1) % self.framebuffer.len();
+                    let x = self.framebuffer_idx % DISPLAY_WIDTH;
+                    let y = self.framebuffer_idx / DISPLAY_WIDTH;
+                    let offset = y * stride + x * bytes_per_pixel;
+
+                    self.format_color_output(pixel, &mut buffer[offset..offset + bytes_per_pixel]);
+
+                    if let Some((ref mut background, ref mut sprites)) = layer_buffers {
+                        let (bg_pixel, sp_pixel) = self.ppu.borrow().layer_pixels();
+                        let offset = self.framebuffer_idx * bytes_per_pixel;
+
+                        self.format_color_output(bg_pixel, &mut background[offset..offset + bytes_per_pixel]);
+                        self.format_color_output(sp_pixel, &mut sprites[offset..offset + bytes_per_pixel]);
                     }
+
+                    self.framebuffer_idx = (self.framebuffer_idx + 1) % (DISPLAY_WIDTH * DISPLAY_HEIGHT);
                 }
 
                 if let Some(sample) = sample {
@@ -195,17 +402,16 @@ impl Nes {
             }
         }
 
-        (&self.framebuffer, samplebuffer)
+        self.layer_buffers = layer_buffers;
+
+        samplebuffer
     }
 
-    /// Covert a PPU RGB pixel into different color formats
-    /// return 4 bytes with the color data and a bool that indicates if the last byte is used
-    fn format_color_output(&self, pixel: Pixel) -> [u8; 4] {
+    /// Convert a raw PPU pixel into the configured pixel format
+    fn format_color_output(&self, pixel: RawPixel, output: &mut [u8]) {
         match self.pixel_format {
-            PixelFormat::RGB8 =>  [pixel.0, pixel.1, pixel.2, 0],
-            PixelFormat::RGBA8 => [pixel.0, pixel.1, pixel.2, 255],
-            PixelFormat::GBRA8 => [pixel.1, pixel.2, pixel.0, 255],
-            PixelFormat::BGRA8 => [pixel.2, pixel.1, pixel.0, 255],
+            PixelFormat::Indexed => output.copy_from_slice(&(pixel & 0x1FF).to_le_bytes()),
+            pixel_format => pixel_format.write_rgb(self.palette.raw_color(pixel), output),
         }
     }
 
@@ -262,8 +468,8 @@ impl Nes {
     }
 
     /// Clock the NES components
-    fn clock_components(&mut self) -> (Option<Pixel>, Option<Sample>) {
-        let mut pixel: Option<Pixel> = None;
+    fn clock_components(&mut self) -> (Option<RawPixel>, Option<Sample>) {
+        let mut pixel: Option<RawPixel> = None;
         let mut sample: Option<Sample> = None;
 
         for event in self.sequencer.tick().iter() {
@@ -272,9 +478,27 @@ impl Nes {
                     pixel = self.ppu.borrow_mut().tick();
                 },
                 Event::CPU => {
+                    if let Some(ref mut log) = *self.audio_log.borrow_mut() {
+                        log.cpu_tick();
+                    }
+
                     self.cpu.borrow_mut().tick();
+
+                    let mut irq = self.apu.borrow().irq();
+
+                    if let Some(ref mapper) = self.mapper {
+                        mapper.borrow_mut().cpu_tick();
+                        irq |= mapper.borrow().irq();
+                    }
+
+                    // The IRQ line is level triggered and shared by the APU and the cartridge
+                    self.cpu.borrow_mut().set_irq_line(irq);
                 },
                 Event::APU => {
+                    if let Some(ref mapper) = self.mapper {
+                        self.apu.borrow_mut().set_expansion_output(mapper.borrow().expansion_audio());
+                    }
+
                     sample = Some(self.apu.borrow_mut().tick());
                 },
                 Event::None => {},
@@ -294,8 +518,32 @@ impl Nes {
         // Consume provided cartridge and get the mapper
         let mapper = crate::mapper::from_cartridge(cart);
 
+        self.nsf = None;
+        self.connect(mapper);
+    }
+
+    /// Load an NSF file in place of a cartridge
+    pub fn insert_nsf(&mut self, nsf: &Nsf) {
+        let player = Rc::new(RefCell::new(NsfMapper::from(nsf)));
+
+        self.connect(player.clone());
+        self.nsf = Some(player);
+    }
+
+    /// Restart NSF playback from the given track, starting from 0. Tracks past the end of the tune select the last track.
+    /// Does nothing if no NSF file is loaded
+    pub fn select_track(&mut self, track: u8) {
+        if let Some(ref nsf) = self.nsf {
+            nsf.borrow_mut().reset(track);
+            self.cpu.borrow_mut().reset();
+        }
+    }
+
+    /// Connect the mapper to the CPU, PPU and APU
+    fn connect(&mut self, mapper: Mapper) {
         // Complete initialization of components
-        let cpu_bus = CpuIoBus::new(self.ppu.clone(), self.apu.clone(), self.joy.clone(), mapper.clone());
+        let mut cpu_bus = CpuIoBus::new(self.ppu.clone(), self.apu.clone(), self.joy.clone(), mapper.clone());
+        cpu_bus.set_audio_log(self.audio_log.clone());
         self.cpu.borrow_mut().load_bus(cpu_bus);
 
         let ppu_bus = PpuIoBus::new(self.cpu.clone(), mapper.clone());
@@ -318,6 +566,22 @@ impl Nes {
         self.mapper.map_or(vec![], |mapper| mapper.borrow().get_battery_ram())
     }
 
+    /// Start recording writes to the APU and cartridge sound chip. Discards any recording in progress
+    pub fn start_audio_log(&mut self) {
+        *self.audio_log.borrow_mut() = Some(AudioLog::default());
+    }
+
+    /// Stop recording sound register writes and return the log
+    /// ```
+    /// # use nescore::Nes;
+    /// let mut nes = Nes::default();
+    /// nes.start_audio_log();
+    /// let vgm = nes.stop_audio_log().unwrap().to_vgm();
+    /// ```
+    pub fn stop_audio_log(&mut self) -> Option<AudioLog> {
+        self.audio_log.borrow_mut().take()
+    }
+
     //------------------------------------------------------------------------------------------------------------------
     // Event Logging
     //------------------------------------------------------------------------------------------------------------------
@@ -351,15 +615,76 @@ impl Nes {
         self.cpu.borrow().read_ram(addr)
     }
 
+    /// Get a copy of the CPU's shadow call stack
+    /// ```
+    /// # use nescore::Nes;
+    /// # let nes = Nes::default();
+    /// let call_stack = nes.call_stack();
+    /// for frame in call_stack.frames().iter().rev() {
+    ///     println!("{}", frame);
+    /// }
+    /// ```
+    pub fn call_stack(&self) -> CallStack {
+        self.cpu.borrow().call_stack().clone()
+    }
+
+    /// Whether the last emulated frame was one dot short. The NTSC filter uses this to track the color subcarrier phase
+    pub fn short_frame(&self) -> bool {
+        self.ppu.borrow().short_frame()
+    }
+
+    /// Total number of CPU cycles executed
+    pub fn cpu_cycles(&self) -> u64 {
+        self.cpu.borrow().cycles()
+    }
+
     /// Read directly from VRAM
     pub fn read_ppu_memory(&self, addr: u16) -> u8 {
-        self.ppu.borrow().read_vram(addr)
+        self.ppu.borrow().peek_vram(addr)
     }
 
     /// Read a tile from the current nametable
     pub fn read_tile(&self, nametable: u16, x: usize, y: usize) -> u8 {
         self.ppu.borrow().read_tile(nametable, x, y)
     }
+
+    /// Render the four nametables into a 512x480 image
+    /// ```no_run
+    /// # use nescore::{Nes, Cartridge};
+    /// # use nescore::debug::NametableOverlays;
+    /// # let cart = Cartridge::from_path("/path/to/rom").unwrap();
+    /// let nes = Nes::from(cart);
+    /// let image = nes.render_nametables(NametableOverlays { scroll: true, sprite0: true });
+    /// assert_eq!((image.width, image.height), (512, 480));
+    /// ```
+    pub fn render_nametables(&self, overlays: NametableOverlays) -> DebugImage {
+        ppu_debug::render_nametables(&self.ppu.borrow(), &self.palette, overlays)
+    }
+
+    /// Render both pattern tables into a 256x128 image, using one of the eight palettes in palette RAM
+    pub fn render_pattern_tables(&self, palette: u8) -> DebugImage {
+        ppu_debug::render_pattern_tables(&self.ppu.borrow(), &self.palette, palette)
+    }
+
+    /// Render the 64 OAM sprites in an 8x8 grid
+    pub fn render_sprites(&self) -> DebugImage {
+        ppu_debug::render_sprites(&self.ppu.borrow(), &self.palette)
+    }
+
+    /// Render palette RAM as two rows of 16 colors
+    pub fn render_palette(&self) -> DebugImage {
+        ppu_debug::render_palette(&self.ppu.borrow(), &self.palette)
+    }
+
+    /// Decode the sprites in OAM
+    pub fn sprites(&self) -> Vec<SpriteInfo> {
+        ppu_debug::sprites(&self.ppu.borrow())
+    }
+
+    /// Current contents of palette RAM
+    pub fn palette_ram(&self) -> [u8; 32] {
+        ppu_debug::palette_ram(&self.ppu.borrow())
+    }
 }
 
 impl From<Cartridge> for Nes {
@@ -370,5 +695,186 @@ impl From<Cartridge> for Nes {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
 
+    fn format(pixel_format: PixelFormat, pixel: RawPixel) -> Vec<u8> {
+        let nes = Nes::default().pixel_format(pixel_format).palette(Palette::rgb());
+        let mut output = vec![0u8; pixel_format.num_bytes()];
+        nes.format_color_output(pixel, &mut output);
+
+        output
+    }
+
+    #[test]
+    fn pixel_formats() {
+        // RGB palette color $01 is (0x00, 0x24, 0x91)
+        assert_eq!(format(PixelFormat::RGB8, 0x01), vec![0x00, 0x24, 0x91]);
+        assert_eq!(format(PixelFormat::RGBA8, 0x01), vec![0x00, 0x24, 0x91, 0xFF]);
+        assert_eq!(format(PixelFormat::GBRA8, 0x01), vec![0x24, 0x91, 0x00, 0xFF]);
+        assert_eq!(format(PixelFormat::BGRA8, 0x01), vec![0x91, 0x24, 0x00, 0xFF]);
+        assert_eq!(format(PixelFormat::XRGB8888, 0x01), vec![0x91, 0x24, 0x00, 0xFF]);
+        // 00000 001001 10010
+        assert_eq!(format(PixelFormat::RGB565, 0x01), vec![0x32, 0x01]);
+        // 1 00000 00100 10010
+        assert_eq!(format(PixelFormat::ARGB1555, 0x01), vec![0x92, 0x80]);
+    }
+
+    #[test]
+    fn indexed_output_ignores_palette() {
+        // Color $16 with red and blue emphasis
+        assert_eq!(format(PixelFormat::Indexed, 0x156), vec![0x56, 0x01]);
+    }
+
+    #[test]
+    fn frame_with_stride() {
+        // Set the universal background color and enable background rendering
+        let cart = crate::asm::assemble_cart("
+                .org $C000
+                LDA #$3F
+                STA $2006
+                LDA #$00
+                STA $2006
+                LDA #$16
+                STA $2007
+                LDA #$0A
+                STA $2001
+            loop:
+                JMP loop
+        ", &[]).unwrap();
+
+        let mut nes = Nes::from(cart).pixel_format(PixelFormat::Indexed);
+
+        let stride = DISPLAY_WIDTH * 2 + 16;
+        let mut buffer = vec![0xAAu8; stride * DISPLAY_HEIGHT];
+
+        nes.emulate_frame_into(&mut buffer, stride);
+        nes.emulate_frame_into(&mut buffer, stride);
+
+        for y in 0..DISPLAY_HEIGHT {
+            let row = &buffer[y * stride..(y + 1) * stride];
+            assert!(row[..DISPLAY_WIDTH * 2].chunks(2).all(|p| p == [0x16, 0x00]), "Row {}", y);
+            // Padding is not touched
+            assert!(row[DISPLAY_WIDTH * 2..].iter().all(|&b| b == 0xAA), "Row {}", y);
+        }
+    }
+
+    #[test]
+    fn layer_buffers() {
+        // Fill the background with color 1 of the first palette
+        let cart = crate::asm::assemble_cart("
+                .org $C000
+                LDA #$3F
+                STA $2006
+                LDA #$00
+                STA $2006
+                LDA #$16
+                STA $2007
+                LDA #$2A
+                STA $2007
+                LDA #$0A
+                STA $2001
+            loop:
+                JMP loop
+        ", &[0xFF; 8]).unwrap();
+
+        let mut nes = Nes::from(cart).layer_output(true).pixel_format(PixelFormat::Indexed);
+
+        let is_color = |buffer: &[u8], color: u8| buffer.chunks(2).all(|p| p == [color, 0x00]);
+
+        nes.emulate_frame();
+        let framebuffer = nes.emulate_frame().0.to_vec();
+        let (background, sprites) = nes.layers().unwrap();
+
+        assert!(is_color(&framebuffer, 0x2A));
+        assert!(is_color(background, 0x2A));
+        assert!(is_color(sprites, 0x16));
+
+        nes.set_background_visible(false);
+        let framebuffer = nes.emulate_frame().0.to_vec();
+
+        assert!(is_color(&framebuffer, 0x16));
+        assert!(is_color(nes.layers().unwrap().0, 0x16));
+    }
+
+    #[test]
+    fn nsf_playback() {
+        let mut data = vec![0u8; 0x80];
+        data[..5].copy_from_slice(b"NESM\x1A");
+        data[0x06] = 2;
+        data[0x07] = 1;
+        data[0x08..0x0E].copy_from_slice(&[0x00, 0x80, 0x00, 0x80, 0x03, 0x80]);
+        data.extend_from_slice(&[
+            0x85, 0x00, // INIT: STA $00
+            0x60,       //       RTS
+            0xE6, 0x01, // PLAY: INC $01
+            0x60,       //       RTS
+        ]);
+
+        let nsf = Nsf::from(data).unwrap();
+        let mut nes = Nes::default().with_nsf(&nsf);
+
+        // Roughly 4 PLAY calls at 60 Hz
+        nes.run_audio(60_000);
+        assert_eq!(nes.read_cpu_ram(0x00), 0);
+        assert!((3..=4).contains(&nes.read_cpu_ram(0x01)), "PLAY called {} times", nes.read_cpu_ram(0x01));
+
+        nes.select_track(1);
+        nes.run_audio(10_000);
+        assert_eq!(nes.read_cpu_ram(0x00), 1);
+        assert_eq!(nes.read_cpu_ram(0x01), 0);
+    }
+
+    #[test]
+    fn audio_log() {
+        use crate::vgm::{LogEntry, RegisterWrite};
+
+        let mut data = vec![0u8; 0x80];
+        data[..5].copy_from_slice(b"NESM\x1A");
+        data[0x06] = 1;
+        data[0x07] = 1;
+        data[0x08..0x0E].copy_from_slice(&[0x00, 0x80, 0x00, 0x80, 0x06, 0x80]);
+        data[0x7B] = 0x01; // VRC6
+        data.extend_from_slice(&[
+            0xA9, 0x1F,       // INIT: LDA #$1F
+            0x8D, 0x15, 0x40, //       STA $4015
+            0x60,             //       RTS
+            0x8D, 0x00, 0x90, // PLAY: STA $9000
+            0x8D, 0x00, 0x80, //       STA $8000
+            0x60,             //       RTS
+        ]);
+
+        let nsf = Nsf::from(data).unwrap();
+        let mut nes = Nes::default().with_nsf(&nsf);
+
+        nes.start_audio_log();
+        nes.run_audio(60_000);
+        let log = nes.stop_audio_log().unwrap();
+
+        let writes: Vec<RegisterWrite> = log.entries().iter().filter_map(|entry| match entry {
+            LogEntry::Write(write) => Some(*write),
+            _ => None,
+        }).collect();
+
+        // The driver clears $4000-$4013 then sets up $4015 and $4017
+        assert_eq!(writes.iter().filter(|w| w.addr < 0x4014).count(), 0x14);
+        assert!(writes.windows(2).all(|w| w[0].cycle <= w[1].cycle));
+        assert!(writes.iter().any(|w| w.addr == 0x4015 && w.value == 0x1F));
+        // PLAY's VRC6 write is logged. The write to ROM is not
+        let cart_writes: Vec<u16> = writes.iter().filter(|w| w.addr >= 0x8000).map(|w| w.addr).collect();
+        assert!(!cart_writes.is_empty() && cart_writes.iter().all(|&addr| addr == 0x9000));
+
+        // Starting the DMC captures its sample
+        assert!(log.entries().iter().any(|entry| matches!(entry, LogEntry::Dpcm(block) if block.addr == 0xC000)));
+
+        assert!(nes.stop_audio_log().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn frame_stride_too_small() {
+        let mut nes = Nes::default().pixel_format(PixelFormat::RGBA8);
+        let mut buffer = vec![0u8; DISPLAY_WIDTH * DISPLAY_HEIGHT * 4];
+
+        nes.emulate_frame_into(&mut buffer, DISPLAY_WIDTH * 3);
+    }
 }
@@ -0,0 +1,509 @@
+//
+// nsf.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+
+use std::fmt;
+use std::io;
+use std::error::Error;
+use std::convert::TryInto;
+
+use crate::cart::load_file;
+
+const NSF_HEADER_SIZE: usize = 0x80;
+const NSF_SIG: &[u8] = b"NESM\x1A";
+const NSFE_SIG: &[u8] = b"NSFE";
+
+/// Default play speeds, in microseconds, when a file does not specify them
+const DEFAULT_NTSC_SPEED: u16 = 16_639;
+const DEFAULT_PAL_SPEED: u16 = 19_997;
+
+//
+// Error types
+//
+
+/// Error loading an NSF or NSFe file
+#[derive(Debug)]
+pub enum NsfError {
+    ReadFail(io::Error),
+    InvalidSig,
+    InvalidSize(usize),
+    /// A chunk the file must contain is missing
+    MissingChunk(&'static str),
+    /// The file contains a required NSFe chunk that is not supported
+    UnsupportedChunk(String),
+    InvalidLoadAddress(u16),
+}
+
+impl fmt::Display for NsfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NsfError::ReadFail(ref e)           => write!(f, "Failed to read NSF file: {}", e),
+            NsfError::InvalidSig                => write!(f, "Invalid signature at start of file. Expected `NESM` or `NSFE`"),
+            NsfError::InvalidSize(s)            => write!(f, "Not enough data to parse the file (Size: {})", s),
+            NsfError::MissingChunk(id)          => write!(f, "NSFe file is missing the `{}` chunk", id),
+            NsfError::UnsupportedChunk(ref id)  => write!(f, "NSFe file requires an unsupported chunk: `{}`", id),
+            NsfError::InvalidLoadAddress(addr)  => write!(f, "Invalid load address: ${:04X}", addr),
+        }
+    }
+}
+
+impl Error for NsfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            NsfError::ReadFail(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+//
+// NSF structs
+//
+
+/// Video region a tune was written for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Region {
+    Ntsc,
+    Pal,
+    /// Tune supports both NTSC and PAL
+    Dual,
+}
+
+impl From<u8> for Region {
+    fn from(flags: u8) -> Self {
+        match flags & 0x03 {
+            0 => Region::Ntsc,
+            1 => Region::Pal,
+            _ => Region::Dual,
+        }
+    }
+}
+
+/// Cartridge sound chips used by a tune
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ExpansionChips {
+    pub vrc6: bool,
+    pub vrc7: bool,
+    pub fds: bool,
+    pub mmc5: bool,
+    pub n163: bool,
+    pub sunsoft5b: bool,
+}
+
+impl From<u8> for ExpansionChips {
+    fn from(flags: u8) -> Self {
+        ExpansionChips {
+            vrc6: bit_is_set!(flags, 0),
+            vrc7: bit_is_set!(flags, 1),
+            fds: bit_is_set!(flags, 2),
+            mmc5: bit_is_set!(flags, 3),
+            n163: bit_is_set!(flags, 4),
+            sunsoft5b: bit_is_set!(flags, 5),
+        }
+    }
+}
+
+impl fmt::Display for ExpansionChips {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = [
+            (self.vrc6, "VRC6"),
+            (self.vrc7, "VRC7"),
+            (self.fds, "FDS"),
+            (self.mmc5, "MMC5"),
+            (self.n163, "N163"),
+            (self.sunsoft5b, "5B"),
+        ];
+
+        let chips: Vec<&str> = names.iter().filter(|(used, _)| *used).map(|(_, name)| *name).collect();
+
+        if chips.is_empty() {
+            write!(f, "None")
+        }
+        else {
+            write!(f, "{}", chips.join(", "))
+        }
+    }
+}
+
+/// An NES Sound Format file
+/// https://wiki.nesdev.com/w/index.php/NSF
+/// https://wiki.nesdev.com/w/index.php/NSFe
+pub struct Nsf {
+    pub title: String,
+    pub artist: String,
+    pub copyright: String,
+
+    pub num_tracks: u8,
+    /// First track to play, starting from 0
+    pub start_track: u8,
+
+    pub load_addr: u16,
+    pub init_addr: u16,
+    pub play_addr: u16,
+
+    /// Microseconds between PLAY calls
+    pub ntsc_speed: u16,
+    pub pal_speed: u16,
+    pub region: Region,
+
+    /// Initial values of the bank registers. `None` if the tune does not use bank switching
+    pub bank_init: Option<[u8; 8]>,
+    pub chips: ExpansionChips,
+
+    /// Track names and lengths in milliseconds, when provided by an NSFe file
+    pub track_labels: Vec<String>,
+    pub track_times: Vec<Option<u32>>,
+
+    pub(crate) data: Vec<u8>,
+}
+
+impl Nsf {
+    /// Load an NSF or NSFe file from a byte buffer
+    /// ```no_run
+    /// # use nescore::Nsf;
+    /// # let data = vec![0u8; 10]; // A buffer of data
+    /// let nsf = Nsf::from(data).unwrap();
+    /// ```
+    pub fn from(data: Vec<u8>) -> Result<Nsf, NsfError> {
+        Nsf::from_slice(&data)
+    }
+
+    pub fn from_slice(data: &[u8]) -> Result<Nsf, NsfError> {
+        let nsf = if data.starts_with(NSF_SIG) {
+            parse_nsf(data)?
+        }
+        else if data.starts_with(NSFE_SIG) {
+            parse_nsfe(data)?
+        }
+        else {
+            return Err(NsfError::InvalidSig);
+        };
+
+        // Tunes are loaded into ROM, or RAM from $6000 for FDS tunes. Bank switched tunes only use the lower 12 bits
+        let min_load_addr = if nsf.chips.fds { 0x6000 } else { 0x8000 };
+        if nsf.bank_init.is_none() && nsf.load_addr < min_load_addr {
+            return Err(NsfError::InvalidLoadAddress(nsf.load_addr));
+        }
+
+        Ok(nsf)
+    }
+
+    /// Load an NSF or NSFe file
+    /// ```no_run
+    /// # use nescore::Nsf;
+    /// let nsf = Nsf::from_path("/path/to/nsf").unwrap();
+    /// ```
+    pub fn from_path(path: &str) -> Result<Nsf, NsfError> {
+        load_file(path)
+            .map_err(NsfError::ReadFail)
+            .and_then(Nsf::from)
+    }
+
+    /// Name of a track, falling back to its number
+    pub fn track_label(&self, track: u8) -> String {
+        self.track_labels.get(track as usize)
+            .filter(|label| !label.is_empty())
+            .cloned()
+            .unwrap_or_else(|| format!("Track {}", track + 1))
+    }
+}
+
+impl fmt::Display for Nsf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Title:     {}", self.title)?;
+        writeln!(f, "Artist:    {}", self.artist)?;
+        writeln!(f, "Copyright: {}", self.copyright)?;
+        writeln!(f, "Tracks:    {} (Start: {})", self.num_tracks, self.start_track + 1)?;
+        writeln!(f, "Region:    {:?}", self.region)?;
+        writeln!(f, "Chips:     {}", self.chips)?;
+        write!(f, "Load: ${:04X}, Init: ${:04X}, Play: ${:04X}", self.load_addr, self.init_addr, self.play_addr)
+    }
+}
+
+//
+// Parsing
+//
+
+fn parse_nsf(data: &[u8]) -> Result<Nsf, NsfError> {
+    if data.len() < NSF_HEADER_SIZE {
+        return Err(NsfError::InvalidSize(data.len()));
+    }
+
+    let bank_init = data[0x70..0x78].try_into().unwrap();
+    let bankswitched = data[0x70..0x78].iter().any(|&bank| bank != 0);
+
+    Ok(Nsf {
+        title: read_string(&data[0x0E..0x2E]),
+        artist: read_string(&data[0x2E..0x4E]),
+        copyright: read_string(&data[0x4E..0x6E]),
+
+        num_tracks: data[0x06],
+        start_track: data[0x07].saturating_sub(1),
+
+        load_addr: read_u16(data, 0x08),
+        init_addr: read_u16(data, 0x0A),
+        play_addr: read_u16(data, 0x0C),
+
+        ntsc_speed: play_speed(read_u16(data, 0x6E), DEFAULT_NTSC_SPEED),
+        pal_speed: play_speed(read_u16(data, 0x78), DEFAULT_PAL_SPEED),
+        region: Region::from(data[0x7A]),
+
+        bank_init: if bankswitched { Some(bank_init) } else { None },
+        chips: ExpansionChips::from(data[0x7B]),
+
+        track_labels: vec![],
+        track_times: vec![],
+
+        data: data[NSF_HEADER_SIZE..].to_vec(),
+    })
+}
+
+fn parse_nsfe(data: &[u8]) -> Result<Nsf, NsfError> {
+    let mut nsf = Nsf {
+        title: String::new(),
+        artist: String::new(),
+        copyright: String::new(),
+
+        num_tracks: 1,
+        start_track: 0,
+
+        load_addr: 0,
+        init_addr: 0,
+        play_addr: 0,
+
+        ntsc_speed: DEFAULT_NTSC_SPEED,
+        pal_speed: DEFAULT_PAL_SPEED,
+        region: Region::Ntsc,
+
+        bank_init: None,
+        chips: ExpansionChips::default(),
+
+        track_labels: vec![],
+        track_times: vec![],
+
+        data: vec![],
+    };
+
+    let mut has_info = false;
+    let mut has_data = false;
+    let mut offset = NSFE_SIG.len();
+
+    while offset + 8 <= data.len() {
+        let len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+        let id = &data[offset + 4..offset + 8];
+        let start = offset + 8;
+
+        let chunk = data.get(start..start + len).ok_or(NsfError::InvalidSize(data.len()))?;
+
+        match id {
+            b"INFO" => {
+                if chunk.len() < 8 {
+                    return Err(NsfError::InvalidSize(chunk.len()));
+                }
+
+                nsf.load_addr = read_u16(chunk, 0);
+                nsf.init_addr = read_u16(chunk, 2);
+                nsf.play_addr = read_u16(chunk, 4);
+                nsf.region = Region::from(chunk[6]);
+                nsf.chips = ExpansionChips::from(chunk[7]);
+                nsf.num_tracks = chunk.get(8).copied().unwrap_or(1);
+                nsf.start_track = chunk.get(9).copied().unwrap_or(0);
+
+                has_info = true;
+            },
+            b"DATA" => {
+                nsf.data = chunk.to_vec();
+                has_data = true;
+            },
+            b"BANK" => {
+                let mut banks = [0u8; 8];
+                for (bank, value) in banks.iter_mut().zip(chunk.iter()) {
+                    *bank = *value;
+                }
+
+                nsf.bank_init = Some(banks);
+            },
+            b"RATE" => {
+                if chunk.len() >= 2 {
+                    nsf.ntsc_speed = play_speed(read_u16(chunk, 0), DEFAULT_NTSC_SPEED);
+                }
+                if chunk.len() >= 4 {
+                    nsf.pal_speed = play_speed(read_u16(chunk, 2), DEFAULT_PAL_SPEED);
+                }
+            },
+            b"auth" => {
+                let mut strings = chunk.split(|&b| b == 0).map(read_string);
+
+                nsf.title = strings.next().unwrap_or_default();
+                nsf.artist = strings.next().unwrap_or_default();
+                nsf.copyright = strings.next().unwrap_or_default();
+            },
+            b"tlbl" => {
+                nsf.track_labels = chunk.split(|&b| b == 0).map(read_string).collect();
+                nsf.track_labels.truncate(nsf.num_tracks as usize);
+            },
+            b"time" => {
+                nsf.track_times = chunk.chunks_exact(4)
+                    .map(|time| i32::from_le_bytes(time.try_into().unwrap()))
+                    .map(|ms| if ms >= 0 { Some(ms as u32) } else { None })
+                    .collect();
+            },
+            b"NEND" => break,
+            // Chunks starting with an uppercase letter must be understood to play the file
+            _ if id[0].is_ascii_uppercase() => {
+                return Err(NsfError::UnsupportedChunk(String::from_utf8_lossy(id).to_string()));
+            },
+            _ => {},
+        }
+
+        offset = start + len;
+    }
+
+    if !has_info {
+        return Err(NsfError::MissingChunk("INFO"));
+    }
+    if !has_data {
+        return Err(NsfError::MissingChunk("DATA"));
+    }
+
+    Ok(nsf)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    (data[offset] as u16) | (data[offset + 1] as u16) << 8
+}
+
+/// Read a null terminated string
+fn read_string(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).to_string()
+}
+
+fn play_speed(speed: u16, default: u16) -> u16 {
+    if speed == 0 { default } else { speed }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header() {
+        let mut data = nsf_header(0x8000);
+        data[0x06] = 3;
+        data[0x07] = 2;
+        data[0x0E..0x13].copy_from_slice(b"Title");
+        data[0x7B] = 0x21;
+        data.extend_from_slice(&[0xEA; 16]);
+
+        let nsf = Nsf::from(data).unwrap();
+
+        assert_eq!(nsf.title, "Title");
+        assert_eq!(nsf.num_tracks, 3);
+        assert_eq!(nsf.start_track, 1);
+        assert_eq!(nsf.load_addr, 0x8000);
+        assert_eq!(nsf.init_addr, 0x8003);
+        assert_eq!(nsf.play_addr, 0x8006);
+        assert_eq!(nsf.ntsc_speed, DEFAULT_NTSC_SPEED);
+        assert_eq!(nsf.bank_init, None);
+        assert!(nsf.chips.vrc6 && nsf.chips.sunsoft5b && !nsf.chips.vrc7);
+        assert_eq!(nsf.data.len(), 16);
+    }
+
+    #[test]
+    fn nsf_bankswitched() {
+        let mut data = nsf_header(0x0000);
+        data[0x71] = 0x01;
+
+        let nsf = Nsf::from(data).unwrap();
+        assert_eq!(nsf.bank_init, Some([0, 1, 0, 0, 0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn nsf_fds_load_address() {
+        let mut data = nsf_header(0x6000);
+        data[0x7B] = 0x04;
+
+        let nsf = Nsf::from(data).unwrap();
+        assert_eq!(nsf.load_addr, 0x6000);
+        assert!(nsf.chips.fds);
+    }
+
+    #[test]
+    fn nsf_invalid() {
+        assert!(matches!(Nsf::from(vec![0; 0x80]), Err(NsfError::InvalidSig)));
+        assert!(matches!(Nsf::from(b"NESM\x1A".to_vec()), Err(NsfError::InvalidSize(5))));
+        assert!(matches!(Nsf::from(nsf_header(0x6000)), Err(NsfError::InvalidLoadAddress(0x6000))));
+    }
+
+    #[test]
+    fn nsfe_chunks() {
+        let mut data = b"NSFE".to_vec();
+        push_chunk(&mut data, b"INFO", &[0x00, 0x80, 0x03, 0x80, 0x06, 0x80, 0x02, 0x02, 0x02, 0x01]);
+        push_chunk(&mut data, b"DATA", &[0xEA; 8]);
+        push_chunk(&mut data, b"RATE", &[0x0A, 0x41]);
+        push_chunk(&mut data, b"auth", b"Game\0Artist\0Copyright\0Ripper\0");
+        push_chunk(&mut data, b"tlbl", b"Intro\0Stage 1\0");
+        push_chunk(&mut data, b"time", &[0x10, 0x27, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF]);
+        push_chunk(&mut data, b"xtra", &[]);
+        push_chunk(&mut data, b"NEND", &[]);
+
+        let nsf = Nsf::from(data).unwrap();
+
+        assert_eq!(nsf.load_addr, 0x8000);
+        assert_eq!(nsf.region, Region::Dual);
+        assert!(nsf.chips.vrc7);
+        assert_eq!(nsf.num_tracks, 2);
+        assert_eq!(nsf.start_track, 1);
+        assert_eq!(nsf.ntsc_speed, 0x410A);
+        assert_eq!(nsf.pal_speed, DEFAULT_PAL_SPEED);
+        assert_eq!(nsf.artist, "Artist");
+        assert_eq!(nsf.track_label(1), "Stage 1");
+        assert_eq!(nsf.track_times, vec![Some(10_000), None]);
+        assert_eq!(nsf.data, vec![0xEA; 8]);
+    }
+
+    #[test]
+    fn nsfe_short_info() {
+        let mut data = b"NSFE".to_vec();
+        push_chunk(&mut data, b"INFO", &[0x00, 0x80, 0x03, 0x80, 0x06, 0x80, 0x00, 0x00]);
+        push_chunk(&mut data, b"DATA", &[0xEA; 8]);
+        push_chunk(&mut data, b"NEND", &[]);
+
+        let nsf = Nsf::from(data).unwrap();
+        assert_eq!(nsf.num_tracks, 1);
+        assert_eq!(nsf.start_track, 0);
+    }
+
+    #[test]
+    fn nsfe_required_chunks() {
+        let mut data = b"NSFE".to_vec();
+        push_chunk(&mut data, b"DATA", &[0xEA; 8]);
+        assert!(matches!(Nsf::from(data.clone()), Err(NsfError::MissingChunk("INFO"))));
+
+        push_chunk(&mut data, b"XTRA", &[]);
+        assert!(matches!(Nsf::from(data), Err(NsfError::UnsupportedChunk(_))));
+    }
+
+    fn nsf_header(load_addr: u16) -> Vec<u8> {
+        let mut data = vec![0u8; NSF_HEADER_SIZE];
+        data[..5].copy_from_slice(NSF_SIG);
+        data[0x05] = 1;
+        data[0x06] = 1;
+        data[0x07] = 1;
+        data[0x08..0x0A].copy_from_slice(&load_addr.to_le_bytes());
+        data[0x0A..0x0C].copy_from_slice(&(load_addr + 3).to_le_bytes());
+        data[0x0C..0x0E].copy_from_slice(&(load_addr + 6).to_le_bytes());
+
+        data
+    }
+
+    fn push_chunk(data: &mut Vec<u8>, id: &[u8], chunk: &[u8]) {
+        data.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        data.extend_from_slice(id);
+        data.extend_from_slice(chunk);
+    }
+}
@@ -25,11 +25,22 @@ impl PpuIoBus {
 
 impl IoAccess for PpuIoBus {
     fn read_byte(&self, addr: u16) -> u8 {
+        self.mapper.borrow_mut().ppu_address(addr);
         self.mapper.borrow().read_chr(addr)
     }
 
     fn write_byte(&mut self, addr: u16, value: u8) {
-        self.mapper.borrow_mut().write_chr(addr, value);
+        let mut mapper = self.mapper.borrow_mut();
+        mapper.ppu_address(addr);
+        mapper.write_chr(addr, value);
+    }
+
+    fn peek_byte(&self, addr: u16) -> u8 {
+        self.mapper.borrow().read_chr(addr)
+    }
+
+    fn set_address(&mut self, addr: u16) {
+        self.mapper.borrow_mut().ppu_address(addr);
     }
 
     fn raise_interrupt(&mut self, interrupt_type: Interrupt) {
@@ -0,0 +1,394 @@
+//
+// ppu/debug.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::ppu::{Ppu, Pixel, DISPLAY_WIDTH, DISPLAY_HEIGHT};
+use super::palette::Palette;
+use super::sprite::NUM_SPRITES;
+use crate::common::IoAccess;
+
+/// Width of the nametable view, two nametables side by side
+pub const NAMETABLE_VIEW_WIDTH: usize = DISPLAY_WIDTH * 2;
+/// Height of the nametable view, two nametables stacked
+pub const NAMETABLE_VIEW_HEIGHT: usize = DISPLAY_HEIGHT * 2;
+
+/// Size of a swatch in the palette view
+const SWATCH_SIZE: usize = 8;
+/// Number of sprites per row in the OAM view
+const SPRITES_PER_ROW: usize = 8;
+
+/// Outline of the visible screen in the nametable view
+const SCROLL_OVERLAY_COLOR: Pixel = (0xFF, 0x00, 0xFF);
+/// Outline of sprite 0 in the nametable view
+const SPRITE0_OVERLAY_COLOR: Pixel = (0x00, 0xFF, 0x00);
+
+/// An RGB8 image of PPU memory
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugImage {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl DebugImage {
+    fn new(width: usize, height: usize) -> Self {
+        DebugImage {
+            width,
+            height,
+            data: vec![0; width * height * 3],
+        }
+    }
+
+    /// Color of the pixel at the given position
+    pub fn pixel(&self, x: usize, y: usize) -> Pixel {
+        let idx = (y * self.width + x) * 3;
+        (self.data[idx], self.data[idx + 1], self.data[idx + 2])
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, (r, g, b): Pixel) {
+        let idx = (y * self.width + x) * 3;
+        self.data[idx..idx + 3].copy_from_slice(&[r, g, b]);
+    }
+
+    /// Draw a rectangle outline, wrapping around the edges of the image
+    fn draw_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Pixel) {
+        for i in 0..width {
+            self.set_pixel((x + i) % self.width, y % self.height, color);
+            self.set_pixel((x + i) % self.width, (y + height - 1) % self.height, color);
+        }
+        for i in 0..height {
+            self.set_pixel(x % self.width, (y + i) % self.height, color);
+            self.set_pixel((x + width - 1) % self.width, (y + i) % self.height, color);
+        }
+    }
+}
+
+/// Overlays drawn on the nametable view
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NametableOverlays {
+    /// Outline the screen at the current scroll position
+    pub scroll: bool,
+    /// Outline sprite 0, relative to the current scroll position
+    pub sprite0: bool,
+}
+
+/// A sprite in OAM
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpriteInfo {
+    pub index: u8,
+    pub x: u8,
+    pub y: u8,       // Y position as stored in OAM. The sprite is displayed starting on the following scanline
+    pub tile: u8,
+    pub palette: u8, // Sprite palette (0-3)
+    pub behind_background: bool,
+    pub flip_h: bool,
+    pub flip_v: bool,
+}
+
+impl SpriteInfo {
+    fn from(data: &[u8], index: u8) -> Self {
+        SpriteInfo {
+            index,
+            x: data[3],
+            y: data[0],
+            tile: data[1],
+            palette: data[2] & 0x03,
+            behind_background: bit_is_set!(data[2], 5),
+            flip_h: bit_is_set!(data[2], 6),
+            flip_v: bit_is_set!(data[2], 7),
+        }
+    }
+}
+
+/// Read the 32 bytes of palette RAM
+pub fn palette_ram<Io: IoAccess>(ppu: &Ppu<Io>) -> [u8; 32] {
+    let mut ram = [0; 32];
+    for (i, entry) in ram.iter_mut().enumerate() {
+        *entry = ppu.peek_vram(0x3F00 + i as u16);
+    }
+
+    ram
+}
+
+/// Decode the sprites in OAM
+pub fn sprites<Io: IoAccess>(ppu: &Ppu<Io>) -> Vec<SpriteInfo> {
+    ppu.oam().chunks(4).enumerate().map(|(i, data)| SpriteInfo::from(data, i as u8)).collect()
+}
+
+/// Render the four nametables, as mapped by the cartridge, into a 512x480 image
+pub fn render_nametables<Io: IoAccess>(ppu: &Ppu<Io>, palette: &Palette, overlays: NametableOverlays) -> DebugImage {
+    let colors = palette_colors(ppu, palette);
+    let pattern_table = ppu.ctrl().background_pattern_table();
+
+    let mut image = DebugImage::new(NAMETABLE_VIEW_WIDTH, NAMETABLE_VIEW_HEIGHT);
+
+    for nametable in 0..4 {
+        let base = 0x2000 + nametable as u16 * 0x400;
+        let origin_x = (nametable & 0x01) * DISPLAY_WIDTH;
+        let origin_y = (nametable >> 1) * DISPLAY_HEIGHT;
+
+        for tile_y in 0..(DISPLAY_HEIGHT / 8) {
+            for tile_x in 0..(DISPLAY_WIDTH / 8) {
+                let tile = ppu.peek_vram(base + (tile_y * 32 + tile_x) as u16);
+                let attr = ppu.peek_vram(base + 0x3C0 + ((tile_y / 4) * 8 + (tile_x / 4)) as u16);
+                let shift = ((tile_y & 0x02) << 1) | (tile_x & 0x02);
+                let pal = (attr >> shift) & 0x03;
+
+                draw_tile(ppu, &mut image, pattern_table, tile, false, false, origin_x + tile_x * 8, origin_y + tile_y * 8, |px| {
+                    colors[helpers::palette_entry(pal, px)]
+                });
+            }
+        }
+    }
+
+    let (scroll_x, scroll_y) = ppu.scroll();
+
+    if overlays.scroll {
+        image.draw_rect(scroll_x, scroll_y, DISPLAY_WIDTH, DISPLAY_HEIGHT, SCROLL_OVERLAY_COLOR);
+    }
+
+    if overlays.sprite0 {
+        let sprite = SpriteInfo::from(&ppu.oam()[0..4], 0);
+        let x = scroll_x + sprite.x as usize;
+        let y = scroll_y + sprite.y as usize + 1;
+        image.draw_rect(x, y, 8, ppu.ctrl().sprite_height() as usize, SPRITE0_OVERLAY_COLOR);
+    }
+
+    image
+}
+
+/// Render both pattern tables side by side into a 256x128 image, using one of the eight palettes.
+/// Palettes 0-3 are background palettes and 4-7 are sprite palettes
+pub fn render_pattern_tables<Io: IoAccess>(ppu: &Ppu<Io>, palette: &Palette, pal: u8) -> DebugImage {
+    let colors = palette_colors(ppu, palette);
+    let mut image = DebugImage::new(256, 128);
+
+    for table in 0..2 {
+        for tile in 0..256 {
+            let x = table * 128 + (tile % 16) * 8;
+            let y = (tile / 16) * 8;
+            draw_tile(ppu, &mut image, table as u16 * 0x1000, tile as u8, false, false, x, y, |px| {
+                colors[helpers::palette_entry(pal, px)]
+            });
+        }
+    }
+
+    image
+}
+
+/// Render the 64 OAM sprites in an 8x8 grid, as they would be displayed. Transparent pixels use the backdrop color
+pub fn render_sprites<Io: IoAccess>(ppu: &Ppu<Io>, palette: &Palette) -> DebugImage {
+    let colors = palette_colors(ppu, palette);
+    let height = ppu.ctrl().sprite_height() as usize;
+
+    let mut image = DebugImage::new(SPRITES_PER_ROW * 8, (NUM_SPRITES / SPRITES_PER_ROW) * height);
+
+    for sprite in sprites(ppu) {
+        let x = (sprite.index as usize % SPRITES_PER_ROW) * 8;
+        let y = (sprite.index as usize / SPRITES_PER_ROW) * height;
+        let pal = sprite.palette + 4;
+
+        let (pattern_table, tiles) = if height == 16 {
+            // 8x16 sprites select the pattern table with bit 0 of the tile number
+            let top = sprite.tile & 0xFE;
+            let tiles = if sprite.flip_v { [top + 1, top] } else { [top, top + 1] };
+            ((sprite.tile & 0x01) as u16 * 0x1000, tiles)
+        }
+        else {
+            (ppu.ctrl().sprite_pattern_table(), [sprite.tile, sprite.tile])
+        };
+
+        for (i, &tile) in tiles.iter().take(height / 8).enumerate() {
+            draw_tile(ppu, &mut image, pattern_table, tile, sprite.flip_h, sprite.flip_v, x, y + i * 8, |px| {
+                colors[helpers::palette_entry(pal, px)]
+            });
+        }
+    }
+
+    image
+}
+
+/// Render palette RAM as two rows of 16 swatches. Background palettes on the top row, sprite palettes on the bottom
+pub fn render_palette<Io: IoAccess>(ppu: &Ppu<Io>, palette: &Palette) -> DebugImage {
+    let colors = palette_colors(ppu, palette);
+    let mut image = DebugImage::new(16 * SWATCH_SIZE, 2 * SWATCH_SIZE);
+
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let entry = (y / SWATCH_SIZE) * 16 + x / SWATCH_SIZE;
+            image.set_pixel(x, y, colors[entry]);
+        }
+    }
+
+    image
+}
+
+/// RGB colors of each palette RAM entry
+fn palette_colors<Io: IoAccess>(ppu: &Ppu<Io>, palette: &Palette) -> [Pixel; 32] {
+    let mut colors = [(0, 0, 0); 32];
+    for (color, &entry) in colors.iter_mut().zip(palette_ram(ppu).iter()) {
+        *color = palette.color(entry, 0);
+    }
+
+    colors
+}
+
+/// Draw an 8x8 tile from a pattern table, mapping each 2-bit pixel to a color
+#[allow(clippy::too_many_arguments)]
+fn draw_tile<Io: IoAccess, F: Fn(u8) -> Pixel>(ppu: &Ppu<Io>, image: &mut DebugImage, base: u16, tile: u8, flip_h: bool, flip_v: bool, x: usize, y: usize, color: F) {
+    for row in 0..8 {
+        let addr = base + (tile as u16 * 16) + row as u16;
+        let lo = ppu.peek_vram(addr);
+        let hi = ppu.peek_vram(addr + 8);
+
+        let dest_y = if flip_v { 7 - row } else { row };
+
+        for col in 0..8 {
+            let bit = 7 - col;
+            let px = (((hi >> bit) & 0x01) << 1) | ((lo >> bit) & 0x01);
+            let dest_x = if flip_h { 7 - col } else { col };
+
+            image.set_pixel(x + dest_x, y + dest_y, color(px));
+        }
+    }
+}
+
+mod helpers {
+    /// Palette RAM entry for a pixel. Transparent pixels use the backdrop color
+    pub fn palette_entry(pal: u8, px: u8) -> usize {
+        if px == 0 { 0 } else { ((pal as usize & 0x07) << 2) | px as usize }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Interrupt;
+
+    struct VramBus {
+        vram: [u8; 0x4000],
+    }
+
+    impl IoAccess for VramBus {
+        fn read_byte(&self, addr: u16) -> u8 {
+            self.vram[addr as usize]
+        }
+        fn write_byte(&mut self, addr: u16, value: u8) {
+            self.vram[addr as usize] = value;
+        }
+        fn raise_interrupt(&mut self, _: Interrupt) {}
+    }
+
+    fn init_ppu() -> Ppu<VramBus> {
+        let mut ppu = Ppu::default();
+        ppu.load_bus(VramBus { vram: [0; 0x4000] });
+
+        // Tile 1 is solid color 3, tile 2 has a single color 1 pixel in the top left
+        for row in 0..8 {
+            ppu.write_vram(0x0010 + row, 0xFF);
+            ppu.write_vram(0x0018 + row, 0xFF);
+        }
+        ppu.write_vram(0x0020, 0x80);
+
+        for i in 0..32 {
+            ppu.write_vram(0x3F00 + i, i as u8);
+        }
+
+        ppu
+    }
+
+    #[test]
+    fn nametable_view() {
+        let mut ppu = init_ppu();
+        let palette = Palette::default();
+
+        // Tile 1 in the second nametable with attribute palette 2 for the top left quadrant
+        ppu.write_vram(0x2400, 0x01);
+        ppu.write_vram(0x27C0, 0x02);
+
+        let image = render_nametables(&ppu, &palette, NametableOverlays::default());
+
+        assert_eq!((image.width, image.height), (512, 480));
+        assert_eq!(image.pixel(256, 0), palette.color(0x0B, 0));
+        assert_eq!(image.pixel(256 + 7, 7), palette.color(0x0B, 0));
+        assert_eq!(image.pixel(256 + 8, 0), palette.color(0x00, 0));
+        assert_eq!(image.pixel(0, 0), palette.color(0x00, 0));
+    }
+
+    #[test]
+    fn nametable_overlays() {
+        let mut ppu = init_ppu();
+        let palette = Palette::default();
+
+        // Scroll to X=16, Y=8 in the bottom right nametable
+        ppu.write_byte(0x2000, 0x03);
+        ppu.write_byte(0x2005, 16);
+        ppu.write_byte(0x2005, 8);
+        assert_eq!(ppu.scroll(), (256 + 16, 240 + 8));
+
+        let overlays = NametableOverlays { scroll: true, sprite0: true };
+        let image = render_nametables(&ppu, &palette, overlays);
+
+        assert_eq!(image.pixel(256 + 16, 240 + 8), SCROLL_OVERLAY_COLOR);
+        // Wraps around to the top left nametable
+        assert_eq!(image.pixel(15, 7), SCROLL_OVERLAY_COLOR);
+        assert_eq!(image.pixel(16, 8), palette.color(0x00, 0));
+
+        // Sprite 0 at the top left of the screen
+        assert_eq!(image.pixel(256 + 16, 240 + 9), SPRITE0_OVERLAY_COLOR);
+        assert_eq!(image.pixel(256 + 23, 240 + 16), SPRITE0_OVERLAY_COLOR);
+    }
+
+    #[test]
+    fn pattern_table_view() {
+        let ppu = init_ppu();
+        let palette = Palette::default();
+
+        let image = render_pattern_tables(&ppu, &palette, 5);
+
+        assert_eq!((image.width, image.height), (256, 128));
+        assert_eq!(image.pixel(8, 0), palette.color(0x17, 0));
+        assert_eq!(image.pixel(16, 0), palette.color(0x15, 0));
+        assert_eq!(image.pixel(17, 0), palette.color(0x00, 0));
+        assert_eq!(image.pixel(128 + 8, 0), palette.color(0x00, 0));
+    }
+
+    #[test]
+    fn sprite_view() {
+        let mut ppu = init_ppu();
+        let palette = Palette::default();
+
+        // Sprite 9: tile 2, palette 1, flipped horizontally and vertically
+        for (i, &value) in [0x20, 0x02, 0xC1, 0x40].iter().enumerate() {
+            ppu.write_oam(36 + i as u8, value);
+        }
+
+        let info = sprites(&ppu);
+        assert_eq!(info.len(), 64);
+        assert_eq!(info[9], SpriteInfo {
+            index: 9, x: 0x40, y: 0x20, tile: 2, palette: 1, behind_background: false, flip_h: true, flip_v: true,
+        });
+
+        let image = render_sprites(&ppu, &palette);
+        assert_eq!((image.width, image.height), (64, 64));
+        // Second sprite on the second row. The pixel is moved to the bottom right corner
+        assert_eq!(image.pixel(8 + 7, 8 + 7), palette.color(0x15, 0));
+        assert_eq!(image.pixel(8, 8), palette.color(0x00, 0));
+    }
+
+    #[test]
+    fn palette_view() {
+        let ppu = init_ppu();
+        let palette = Palette::default();
+
+        let ram = palette_ram(&ppu);
+        assert_eq!(ram[0x11], 0x11);
+
+        let image = render_palette(&ppu, &palette);
+        assert_eq!((image.width, image.height), (128, 16));
+        assert_eq!(image.pixel(8, 0), palette.color(0x01, 0));
+        assert_eq!(image.pixel(127, 15), palette.color(0x1F, 0));
+    }
+}
@@ -133,12 +133,73 @@ impl SpriteRegister {
     pub fn active(&self) -> bool {
         self.is_active
     }
+
+    pub fn sprite_num(&self) -> u8 {
+        self.sprite_num
+    }
+}
+
+/// Number of frames an I/O latch bit holds its value without being refreshed (About 600ms)
+const LATCH_DECAY_FRAMES: u64 = 36;
+
+/// The PPU's I/O data bus latch
+///
+/// Holds the last value driven onto the bus by a register read or write. Reads from write-only registers return the
+/// latch. Bits that are not refreshed decay to 0.
+#[derive(Default, Clone, Copy)]
+pub struct IoLatch {
+    value: u8,
+    refreshed: [u64; 8], // Frame each bit was last driven
+}
+
+impl IoLatch {
+    /// Drive the bits selected by `mask`
+    pub fn load(&mut self, value: u8, mask: u8, frame: u64) {
+        self.value = (self.value & !mask) | (value & mask);
+
+        for (bit, refreshed) in self.refreshed.iter_mut().enumerate() {
+            if bit_is_set!(mask, bit) {
+                *refreshed = frame;
+            }
+        }
+    }
+
+    /// Clear bits that have not been refreshed recently
+    pub fn decay(&mut self, frame: u64) {
+        for (bit, refreshed) in self.refreshed.iter().enumerate() {
+            if frame.saturating_sub(*refreshed) > LATCH_DECAY_FRAMES {
+                self.value &= !(1 << bit);
+            }
+        }
+    }
+
+    pub fn value(&self) -> u8 {
+        self.value
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn io_latch_decay() {
+        let mut latch = IoLatch::default();
+        latch.load(0xFF, 0xFF, 0);
+        latch.load(0x00, 0x0F, 20);
+        latch.load(0xFF, 0x0F, 30);
+
+        latch.decay(LATCH_DECAY_FRAMES);
+        assert_eq!(latch.value(), 0xFF);
+
+        // The high bits were refreshed first
+        latch.decay(LATCH_DECAY_FRAMES + 1);
+        assert_eq!(latch.value(), 0x0F);
+
+        latch.decay(30 + LATCH_DECAY_FRAMES + 1);
+        assert_eq!(latch.value(), 0x00);
+    }
+
     #[test]
     fn sprite_register_load_and_shift() {
         let mut sprite_reg = SpriteRegister::default();
@@ -10,6 +10,9 @@ pub mod bus;
 mod regs;
 mod hw;
 mod sprite;
+mod palette;
+pub mod debug;
 
 // Public re-exports
-pub use ppu::{Ppu, Pixel, DISPLAY_HEIGHT, DISPLAY_WIDTH, CYCLES_PER_FRAME};
+pub use ppu::{Ppu, Pixel, RawPixel, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+pub use palette::{Palette, PaletteError, PALETTE_COLORS, PALETTE_ENTRIES};
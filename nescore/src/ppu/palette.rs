@@ -0,0 +1,242 @@
+//
+// ppu/palette.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use super::{Pixel, RawPixel};
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Number of colors the PPU can output, without emphasis
+pub const PALETTE_COLORS: usize = 64;
+/// Number of colors including every combination of the emphasis bits
+pub const PALETTE_ENTRIES: usize = PALETTE_COLORS * 8;
+
+/// Amount the non-emphasized channels are attenuated by each emphasis bit
+const EMPHASIS_ATTENUATION: f32 = 0.746;
+
+/// 2C03/2C05 RGB PPU palette as 3-bit per channel values
+/// http://wiki.nesdev.com/w/index.php/PPU_palettes#2C03_and_2C05
+const RGB_PPU_PALETTE: [u16; PALETTE_COLORS] = [
+    0o333, 0o014, 0o006, 0o326, 0o403, 0o503, 0o510, 0o420, 0o320, 0o120, 0o031, 0o040, 0o022, 0o000, 0o000, 0o000,
+    0o555, 0o036, 0o027, 0o407, 0o507, 0o704, 0o700, 0o630, 0o430, 0o140, 0o040, 0o053, 0o044, 0o000, 0o000, 0o000,
+    0o777, 0o357, 0o447, 0o637, 0o707, 0o737, 0o740, 0o750, 0o660, 0o360, 0o070, 0o276, 0o077, 0o000, 0o000, 0o000,
+    0o777, 0o567, 0o657, 0o757, 0o747, 0o755, 0o764, 0o772, 0o773, 0o572, 0o473, 0o276, 0o467, 0o000, 0o000, 0o000,
+];
+
+//
+// Error types
+//
+
+/// Error loading a palette
+#[derive(Debug)]
+pub enum PaletteError {
+    ReadFail(io::Error),
+    InvalidSize(usize),
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PaletteError::ReadFail(ref e)  => write!(f, "Failed to read palette file: {}", e),
+            PaletteError::InvalidSize(s)   => write!(f, "Palette must contain 64 or 512 RGB entries (Size: {})", s),
+        }
+    }
+}
+
+impl Error for PaletteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            PaletteError::ReadFail(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// RGB colors for each PPU color index and combination of PPUMASK emphasis bits
+///
+/// Entries are ordered by the emphasis bits (PPUMASK bits 5-7), then color index. This is the layout of 512 entry
+/// `.pal` files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    colors: [Pixel; PALETTE_ENTRIES],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::ntsc()
+    }
+}
+
+impl Palette {
+    /// NTSC 2C02 palette
+    pub fn ntsc() -> Self {
+        Palette::from_slice(include_bytes!("ntscpalette.pal")).unwrap()
+    }
+
+    /// PAL 2C07 palette. The 2C07 swaps the red and green emphasis bits
+    pub fn pal() -> Self {
+        Palette::from_slice(include_bytes!("palpalette.pal")).unwrap()
+    }
+
+    /// 2C03/2C05 RGB PPU palette, used by Vs. System and PlayChoice-10 boards
+    ///
+    /// The RGB PPUs set emphasized channels to full brightness instead of attenuating the others.
+    pub fn rgb() -> Self {
+        let base: Vec<Pixel> = RGB_PPU_PALETTE.iter().map(|&c| {
+            let level = |shift: u16| (((c >> shift) & 0x07) as u32 * 255 / 7) as u8;
+            (level(6), level(3), level(0))
+        }).collect();
+
+        let mut colors = [(0, 0, 0); PALETTE_ENTRIES];
+        for (i, color) in colors.iter_mut().enumerate() {
+            let emphasis = i / PALETTE_COLORS;
+            let (r, g, b) = base[i % PALETTE_COLORS];
+
+            *color = (
+                if emphasis & 0x01 != 0 { 0xFF } else { r },
+                if emphasis & 0x02 != 0 { 0xFF } else { g },
+                if emphasis & 0x04 != 0 { 0xFF } else { b },
+            );
+        }
+
+        Palette { colors }
+    }
+
+    /// Load a palette from a `.pal` file
+    /// ```no_run
+    /// # use nescore::Palette;
+    /// let palette = Palette::from_path("/path/to/palette.pal").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, PaletteError> {
+        fs::read(path).map_err(PaletteError::ReadFail).and_then(|data| Palette::from_slice(&data))
+    }
+
+    /// Load a palette from `.pal` data, 64 or 512 RGB entries
+    ///
+    /// Emphasis colors for 64 entry palettes are derived by attenuating the channels that are not emphasized.
+    pub fn from_slice(data: &[u8]) -> Result<Self, PaletteError> {
+        let entries = data.len() / 3;
+        if entries * 3 != data.len() || (entries != PALETTE_COLORS && entries != PALETTE_ENTRIES) {
+            return Err(PaletteError::InvalidSize(data.len()));
+        }
+
+        let mut colors = [(0, 0, 0); PALETTE_ENTRIES];
+
+        for (i, color) in colors.iter_mut().enumerate() {
+            let idx = if entries == PALETTE_ENTRIES { i } else { i % PALETTE_COLORS };
+            let rgb = (data[idx * 3], data[idx * 3 + 1], data[idx * 3 + 2]);
+
+            *color = if entries == PALETTE_ENTRIES { rgb } else { helpers::emphasize(rgb, i % PALETTE_COLORS, i / PALETTE_COLORS) };
+        }
+
+        Ok(Palette { colors })
+    }
+
+    /// Construct a palette from 512 colors
+    pub fn from_colors(colors: [Pixel; PALETTE_ENTRIES]) -> Self {
+        Palette { colors }
+    }
+
+    /// Get the RGB value of a color index with the given emphasis bits (PPUMASK bits 5-7, shifted down)
+    pub fn color(&self, color: u8, emphasis: u8) -> Pixel {
+        self.colors[(emphasis as usize & 0x07) * PALETTE_COLORS + (color as usize & 0x3F)]
+    }
+
+    /// Get the RGB value of a raw PPU pixel
+    pub fn raw_color(&self, pixel: RawPixel) -> Pixel {
+        self.colors[pixel as usize % PALETTE_ENTRIES]
+    }
+
+    /// Serialize to a 512 entry `.pal` file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.colors.iter().flat_map(|&(r, g, b)| vec![r, g, b]).collect()
+    }
+}
+
+mod helpers {
+    use super::{Pixel, EMPHASIS_ATTENUATION};
+
+    /// Apply emphasis to a base color by attenuating the other channels
+    pub fn emphasize(rgb: Pixel, color: usize, emphasis: usize) -> Pixel {
+        // $xE and $xF are black and not affected by emphasis
+        if emphasis == 0 || color & 0x0F >= 0x0E {
+            return rgb;
+        }
+
+        let mut channels = [rgb.0 as f32, rgb.1 as f32, rgb.2 as f32];
+
+        for bit in 0..3 {
+            if emphasis & (1 << bit) != 0 {
+                for (other, channel) in channels.iter_mut().enumerate() {
+                    if other != bit {
+                        *channel *= EMPHASIS_ATTENUATION;
+                    }
+                }
+            }
+        }
+
+        (channels[0] as u8, channels[1] as u8, channels[2] as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_palettes() {
+        let ntsc = Palette::ntsc();
+        assert_eq!(ntsc.color(0x01, 0), (1, 26, 81));
+        assert_eq!(ntsc.to_bytes().as_slice(), &include_bytes!("ntscpalette.pal")[..]);
+
+        assert_eq!(Palette::pal().to_bytes().len(), PALETTE_ENTRIES * 3);
+        assert_ne!(Palette::pal(), ntsc);
+    }
+
+    #[test]
+    fn rgb_palette() {
+        let rgb = Palette::rgb();
+
+        assert_eq!(rgb.color(0x20, 0), (0xFF, 0xFF, 0xFF));
+        assert_eq!(rgb.color(0x01, 0), (0x00, 0x24, 0x91));
+        // Red emphasis sets the red channel to full brightness
+        assert_eq!(rgb.color(0x01, 0x01), (0xFF, 0x24, 0x91));
+        assert_eq!(rgb.color(0x01, 0x06), (0x00, 0xFF, 0xFF));
+    }
+
+    #[test]
+    fn load_64_entries() {
+        let data: Vec<u8> = (0..PALETTE_COLORS).flat_map(|_| vec![200u8, 100, 50]).collect();
+        let palette = Palette::from_slice(&data).unwrap();
+
+        assert_eq!(palette.color(0x05, 0), (200, 100, 50));
+        // Red emphasis attenuates green and blue
+        assert_eq!(palette.color(0x05, 0x01), (200, 74, 37));
+        // All emphasis bits attenuate every channel twice
+        assert_eq!(palette.color(0x05, 0x07), (111, 55, 27));
+        // Black is not affected
+        assert_eq!(palette.color(0x0F, 0x07), (200, 100, 50));
+    }
+
+    #[test]
+    fn load_512_entries() {
+        let data: Vec<u8> = (0..PALETTE_ENTRIES).flat_map(|i| vec![(i / PALETTE_COLORS) as u8, (i % PALETTE_COLORS) as u8, 0]).collect();
+        let palette = Palette::from_slice(&data).unwrap();
+
+        assert_eq!(palette.color(0x3F, 0x05), (5, 0x3F, 0));
+        assert_eq!(palette.raw_color(0x17F), (5, 0x3F, 0));
+        assert_eq!(palette.to_bytes(), data);
+    }
+
+    #[test]
+    fn invalid_size() {
+        assert!(matches!(Palette::from_slice(&[0u8; 100]), Err(PaletteError::InvalidSize(100))));
+        assert!(matches!(Palette::from_slice(&[0u8; 193]), Err(PaletteError::InvalidSize(193))));
+    }
+}
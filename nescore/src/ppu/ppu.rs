@@ -6,7 +6,7 @@
 //
 use super::regs::*;
 use super::hw::*;
-use super::sprite::Sprite;
+use super::sprite::{self, Sprite, SpriteEvaluator, NUM_SPRITES, SPRITES_PER_SCANLINE};
 use crate::common::{IoAccess, Clockable, Register, Interrupt};
 
 use std::cell::RefCell;
@@ -14,12 +14,15 @@ use std::cell::RefCell;
 const NUM_SCANLINES: usize = 262;
 const CYCLES_PER_SCANLINE: usize = 341;
 const TILES_PER_ROW: usize = 32;
+/// Dots between the NMI output going high and the CPU seeing the interrupt
+const NMI_DELAY: u8 = 2;
 
 /// RGB Pixel
 pub type Pixel = (u8, u8, u8);
+/// Raw PPU output. 6-bit color index in bits 0-5 and the PPUMASK emphasis bits in bits 6-8
+pub type RawPixel = u16;
 pub const DISPLAY_WIDTH: usize = 256;
 pub const DISPLAY_HEIGHT: usize = 240;
-pub const CYCLES_PER_FRAME: usize = NUM_SCANLINES * CYCLES_PER_SCANLINE;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 enum Scanline {
@@ -45,7 +48,15 @@ impl Scanline {
 /// NES Picture Processing Unit
 pub struct Ppu<Io: IoAccess> {
     oam: [u8; 256],            // Object Attribute Memory (Sprites)
-    sprite_cache: [Option<Sprite>; 8],   // Up to 8 sprites per scanline
+    sprite_cache: Vec<Sprite>, // Sprites on the next scanline
+    sprite_eval: SpriteEvaluator,
+    sprite_limit: bool,        // Limit sprites to 8 per scanline
+
+    background_visible: bool,  // Display toggles, independent of PPUMASK
+    sprites_visible: bool,
+    hidden_sprites: u64,       // Bit mask of sprite indices that are not displayed
+    layer_output: bool,        // Record the background and sprite layers of each pixel
+    layer_pixels: (RawPixel, RawPixel),
 
     ctrl: PpuCtrl,              // PPUCTRL   - Control Register
     status: RefCell<PpuStatus>, // PPUSTATUS - Status Register
@@ -60,21 +71,37 @@ pub struct Ppu<Io: IoAccess> {
     // Render pipeline hardware
     tile_reg: TileRegister,    // PPU tile shift registers
     pal_reg: PaletteRegister,  // PPU palette shift registers
-    sprite_regs: [SpriteRegister; 8],
+    sprite_regs: Vec<SpriteRegister>,
 
     cycle: usize,              // Cycle count per scanline
     scanline: usize,           // Current scanline
+    frame: u64,                // Number of frames rendered
+    dot_skipped: bool,         // The current frame skipped the last dot of the pre-render scanline
+    short_frame: bool,         // The previous frame skipped the last dot of the pre-render scanline
 
-    bus: Option<Io>,
+    nmi_output: bool,          // State of the NMI line on the previous dot
+    nmi_delay: Option<u8>,     // Dots until a pending NMI reaches the CPU
+    vbl_suppress: RefCell<bool>, // PPUSTATUS was read just before the VBL flag was set
+
+    latch: RefCell<IoLatch>,   // I/O data bus latch
+    read_buffer: RefCell<u8>,  // PPUDATA read buffer
 
-    rgb_palette: [u8; 0x600],
+    bus: Option<Io>,
 }
 
 impl<Io: IoAccess> Default for Ppu<Io> {
     fn default() -> Self {
         Ppu{
             oam: [0; 256],
-            sprite_cache: [None; 8],
+            sprite_cache: Vec::with_capacity(NUM_SPRITES),
+            sprite_eval: SpriteEvaluator::default(),
+            sprite_limit: true,
+
+            background_visible: true,
+            sprites_visible: true,
+            hidden_sprites: 0,
+            layer_output: false,
+            layer_pixels: (0, 0),
 
             ctrl: PpuCtrl::default(),
             status: RefCell::new(PpuStatus::default()),
@@ -88,26 +115,35 @@ impl<Io: IoAccess> Default for Ppu<Io> {
 
             tile_reg: TileRegister::default(),
             pal_reg: PaletteRegister::default(),
-            sprite_regs: [SpriteRegister::default(); 8],
+            sprite_regs: Vec::with_capacity(NUM_SPRITES),
 
             cycle: 0,
             scanline: NUM_SCANLINES - 1, // Initialize to the Pre-render scanline
+            frame: 0,
+            dot_skipped: false,
+            short_frame: false,
 
-            bus: None,
+            nmi_output: false,
+            nmi_delay: None,
+            vbl_suppress: RefCell::new(false),
 
-            rgb_palette: *include_bytes!("ntscpalette.pal"),
+            latch: RefCell::new(IoLatch::default()),
+            read_buffer: RefCell::new(0),
+
+            bus: None,
         }
     }
 }
 
 impl<Io: IoAccess> Ppu<Io> {
-    fn run_cycle(&mut self) -> Option<Pixel> {
+    fn run_cycle(&mut self) -> Option<RawPixel> {
         let scanline = Scanline::from(self.scanline);
         match scanline {
             Scanline::PreRender => {
                 if self.cycle == 1 {
                     self.clear_sprite_data();
                     self.status.borrow_mut().sprite0_hit = false;
+                    self.status.borrow_mut().sprite_overflow = false;
                     self.status.borrow_mut().vblank = false;
                 }
 
@@ -151,11 +187,9 @@ impl<Io: IoAccess> Ppu<Io> {
             },
             Scanline::VBlank => {
                 if self.cycle == 1 && self.scanline == 241 {
-                    self.status.borrow_mut().vblank = true;
-
-                    // Signal NMI interrupt
-                    if self.ctrl.nmi_enable {
-                        self.raise_interrupt();
+                    // Reading PPUSTATUS one dot before prevents the flag from being set for this frame
+                    if !self.vbl_suppress.replace(false) {
+                        self.status.borrow_mut().vblank = true;
                     }
                 }
 
@@ -177,6 +211,24 @@ impl<Io: IoAccess> Ppu<Io> {
                     self.load_shift_registers();
                 }
 
+                // Sprite evaluation for the next scanline. Secondary OAM is cleared during cycles 1-64
+                if self.scanline < DISPLAY_HEIGHT {
+                    if dot == 1 {
+                        self.sprite_eval.reset();
+                    }
+                    else if dot >= 65 && dot % 2 == 1 {
+                        // Evaluation starts at the sprite OAMADDR points to
+                        if dot == 65 {
+                            self.sprite_eval.start(*self.oam_addr.borrow() as u8);
+                        }
+
+                        let overflow = self.sprite_eval.step(&self.oam, self.scanline, self.ctrl.sprite_height());
+                        if overflow {
+                            self.status.borrow_mut().sprite_overflow = true;
+                        }
+                    }
+                }
+
                 if dot == 256 && self.mask.rendering_enabled() {
                     // At dot 256 the increment part of v is incremented (if rendering)
                     self.v.borrow_mut().increment_v();
@@ -185,33 +237,42 @@ impl<Io: IoAccess> Ppu<Io> {
             257..=320 => {
                 // Cycles 257 - 320: Get tile data for sprites on next scanline
                 // Sprite eval is complete by cycle 257
+                // OAMADDR is cleared during sprite tile loading
+                *self.oam_addr.borrow_mut() = 0;
+
                 if dot == 257 {
-                    let scanline = ((self.scanline + 1) % NUM_SCANLINES) as u16;
-                    self.evaluate_sprites(scanline);
+                    self.evaluate_sprites();
+                    self.sprite_regs.clear();
 
                     // At dot 257, the horizontal bits of t are copied to v (if rendering)
                     if self.mask.rendering_enabled() {
                         self.v.borrow_mut().reload_x(self.t.borrow().value());
                     }
                 }
+
+                // Each sprite slot takes 8 cycles: two garbage nametable fetches, then the pattern table low and high
+                // bytes. The pattern fetch for slot 0 happens at dot 261
+                if (dot - 257) % 8 == 0 || (dot - 257) % 8 == 2 {
+                    self.read_vram(self.v.borrow().tile());
+                }
+                else if (dot - 257) % 8 == 4 {
+                    let scanline = (self.scanline + 1) % NUM_SCANLINES;
+                    self.load_sprite_data((dot - 257) / 8, scanline as u16);
+                }
             },
             321..=336 => {
                 // Cycles 321-336: Fetch first two tiles of the next scanline
                 // accesses: 2 nametable bytes, attribute, pattern table low, pattern table high
-                let scanline = (self.scanline + 1) % NUM_SCANLINES;
-
                 if dot % 8 == 0 {
                     self.load_shift_registers();
                 }
-
-                // Sprite data loading has completed by cycle 321
-                if dot == 321 {
-                    self.load_sprite_data(scanline as u16);
-                }
             },
             337..=340 => {
-                // Cycles 337 - 340
-                // Two nametable bytes are fetch, unknown purpose
+                // Cycles 337 - 340: Two nametable fetches of the next tile. Their values are unused, but they are seen
+                // by mappers watching the address bus (MMC5 counts them to detect the scanline)
+                if dot == 337 || dot == 339 {
+                    self.read_vram(self.v.borrow().tile());
+                }
             },
             _ => panic!("Invalid cycle for scanline! {}", dot),
         }
@@ -243,94 +304,98 @@ impl<Io: IoAccess> Ppu<Io> {
         }
     }
 
-    fn evaluate_sprites(&mut self, scanline: u16) {
-        // Sprite data is delay by one scanline
-        let scanline = scanline as i16;
-        // Scan primary OAM for sprites that are on the specified scanline
-        let mut found_sprites = 0;
+    /// Fill the sprite cache with the sprites found during evaluation
+    fn evaluate_sprites(&mut self) {
+        self.sprite_cache.clear();
 
-        // Clear sprite cache
-        self.sprite_cache = [None; 8];
+        // No sprites are evaluated on the pre-render scanline
+        if self.scanline >= DISPLAY_HEIGHT {
+            return;
+        }
 
-        if scanline < 240 {
-            for n in 0..64 {
-                if found_sprites < 8 {
-                    let offset = n * 4;
-                    let sprite = Sprite::from(&self.oam[offset..offset+4], n as u8);
+        let oam = &self.oam;
+        let sprite_from = |n: usize| Sprite::from(&oam[n * 4..n * 4 + 4], n as u8);
 
-                    let intersect = scanline - sprite.y as i16;
-                    let h = self.ctrl.sprite_height() as i16;
+        if self.sprite_limit {
+            self.sprite_cache.extend(self.sprite_eval.sprites().iter().map(|&n| sprite_from(n as usize)));
+        }
+        else {
+            // Scan from the same place as the hardware, so the first 8 sprites are the ones it selects
+            let height = self.ctrl.sprite_height();
+            let scanline = self.scanline;
+            let oam_addr = self.sprite_eval.oam_addr() as usize;
+            let (first, offset) = (oam_addr / 4, oam_addr % 4);
 
-                    if intersect >= 0 && intersect < h {
-                        // Found a valid sprite
-                        // Move to the sprite cache
-                        self.sprite_cache[found_sprites] = Some(sprite);
-                        found_sprites += 1;
-                    }
-                }
-                else {
-                    // TODO: Set sprite overflow flag
-                    break;
-                }
-            }
+            self.sprite_cache.extend((first..NUM_SPRITES).filter(|n| sprite::in_range(oam[n * 4 + offset], scanline, height)).map(sprite_from));
         }
     }
 
-    fn load_sprite_data(&mut self, scanline: u16) {
-        for (i, sprite) in self.sprite_cache.iter().enumerate() {
-            if let Some(ref sprite) = sprite {
-                let sprite_height = self.ctrl.sprite_height();
+    /// Fetch the pattern data for a sprite slot
+    fn load_sprite_data(&mut self, slot: usize, scanline: u16) {
+        if slot >= self.sprite_cache.len() {
+            // Empty slots still fetch a pattern, using tile $FF from the cleared secondary OAM
+            let (pattern_table, tile) = if self.ctrl.sprite_height() == 16 {
+                (0x1000, 0xFE)
+            }
+            else {
+                (self.ctrl.sprite_pattern_table(), 0xFF)
+            };
 
-                // Determine fine y for vertical flipping
-                let fine_y = if !sprite.flip_v() {
-                    (scanline - sprite.y) as u8
-                }
-                else {
-                    (sprite_height - 1) - (scanline - sprite.y) as u8
-                };
+            self.read_pattern(pattern_table, tile, 0);
 
-                // In 8x16 mode the PPU ignores the sprite pattern table selection in the CTRL register
-                // The table selection instead comes from the first bit of the sprite's tile attribute
-                // The tile number selection is then the upper bits of the tile attribute (upper tile)
-                // the bottom tile is the next one
-                // Re-adjusting the fine y will also be necessary
-                let (pattern_table, tile, fine_y) = if sprite_height == 16 {
-                    let bottom_tile = fine_y > 7;
-                    (
-                        sprite.pattern_table_8x16(),
-                        sprite.tile_number_8x16() + if bottom_tile { 1 } else { 0 },
-                        fine_y - if bottom_tile { 8 } else { 0 }
-                    )
-                }
-                else {
-                    (self.ctrl.sprite_pattern_table(), sprite.tile, fine_y)
-                };
+            return;
+        }
 
-                let pattern = self.read_pattern(pattern_table, tile, fine_y);
+        // Without the sprite limit, the extra sprites are fetched along with the last slot
+        let end = if slot == SPRITES_PER_SCANLINE - 1 { self.sprite_cache.len() } else { slot + 1 };
 
-                // Reverse bit pattern if the sprite is horizontally flipped
-                let pattern = if sprite.flip_h() {
-                    (reverse_bits!(pattern.0), reverse_bits!(pattern.1))
-                }
-                else {
-                    pattern
-                };
+        for n in slot..end {
+            let sprite = self.sprite_cache[n];
+            let sprite_height = self.ctrl.sprite_height();
 
-                self.sprite_regs[i].load(sprite.x, pattern, sprite.palette(), sprite.priority(), sprite.num);
+            // Determine fine y for vertical flipping
+            let fine_y = if !sprite.flip_v() {
+                (scanline - sprite.y) as u8
             }
-        }
-    }
+            else {
+                (sprite_height - 1) - (scanline - sprite.y) as u8
+            };
+
+            // In 8x16 mode the PPU ignores the sprite pattern table selection in the CTRL register
+            // The table selection instead comes from the first bit of the sprite's tile attribute
+            // The tile number selection is then the upper bits of the tile attribute (upper tile)
+            // the bottom tile is the next one
+            // Re-adjusting the fine y will also be necessary
+            let (pattern_table, tile, fine_y) = if sprite_height == 16 {
+                let bottom_tile = fine_y > 7;
+                (
+                    sprite.pattern_table_8x16(),
+                    sprite.tile_number_8x16() + if bottom_tile { 1 } else { 0 },
+                    fine_y - if bottom_tile { 8 } else { 0 }
+                )
+            }
+            else {
+                (self.ctrl.sprite_pattern_table(), sprite.tile, fine_y)
+            };
 
-    fn clear_sprite_data(&mut self) {
-        // TODO: Clear OAM data?
-        for sprite_reg in &mut self.sprite_regs {
-            sprite_reg.load(0, (0, 0), 0, false, 0);
+            let pattern = self.read_pattern(pattern_table, tile, fine_y);
+
+            // Reverse bit pattern if the sprite is horizontally flipped
+            let pattern = if sprite.flip_h() {
+                (reverse_bits!(pattern.0), reverse_bits!(pattern.1))
+            }
+            else {
+                pattern
+            };
+
+            let mut sprite_reg = SpriteRegister::default();
+            sprite_reg.load(sprite.x, pattern, sprite.palette(), sprite.priority(), sprite.num);
+            self.sprite_regs.push(sprite_reg);
         }
     }
 
-    fn read_nametable(&self, nametable: u16, idx: usize) -> u8 {
-        let addr = helpers::calc_nametable_address(nametable, idx);
-        self.read_vram(addr)
+    fn clear_sprite_data(&mut self) {
+        self.sprite_regs.clear();
     }
 
     fn read_attribute(&self, nametable: u16, tile_row: usize, tile_col: usize) -> u8 {
@@ -367,12 +432,12 @@ impl<Io: IoAccess> Ppu<Io> {
         (lo, hi)
     }
 
-    fn get_sprite_pixel_data(&self) -> (u8, u8, bool, bool) {
+    fn get_sprite_pixel_data(&self, hidden_sprites: u64) -> (u8, u8, bool, bool) {
         let mut pixel_data = (0, 0, false, false);
 
         // Find the first opaque pixel for the active sprites
         for sprite_reg in self.sprite_regs.iter() {
-            if sprite_reg.active() {
+            if sprite_reg.active() && !bit_is_set!(hidden_sprites, sprite_reg.sprite_num()) {
                 let sprite_data = sprite_reg.get_value();
                 // Check if not opaque
                 if sprite_data.0 != 0 {
@@ -385,7 +450,7 @@ impl<Io: IoAccess> Ppu<Io> {
         pixel_data
     }
 
-    fn apply_mux(&self) -> Pixel {
+    fn apply_mux(&mut self) -> RawPixel {
         let dot = self.cycle;
 
         // Fetch pattern and attributes from shifters
@@ -409,7 +474,7 @@ impl<Io: IoAccess> Ppu<Io> {
                 (0, 0, false, false)
             }
             else {
-                self.get_sprite_pixel_data()
+                self.get_sprite_pixel_data(0)
             }
         }
         else {
@@ -421,27 +486,46 @@ impl<Io: IoAccess> Ppu<Io> {
             self.status.borrow_mut().sprite0_hit = true;
         }
 
+        // Apply the display toggles after sprite 0 hit detection, so hidden layers do not affect the game
+        let (bg_pattern, bg_palette) = if self.background_visible { (bg_pattern, bg_palette) } else { (0, 0) };
+        let (sp_pattern, sp_palette, sp_priority) = if !self.sprites_visible {
+            (0, 0, false)
+        }
+        else if self.hidden_sprites != 0 && self.mask.sprites_enabled && (self.mask.show_sprites_left || dot >= 8) {
+            let (pattern, palette, priority, _) = self.get_sprite_pixel_data(self.hidden_sprites);
+            (pattern, palette, priority)
+        }
+        else {
+            (sp_pattern, sp_palette, sp_priority)
+        };
+
+        if self.layer_output {
+            let bg_layer = helpers::pixel_mux((bg_pattern, bg_palette), (0, 0), false);
+            let sp_layer = helpers::pixel_mux((0, 0), (sp_pattern, sp_palette), false);
+            self.layer_pixels = (self.palette_color(bg_layer), self.palette_color(sp_layer));
+        }
+
         // Choose which pattern and palette to use
         // Select the sprite data is the sprite pixel is opaque and has front priority OR the background is transparent
-        let (pattern, palette, palette_group) = helpers::pixel_mux((bg_pattern, bg_palette), (sp_pattern, sp_palette), sp_priority);
+        let pixel = helpers::pixel_mux((bg_pattern, bg_palette), (sp_pattern, sp_palette), sp_priority);
+
+        self.palette_color(pixel)
+    }
+
+    /// Look up the color of a multiplexed pixel in palette RAM
+    fn palette_color(&self, (pattern, palette, palette_group): (u8, u8, u8)) -> RawPixel {
         // Determine palette offset: http://wiki.nesdev.com/w/index.php/PPU_palettes
         let palette_offset = palette_group | (palette << 2) | pattern;
 
-        // Fetch color from palette
-        let color = self.read_vram(0x3F00 + palette_offset as u16) as usize;
+        // Fetch color from palette. Palette RAM is internal to the PPU and is not accessed over the bus
+        let color = self.peek_vram(0x3F00 + palette_offset as u16) as usize;
 
         // Apply grey scale if applicable
         // Four rows of colors in the palette: $00, $10, $20, $30.
         // The first colors in the row are the grey colors
         let color = if self.mask.greyscale { color & 0x30 } else { color };
-        // Get the index into the RGB palette, account for emphasis bits
-        let color_idx = (self.mask.pal_idx * 64) + (color * 3);
-
-        (
-            self.rgb_palette[color_idx],
-            self.rgb_palette[color_idx + 1],
-            self.rgb_palette[color_idx + 2],
-        )
+        // Combine with the emphasis bits
+        (color as u16 & 0x3F) | ((self.mask.pal_idx as u16 & 0x07) << 6)
     }
 
     fn tick_shifters(&mut self) {
@@ -455,6 +539,30 @@ impl<Io: IoAccess> Ppu<Io> {
         }
     }
 
+    /// Signal NMI on the rising edge of the NMI output, if it is still asserted after a short delay
+    ///
+    /// Reading PPUSTATUS within a couple of dots of the VBL flag being set clears the flag in time to suppress the NMI.
+    /// Enabling NMI while the VBL flag is set causes another NMI.
+    fn update_nmi(&mut self) {
+        let nmi_output = self.status.borrow().vblank && self.ctrl.nmi_enable;
+
+        if nmi_output && !self.nmi_output {
+            self.nmi_delay = Some(NMI_DELAY);
+        }
+        self.nmi_output = nmi_output;
+
+        self.nmi_delay = match self.nmi_delay {
+            Some(0) => {
+                if nmi_output {
+                    self.raise_interrupt();
+                }
+                None
+            },
+            Some(delay) => Some(delay - 1),
+            None => None,
+        };
+    }
+
     /// Raise NMI interrupt
     fn raise_interrupt(&mut self) {
         if let Some(ref mut bus) = self.bus {
@@ -472,6 +580,16 @@ impl<Io: IoAccess> Ppu<Io> {
         }
     }
 
+    /// Read from PPU VRAM without the access appearing on the address bus
+    pub fn peek_vram(&self, addr: u16) -> u8 {
+        if let Some(ref bus) = self.bus {
+            bus.peek_byte(addr & 0x3FFF)
+        }
+        else {
+            panic!("PPU's bus not initialized");
+        }
+    }
+
     /// Write directly to PPU VRAM
     pub fn write_vram(&mut self, addr: u16, value: u8) {
         if let Some(ref mut bus) = self.bus {
@@ -483,13 +601,85 @@ impl<Io: IoAccess> Ppu<Io> {
         self.oam[addr as usize] = value;
     }
 
+    /// Number of frames started. Incremented at the start of the pre-render scanline
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Whether the last completed frame was one dot short, due to the odd frame dot skip
+    pub fn short_frame(&self) -> bool {
+        self.short_frame
+    }
+
+    /// Limit rendering to 8 sprites per scanline, like the hardware. Sprite overflow is still detected without the limit
+    pub fn set_sprite_limit(&mut self, enabled: bool) {
+        self.sprite_limit = enabled;
+    }
+
+    /// Display the background layer. Does not affect rendering or sprite 0 hit
+    pub fn set_background_visible(&mut self, visible: bool) {
+        self.background_visible = visible;
+    }
+
+    /// Display the sprite layer. Does not affect rendering or sprite 0 hit
+    pub fn set_sprites_visible(&mut self, visible: bool) {
+        self.sprites_visible = visible;
+    }
+
+    /// Display an individual sprite. Hidden sprites still take up one of the 8 sprite slots on a scanline
+    ///
+    /// Indices outside of OAM are ignored
+    pub fn set_sprite_visible(&mut self, index: usize, visible: bool) {
+        if index >= NUM_SPRITES {
+            return;
+        }
+
+        let mask = 1u64 << index;
+        if visible {
+            self.hidden_sprites &= !mask;
+        }
+        else {
+            self.hidden_sprites |= mask;
+        }
+    }
+
+    /// Record the background and sprite layers of each output pixel
+    pub fn set_layer_output(&mut self, enabled: bool) {
+        self.layer_output = enabled;
+    }
+
+    /// Background and sprite layers of the last output pixel. Transparent pixels are the backdrop color
+    pub fn layer_pixels(&self) -> (RawPixel, RawPixel) {
+        self.layer_pixels
+    }
+
     pub fn load_bus(&mut self, bus: Io) {
         self.bus = Some(bus);
     }
 
     pub fn read_tile(&self, nametable: u16, x: usize, y: usize) -> u8 {
         let idx = (y * TILES_PER_ROW) + x;
-        self.read_nametable(nametable, idx)
+        self.peek_vram(helpers::calc_nametable_address(nametable, idx))
+    }
+
+    /// Object Attribute Memory
+    pub fn oam(&self) -> &[u8; 256] {
+        &self.oam
+    }
+
+    /// Scroll position of the next frame within the 512x480 nametable space, from the temporary VRAM address and fine X
+    pub fn scroll(&self) -> (usize, usize) {
+        let t = self.t.borrow();
+        let nametable = (t.nametable() >> 10) & 0x03;
+
+        let x = (nametable & 0x01) as usize * DISPLAY_WIDTH + t.coarse_x() as usize * 8 + self.x as usize;
+        let y = (nametable >> 1) as usize * DISPLAY_HEIGHT + t.coarse_y() as usize * 8 + t.fine_y() as usize;
+
+        (x, y % (DISPLAY_HEIGHT * 2))
+    }
+
+    pub(crate) fn ctrl(&self) -> &PpuCtrl {
+        &self.ctrl
     }
 }
 
@@ -498,46 +688,68 @@ impl<Io: IoAccess> Ppu<Io> {
 impl<Io: IoAccess> IoAccess for Ppu<Io> {
     fn read_byte(&self, addr: u16) -> u8 {
         match addr {
-            0x2000 => {
-                // Clear the write toggle
-                *self.w.borrow_mut() = false;
-                self.ctrl.value()
-            },
-            0x2001 => {
-                self.mask.value()
-            },
             0x2002 => {
-                let status = self.status.borrow().value();
-                // VBlank flag is clear on reading the status register
+                // The lower 5 bits are the contents of the I/O latch
+                let status = {
+                    let mut status = self.status.borrow_mut();
+                    status.lsb = self.latch.borrow().value();
+                    status.value()
+                };
+
+                // VBlank flag and the write toggle are cleared on reading the status register
                 self.status.borrow_mut().vblank = false;
+                *self.w.borrow_mut() = false;
+
+                // Reading one dot before VBL is set suppresses the flag
+                if self.scanline == 241 && self.cycle == 1 {
+                    *self.vbl_suppress.borrow_mut() = true;
+                }
+
+                self.latch.borrow_mut().load(status, 0xE0, self.frame);
 
                 status
             },
-            0x2003 => {
-                0
-            },
             0x2004 => {
                 let data = self.oam[*self.oam_addr.borrow() as usize];
-                // Increment OAM pointer
-                let new_oam_addr = self.oam_addr.borrow().wrapping_add(1) % 256;
-                *self.oam_addr.borrow_mut() = new_oam_addr;
+                // Bits 2-4 of the sprite attribute byte are not implemented
+                let data = if *self.oam_addr.borrow() & 0x03 == 0x02 { data & 0xE3 } else { data };
+
+                self.latch.borrow_mut().load(data, 0xFF, self.frame);
 
                 data
             },
-            0x2005 => {
-                self.x
-            },
-            // PPU Address
-            0x2006 => {
-                self.v.borrow().value() as u8
-            },
             // PPU Data
             0x2007 => {
-                let data = self.read_vram(self.v.borrow().value());
+                let addr = self.v.borrow().value() & 0x3FFF;
+
+                let data = if addr >= 0x3F00 {
+                    // Palette data is returned immediately. The upper 2 bits come from the latch
+                    let color = self.peek_vram(addr);
+                    let color = if self.mask.greyscale { color & 0x30 } else { color & 0x3F };
+                    let data = color | (self.latch.borrow().value() & 0xC0);
+
+                    // The buffer is filled with the nametable data "underneath" the palette
+                    *self.read_buffer.borrow_mut() = self.read_vram(addr - 0x1000);
+                    self.latch.borrow_mut().load(data, 0x3F, self.frame);
+
+                    data
+                }
+                else {
+                    // Return the buffered value and fill the buffer with the current address
+                    let data = self.read_buffer.replace(self.read_vram(addr));
+                    self.latch.borrow_mut().load(data, 0xFF, self.frame);
+
+                    data
+                };
+
                 *self.v.borrow_mut() += self.ctrl.vram_increment();
 
                 data
             },
+            // Write-only registers return the contents of the I/O latch
+            0x2000 | 0x2001 | 0x2003 | 0x2005 | 0x2006 => {
+                self.latch.borrow().value()
+            },
 
             _ => panic!("Invalid read from PPU: ${:04X}", addr),
         }
@@ -586,6 +798,12 @@ impl<Io: IoAccess> IoAccess for Ppu<Io> {
                 if *self.w.borrow() {
                     self.t.borrow_mut().set_low_byte(value);
                     self.v.borrow_mut().load(self.t.borrow().value());
+
+                    // The new address is driven onto the bus
+                    let addr = self.v.borrow().value();
+                    if let Some(ref mut bus) = self.bus {
+                        bus.set_address(addr);
+                    }
                 }
                 else {
                     self.t.borrow_mut().set_high_byte(value);
@@ -612,18 +830,32 @@ impl<Io: IoAccess> IoAccess for Ppu<Io> {
             }
         }
 
-        self.status.borrow_mut().lsb = value;
+        self.latch.borrow_mut().load(value, 0xFF, self.frame);
     }
 }
 
-impl<Io: IoAccess> Clockable<Option<Pixel>> for Ppu<Io> {
-    fn tick(&mut self) -> Option<Pixel> {
+impl<Io: IoAccess> Clockable<Option<RawPixel>> for Ppu<Io> {
+    fn tick(&mut self) -> Option<RawPixel> {
         let pixel = self.run_cycle();
 
+        self.update_nmi();
+
         self.cycle += 1;
 
+        // The last dot of the pre-render scanline is skipped on odd frames when rendering
+        if self.scanline == NUM_SCANLINES - 1 && self.cycle == CYCLES_PER_SCANLINE - 1 && self.frame % 2 == 1 && self.mask.rendering_enabled() {
+            self.cycle += 1;
+            self.dot_skipped = true;
+        }
+
         if self.cycle == CYCLES_PER_SCANLINE {
             self.scanline = (self.scanline + 1) % NUM_SCANLINES;
+
+            if self.scanline == NUM_SCANLINES - 1 {
+                self.short_frame = std::mem::take(&mut self.dot_skipped);
+                self.frame += 1;
+                self.latch.borrow_mut().decay(self.frame);
+            }
         }
 
         self.cycle %= CYCLES_PER_SCANLINE;
@@ -677,6 +909,9 @@ mod helpers {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
+
+    const CYCLES_PER_FRAME: usize = NUM_SCANLINES * CYCLES_PER_SCANLINE;
 
     #[test]
     fn mux() {
@@ -771,7 +1006,7 @@ mod tests {
             assert!(pixel.is_none());
         }
 
-        let target_color = ppu_rgb(&ppu, 0x01);
+        let target_color = 0x01;
 
         // Sprites cannot be displayed on the first scanline
         // Run for one more scanline
@@ -785,7 +1020,7 @@ mod tests {
 
         // The color of the pixel should be the index one of the color table
         let color = pixel.unwrap();
-        assert_eq!(color, target_color, "Color was: RGB{:?}", color);
+        assert_eq!(color, target_color, "Color was: ${:03X}", color);
     }
 
     #[test]
@@ -820,7 +1055,7 @@ mod tests {
             assert!(pixel.is_none());
         }
 
-        let target_color = ppu_rgb(&ppu, 0x01);
+        let target_color = 0x01;
 
         // Sprites cannot be displayed on the first scanline
         // Run for one more scanline
@@ -834,7 +1069,168 @@ mod tests {
 
         // The color of the pixel should be the index one of the color table
         let color = pixel.unwrap();
-        assert_eq!(color, target_color, "Color was: RGB{:?}", color);
+        assert_eq!(color, target_color, "Color was: ${:03X}", color);
+    }
+
+    #[test]
+    fn sprite_overflow_flag() {
+        let mut ppu = init_ppu();
+        write_sprite_row(&mut ppu, 9);
+
+        // Run the pre-render scanline
+        for _ in 0..CYCLES_PER_SCANLINE {
+            ppu.tick();
+        }
+
+        assert_eq!(ppu.read_byte(0x2002) & 0x20, 0);
+
+        // Overflow is detected during sprite evaluation for the next scanline
+        for _ in 0..CYCLES_PER_SCANLINE {
+            ppu.tick();
+        }
+
+        assert_eq!(ppu.read_byte(0x2002) & 0x20, 0x20);
+    }
+
+    #[test]
+    fn sprite_fetch_without_sprites() {
+        let mut ppu = init_ppu();
+        write_sprite_row(&mut ppu, 0);
+        // Sprites at $1000, background at $0000
+        ppu.write_byte(0x2000, 0x08);
+
+        // Run the pre-render scanline
+        for _ in 0..CYCLES_PER_SCANLINE {
+            ppu.tick();
+        }
+
+        // Every sprite slot makes a dummy fetch from the sprite pattern table
+        let mut dots = vec![];
+        for _ in 0..CYCLES_PER_SCANLINE {
+            let dot = ppu.cycle;
+            let reads = ppu.bus.as_ref().unwrap().a12_reads.get();
+            ppu.tick();
+
+            if ppu.bus.as_ref().unwrap().a12_reads.get() > reads {
+                dots.push(dot);
+            }
+        }
+
+        assert_eq!(dots, vec![261, 269, 277, 285, 293, 301, 309, 317]);
+    }
+
+    #[test]
+    fn nametable_fetches_after_visible_dots() {
+        let mut ppu = init_ppu();
+        write_sprite_row(&mut ppu, 0);
+
+        for _ in 0..CYCLES_PER_SCANLINE + 257 {
+            ppu.tick();
+        }
+
+        let mut dots = vec![];
+        while ppu.cycle != 0 {
+            let dot = ppu.cycle;
+            let reads = ppu.bus.as_ref().unwrap().nametable_reads.get();
+            ppu.tick();
+
+            if ppu.bus.as_ref().unwrap().nametable_reads.get() > reads {
+                dots.push(dot);
+            }
+        }
+
+        // Two garbage fetches per sprite slot, the first two tiles of the next scanline, then the two unused fetches
+        let sprite_slots = (0..8).flat_map(|slot| vec![257 + slot * 8, 259 + slot * 8]);
+        let expected: Vec<usize> = sprite_slots.chain(vec![328, 336, 337, 339]).collect();
+        assert_eq!(dots, expected);
+    }
+
+    #[test]
+    fn sprite_limit() {
+        let count_sprite_pixels = |limit: bool| {
+            let mut ppu = init_ppu();
+            ppu.set_sprite_limit(limit);
+            write_sprite_row(&mut ppu, 9);
+
+            for _ in 0..CYCLES_PER_SCANLINE * 2 {
+                ppu.tick();
+            }
+
+            (0..CYCLES_PER_SCANLINE).filter_map(|_| ppu.tick()).filter(|&color| color == 0x01).count()
+        };
+
+        assert_eq!(count_sprite_pixels(true), 8);
+        assert_eq!(count_sprite_pixels(false), 9);
+    }
+
+    #[test]
+    fn sprite_limit_starts_at_oam_addr() {
+        let count_sprite_pixels = |limit: bool| {
+            let mut ppu = init_ppu();
+            ppu.set_sprite_limit(limit);
+            write_sprite_row(&mut ppu, 9);
+
+            // The pre-render scanline clears OAMADDR
+            for _ in 0..CYCLES_PER_SCANLINE {
+                ppu.tick();
+            }
+
+            // Evaluation for the next scanline skips sprites 0 and 1
+            ppu.write_byte(0x2003, 0x08);
+
+            for _ in 0..CYCLES_PER_SCANLINE {
+                ppu.tick();
+            }
+
+            (0..CYCLES_PER_SCANLINE).filter_map(|_| ppu.tick()).filter(|&color| color == 0x01).count()
+        };
+
+        assert_eq!(count_sprite_pixels(true), 7);
+        assert_eq!(count_sprite_pixels(false), 7);
+    }
+
+    #[test]
+    fn hidden_sprites() {
+        let count_sprite_pixels = |configure: &dyn Fn(&mut Ppu<FakeBus>)| {
+            let mut ppu = init_ppu();
+            write_sprite_row(&mut ppu, 4);
+            configure(&mut ppu);
+
+            for _ in 0..CYCLES_PER_SCANLINE * 2 {
+                ppu.tick();
+            }
+
+            (0..CYCLES_PER_SCANLINE).filter_map(|_| ppu.tick()).filter(|&color| color == 0x01).count()
+        };
+
+        assert_eq!(count_sprite_pixels(&|_| {}), 4);
+        assert_eq!(count_sprite_pixels(&|ppu| ppu.set_sprite_visible(2, false)), 3);
+        assert_eq!(count_sprite_pixels(&|ppu| {
+            ppu.set_sprite_visible(2, false);
+            ppu.set_sprite_visible(2, true);
+        }), 4);
+        assert_eq!(count_sprite_pixels(&|ppu| ppu.set_sprites_visible(false)), 0);
+        // Out of range indices do not wrap around to another sprite
+        assert_eq!(count_sprite_pixels(&|ppu| ppu.set_sprite_visible(NUM_SPRITES + 2, false)), 4);
+    }
+
+    #[test]
+    fn layer_output() {
+        let mut ppu = init_ppu();
+        ppu.set_layer_output(true);
+        write_sprite_row(&mut ppu, 1);
+        ppu.write_vram(0x3F00, 0x0F);
+
+        for _ in 0..CYCLES_PER_SCANLINE * 2 {
+            ppu.tick();
+        }
+
+        // The first pixel on the scanline is the sprite
+        assert_eq!(ppu.tick(), Some(0x01));
+        assert_eq!(ppu.layer_pixels(), (0x0F, 0x01));
+
+        assert_eq!(ppu.tick(), Some(0x0F));
+        assert_eq!(ppu.layer_pixels(), (0x0F, 0x0F));
     }
 
     #[test]
@@ -869,7 +1265,7 @@ mod tests {
             assert!(pixel.is_none());
         }
 
-        let target_color = ppu_rgb(&ppu, 0x01);
+        let target_color = 0x01;
 
         // Sprites cannot be displayed on the first scanline
         // Run for one more scanline
@@ -916,7 +1312,7 @@ mod tests {
             assert!(pixel.is_none());
         }
 
-        let target_color = ppu_rgb(&ppu, 0x01);
+        let target_color = 0x01;
 
         // Run for all but the last scanline
         for _ in 0..239 {
@@ -974,7 +1370,7 @@ mod tests {
             assert!(pixel.is_none());
         }
 
-        let target_color = ppu_rgb(&ppu, 0x01);
+        let target_color = 0x01;
 
         // Run for all but the last scanline
         for _ in 0..239 {
@@ -1030,7 +1426,7 @@ mod tests {
             assert!(pixel.is_none());
         }
 
-        let target_color = ppu_rgb(&ppu, 0x01);
+        let target_color = 0x01;
 
         // Sprites cannot be displayed on the first scanline
         // Run for one more scanline
@@ -1086,7 +1482,38 @@ mod tests {
 
         // The color of the pixel should be the index one of the color table
         let color = pixel.unwrap();
-        assert_eq!(color, ppu_rgb(&ppu, 0x01), "Color was: RGB{:?}", color);
+        assert_eq!(color, 0x01, "Color was: ${:03X}", color);
+    }
+
+    #[test]
+    fn render_one_pixel_emphasis_and_greyscale() {
+        let mut ppu = init_ppu();
+
+        // Enable background with greyscale and red + blue emphasis
+        let mut mask = PpuMask::default();
+        mask.background_enabled = true;
+        mask.show_background_left = true;
+        mask.greyscale = true;
+        mask.pal_idx = 0x05;
+
+        ppu.write_byte(0x2001, mask.value());
+
+        ppu.write_byte(0x2005, 0);
+        ppu.write_byte(0x2005, 0);
+
+        ppu.write_vram(0x0010, 0x80);
+        ppu.write_vram(0x0018, 0x00);
+        ppu.write_vram(0x2000, 0x01);
+        ppu.write_vram(0x23C0, 0x01);
+        ppu.write_vram(0x3F05, 0x16);
+
+        for _ in 0..CYCLES_PER_SCANLINE {
+            ppu.tick();
+        }
+
+        // Greyscale selects $10, emphasis selects the 6th group of colors
+        let color = ppu.tick().unwrap();
+        assert_eq!(color, 0x150, "Color was: ${:03X}", color);
     }
 
     #[test]
@@ -1119,7 +1546,7 @@ mod tests {
             assert!(ppu.tick().is_none());
         }
 
-        let target_color = ppu_rgb(&ppu, 0x01);
+        let target_color = 0x01;
 
         // The first tile has no data
         for _ in 0..8 {
@@ -1214,22 +1641,82 @@ mod tests {
         ppu.write_byte(0x2006, 0x01);
         ppu.write_byte(0x2006, 0x50);
 
+        // The first read returns the contents of the read buffer
+        ppu.read_byte(0x2007);
         let data = (ppu.read_byte(0x2007), ppu.read_byte(0x2007));
 
         assert_eq!(data, (0xDE, 0xAD));
     }
 
+    #[test]
+    fn palette_read() {
+        let mut ppu = init_ppu();
+
+        ppu.write_vram(0x3F01, 0x21);
+        ppu.write_vram(0x2F01, 0x55);
+        ppu.write_vram(0x2F02, 0x66);
+
+        ppu.write_byte(0x2006, 0x3F);
+        ppu.write_byte(0x2006, 0x01);
+
+        // Palette data is returned immediately, the upper bits come from the latch ($01 was written)
+        assert_eq!(ppu.read_byte(0x2007), 0x21);
+
+        // The buffer contains the nametable data under the palette
+        ppu.write_byte(0x2006, 0x20);
+        ppu.write_byte(0x2006, 0x00);
+        assert_eq!(ppu.read_byte(0x2007), 0x55);
+
+        // Upper bits of palette reads are open bus
+        ppu.write_byte(0x2006, 0x3F);
+        ppu.write_byte(0x2006, 0x01);
+        ppu.write_byte(0x2003, 0xC0);
+        assert_eq!(ppu.read_byte(0x2007), 0xE1);
+    }
+
+    #[test]
+    fn open_bus() {
+        let mut ppu = init_ppu();
+
+        ppu.write_byte(0x2003, 0x5A);
+
+        // Write-only registers return the latch
+        for &addr in [0x2000, 0x2001, 0x2003, 0x2005, 0x2006].iter() {
+            assert_eq!(ppu.read_byte(addr), 0x5A);
+        }
+
+        // PPUSTATUS low bits come from the latch, the status bits are driven onto the bus
+        assert_eq!(ppu.read_byte(0x2002), 0x1A);
+        assert_eq!(ppu.read_byte(0x2000), 0x1A);
+    }
+
+    #[test]
+    fn oam_read() {
+        let mut ppu = init_ppu();
+
+        ppu.write_byte(0x2003, 0x02);
+        ppu.write_byte(0x2004, 0xFF);
+        ppu.write_byte(0x2003, 0x02);
+
+        // Unimplemented attribute bits read as 0. Reading does not increment OAMADDR
+        assert_eq!(ppu.read_byte(0x2004), 0xE3);
+        assert_eq!(ppu.read_byte(0x2004), 0xE3);
+        assert_eq!(ppu.read_byte(0x2001), 0xE3);
+    }
+
     #[test]
     fn vblank() {
         const CYCLES_TO_VBLANK: usize = CYCLES_PER_SCANLINE * 242 + 2;
 
         let mut ppu = init_ppu();
 
-        for _ in 0..CYCLES_TO_VBLANK-1 {
+        for _ in 0..CYCLES_TO_VBLANK-2 {
             ppu.tick();
             assert!(bit_is_clear!(ppu.read_byte(0x2002), 7));
         }
 
+        // Reading on the dot before VBL is set would suppress it
+        ppu.tick();
         ppu.tick();
         assert!(bit_is_set!(ppu.read_byte(0x2002), 7));
         // Should be cleared after reading
@@ -1239,6 +1726,102 @@ mod tests {
         assert!(bit_is_clear!(ppu.read_byte(0x2002), 7));
     }
 
+    #[test]
+    fn vblank_clear_time() {
+        let mut ppu = init_ppu();
+        run_to_vblank(&mut ppu);
+
+        while !(ppu.scanline == NUM_SCANLINES - 1 && ppu.cycle == 1) {
+            ppu.tick();
+        }
+
+        // Cleared at dot 1 of the pre-render scanline
+        assert!(bit_is_set!(ppu.status.borrow().value(), 7));
+        ppu.tick();
+        assert!(bit_is_clear!(ppu.status.borrow().value(), 7));
+    }
+
+    #[test]
+    fn vblank_suppression() {
+        let mut ppu = init_ppu();
+        ppu.write_byte(0x2000, 0x80);
+
+        // Read one dot before the flag is set: flag and NMI are suppressed
+        run_to_vblank(&mut ppu);
+        assert!(bit_is_clear!(ppu.read_byte(0x2002), 7));
+        for _ in 0..CYCLES_PER_SCANLINE {
+            ppu.tick();
+        }
+        assert!(bit_is_clear!(ppu.read_byte(0x2002), 7));
+        assert_eq!(nmi_count(&ppu), 0);
+
+        // Read on the dot the flag is set: the flag is read, NMI is suppressed
+        run_to_vblank(&mut ppu);
+        ppu.tick();
+        assert!(bit_is_set!(ppu.read_byte(0x2002), 7));
+        for _ in 0..CYCLES_PER_SCANLINE {
+            ppu.tick();
+        }
+        assert_eq!(nmi_count(&ppu), 0);
+
+        // Read after the NMI has been signalled
+        run_to_vblank(&mut ppu);
+        for _ in 0..NMI_DELAY {
+            ppu.tick();
+        }
+        assert_eq!(nmi_count(&ppu), 0);
+        ppu.tick();
+        assert!(bit_is_set!(ppu.read_byte(0x2002), 7));
+        assert_eq!(nmi_count(&ppu), 1);
+    }
+
+    #[test]
+    fn nmi_enabled_during_vblank() {
+        let mut ppu = init_ppu();
+
+        run_to_vblank(&mut ppu);
+        for _ in 0..100 {
+            ppu.tick();
+        }
+        assert_eq!(nmi_count(&ppu), 0);
+
+        // Each time NMI is enabled during vblank another NMI occurs
+        for expected in 1..=2 {
+            ppu.write_byte(0x2000, 0x80);
+            for _ in 0..=NMI_DELAY {
+                ppu.tick();
+            }
+            assert_eq!(nmi_count(&ppu), expected);
+
+            ppu.write_byte(0x2000, 0x00);
+            ppu.tick();
+        }
+    }
+
+    #[test]
+    fn odd_frame_dot_skip() {
+        let frame_length = |rendering: bool| {
+            let mut ppu = init_ppu();
+            let mut mask = PpuMask::default();
+            mask.background_enabled = rendering;
+            ppu.write_byte(0x2001, mask.value());
+
+            (0..4).map(|_| {
+                let frame = ppu.frame();
+                let mut dots = 0;
+                while ppu.frame() == frame {
+                    ppu.tick();
+                    dots += 1;
+                }
+                assert_eq!(ppu.short_frame(), dots == CYCLES_PER_FRAME - 1);
+                dots
+            }).collect::<Vec<_>>()
+        };
+
+        assert_eq!(frame_length(false), vec![CYCLES_PER_FRAME; 4]);
+        assert_eq!(frame_length(true), vec![CYCLES_PER_FRAME, CYCLES_PER_FRAME - 1, CYCLES_PER_FRAME, CYCLES_PER_FRAME - 1]);
+    }
+
     #[test]
     fn scanline_state() {
         assert_eq!(Scanline::from(261), Scanline::PreRender);
@@ -1267,34 +1850,75 @@ mod tests {
         assert_eq!(ppu.scanline, 0);
     }
 
-    fn ppu_rgb(ppu: &Ppu<FakeBus>, color: usize) -> (u8, u8, u8) {
-        let idx = color * 3;
-        (
-            ppu.rgb_palette[idx],
-            ppu.rgb_palette[idx + 1],
-            ppu.rgb_palette[idx + 2]
-        )
-    }
-
     struct FakeBus {
         vram: [u8; 0x4000],
+        nmi_count: usize,
+        a12_reads: Cell<usize>,
+        nametable_reads: Cell<usize>,
     }
 
     impl Default for FakeBus {
         fn default() -> Self {
             FakeBus {
                 vram: [0; 0x4000],
+                nmi_count: 0,
+                a12_reads: Cell::new(0),
+                nametable_reads: Cell::new(0),
             }
         }
     }
 
     impl IoAccess for FakeBus {
         fn read_byte(&self, addr: u16) -> u8 {
+            if addr & 0x1000 != 0 && addr < 0x2000 {
+                self.a12_reads.set(self.a12_reads.get() + 1);
+            }
+            if (0x2000..0x3F00).contains(&addr) {
+                self.nametable_reads.set(self.nametable_reads.get() + 1);
+            }
+
             self.vram[addr as usize]
         }
         fn write_byte(&mut self, addr: u16, value: u8) {
             self.vram[addr as usize] = value;
         }
+        fn raise_interrupt(&mut self, _: Interrupt) {
+            self.nmi_count += 1;
+        }
+    }
+
+    fn nmi_count(ppu: &Ppu<FakeBus>) -> usize {
+        ppu.bus.as_ref().unwrap().nmi_count
+    }
+
+    /// Run the PPU until the dot before the VBL flag is set
+    fn run_to_vblank(ppu: &mut Ppu<FakeBus>) {
+        while !(ppu.scanline == 241 && ppu.cycle == 1) {
+            ppu.tick();
+        }
+    }
+
+    /// Enable sprites and place sprites side by side on the second scanline, each with a single opaque pixel
+    fn write_sprite_row(ppu: &mut Ppu<FakeBus>, num_sprites: usize) {
+        let mut mask = PpuMask::default();
+        mask.sprites_enabled = true;
+        mask.show_sprites_left = true;
+
+        ppu.write_byte(0x2001, mask.value());
+
+        for n in 0..num_sprites {
+            for (i, &byte) in [0x00, 0x01, 0x20, (n * 8) as u8].iter().enumerate() {
+                ppu.write_oam((n * 4 + i) as u8, byte);
+            }
+        }
+
+        for n in num_sprites..64 {
+            ppu.write_oam((n * 4) as u8, 0xFF);
+        }
+
+        ppu.write_vram(0x0010, 0x80);
+        ppu.write_vram(0x0018, 0x00);
+        ppu.write_vram(0x3F11, 0x01);
     }
 
     fn init_ppu() -> Ppu<FakeBus> {
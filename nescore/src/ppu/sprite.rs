@@ -5,6 +5,11 @@
 // @date Mar 07 2020
 //
 
+/// Number of sprites the PPU can render on a scanline
+pub const SPRITES_PER_SCANLINE: usize = 8;
+/// Number of sprites in primary OAM
+pub const NUM_SPRITES: usize = 64;
+
 #[derive(Default, Copy, Clone)]
 pub struct Sprite {
     pub y: u16,
@@ -50,10 +55,242 @@ impl Sprite {
     }
 }
 
+/// Sprite evaluation state
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum EvalState {
+    Scan,             // Checking Y coordinates for sprites to copy into secondary OAM
+    Copy(u8),         // Copying the remaining bytes of an in range sprite
+    Overflow,         // Secondary OAM is full, checking for more sprites on the scanline
+    OverflowRead(u8), // Reading the remaining bytes of the sprite that overflowed
+    Done,             // All sprites have been checked
+}
+
+/// Secondary OAM sprite evaluation
+///
+/// Performs one read/write step every two cycles from dot 65 to 256. Once 8 sprites are found the PPU continues to
+/// check for overflow, but incorrectly increments the byte offset along with the sprite index. This causes it to check
+/// tile, attribute and X bytes as if they were Y coordinates.
+/// http://wiki.nesdev.com/w/index.php/PPU_sprite_evaluation
+#[derive(Copy, Clone, Debug)]
+pub struct SpriteEvaluator {
+    n: usize,      // Sprite index in primary OAM
+    m: usize,      // Byte offset into the sprite
+    oam_addr: u8,  // OAMADDR at the start of evaluation
+    state: EvalState,
+    found: [u8; SPRITES_PER_SCANLINE],
+    count: usize,
+}
+
+impl Default for SpriteEvaluator {
+    fn default() -> Self {
+        SpriteEvaluator {
+            n: 0,
+            m: 0,
+            oam_addr: 0,
+            state: EvalState::Scan,
+            found: [0; SPRITES_PER_SCANLINE],
+            count: 0,
+        }
+    }
+}
+
+impl SpriteEvaluator {
+    /// Start evaluation for a new scanline. i.e. Clearing secondary OAM
+    pub fn reset(&mut self) {
+        *self = SpriteEvaluator::default();
+    }
+
+    /// Begin evaluation at the sprite and byte offset selected by OAMADDR
+    ///
+    /// A misaligned OAMADDR offsets the byte checked as the Y coordinate. The sprite found is still loaded from its
+    /// aligned bytes.
+    pub fn start(&mut self, oam_addr: u8) {
+        self.n = (oam_addr / 4) as usize;
+        self.m = (oam_addr % 4) as usize;
+        self.oam_addr = oam_addr;
+    }
+
+    /// OAMADDR evaluation started at
+    pub fn oam_addr(&self) -> u8 {
+        self.oam_addr
+    }
+
+    /// Run a single evaluation step for sprites on `scanline`. Returns true if sprite overflow was detected
+    pub fn step(&mut self, oam: &[u8], scanline: usize, height: u8) -> bool {
+        match self.state {
+            EvalState::Scan => {
+                if in_range(oam[self.n * 4 + self.m], scanline, height) {
+                    self.found[self.count] = self.n as u8;
+                    self.count += 1;
+                    self.state = EvalState::Copy(3);
+                }
+                else {
+                    self.next_sprite();
+                }
+
+                false
+            },
+            EvalState::Copy(remaining) => {
+                if remaining > 1 {
+                    self.state = EvalState::Copy(remaining - 1);
+                }
+                else {
+                    self.next_sprite();
+                }
+
+                false
+            },
+            EvalState::Overflow => {
+                if in_range(oam[self.n * 4 + self.m], scanline, height) {
+                    self.state = EvalState::OverflowRead(3);
+                    true
+                }
+                else {
+                    // Hardware bug: m is incremented along with n
+                    self.n += 1;
+                    self.m = (self.m + 1) % 4;
+
+                    if self.n == NUM_SPRITES {
+                        self.state = EvalState::Done;
+                    }
+
+                    false
+                }
+            },
+            EvalState::OverflowRead(remaining) => {
+                // Read the next bytes, carrying into n
+                self.m += 1;
+                if self.m == 4 {
+                    self.m = 0;
+                    self.n += 1;
+                }
+
+                self.state = if remaining > 1 && self.n < NUM_SPRITES { EvalState::OverflowRead(remaining - 1) } else { EvalState::Done };
+
+                false
+            },
+            EvalState::Done => false,
+        }
+    }
+
+    /// Indices of the sprites copied to secondary OAM
+    pub fn sprites(&self) -> &[u8] {
+        &self.found[..self.count]
+    }
+
+    fn next_sprite(&mut self) {
+        self.n += 1;
+
+        self.state = if self.n == NUM_SPRITES {
+            EvalState::Done
+        }
+        else if self.count < SPRITES_PER_SCANLINE {
+            EvalState::Scan
+        }
+        else {
+            EvalState::Overflow
+        };
+    }
+}
+
+/// Check if a sprite Y coordinate is on the specified scanline
+pub fn in_range(y: u8, scanline: usize, height: u8) -> bool {
+    let row = scanline as i32 - y as i32;
+    row >= 0 && row < height as i32
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Run evaluation for all available cycles
+    fn evaluate(oam: &[u8], scanline: usize) -> (SpriteEvaluator, bool) {
+        let mut eval = SpriteEvaluator::default();
+        let mut overflow = false;
+
+        for _ in (65..=256).step_by(2) {
+            overflow |= eval.step(oam, scanline, 8);
+        }
+
+        (eval, overflow)
+    }
+
+    #[test]
+    fn evaluation_finds_first_eight() {
+        let mut oam = [0xFFu8; 256];
+        for n in [3, 10, 11, 20].iter() {
+            oam[n * 4] = 45;
+        }
+
+        let (eval, overflow) = evaluate(&oam, 50);
+        assert_eq!(eval.sprites(), &[3, 10, 11, 20]);
+        assert!(!overflow);
+
+        // Every sprite is in range
+        let (eval, overflow) = evaluate(&[50u8; 256], 50);
+        assert_eq!(eval.sprites(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+        assert!(overflow);
+    }
+
+    #[test]
+    fn overflow_ninth_sprite() {
+        let mut oam = [0xFFu8; 256];
+        for n in 0..9 {
+            oam[n * 4] = 50;
+        }
+
+        assert!(evaluate(&oam, 50).1);
+    }
+
+    #[test]
+    fn overflow_false_negative() {
+        let mut oam = [0xFFu8; 256];
+        for n in 0..8 {
+            oam[n * 4] = 50;
+        }
+        // Sprite 8 is not in range, so the Y coordinate of sprite 9 is never checked
+        oam[9 * 4] = 50;
+
+        assert!(!evaluate(&oam, 50).1);
+    }
+
+    #[test]
+    fn overflow_false_positive() {
+        let mut oam = [0xFFu8; 256];
+        for n in 0..8 {
+            oam[n * 4] = 50;
+        }
+        // The tile number of sprite 9 is treated as a Y coordinate
+        oam[9 * 4 + 1] = 48;
+
+        assert!(evaluate(&oam, 50).1);
+    }
+
+    #[test]
+    fn evaluation_starts_at_oam_addr() {
+        let mut oam = [0xFFu8; 256];
+        for n in [1, 5, 6].iter() {
+            oam[n * 4] = 50;
+        }
+
+        let mut eval = SpriteEvaluator::default();
+        eval.start(5 * 4);
+        for _ in (65..=256).step_by(2) {
+            eval.step(&oam, 50, 8);
+        }
+
+        assert_eq!(eval.sprites(), &[5, 6]);
+
+        // A misaligned address checks the tile byte of sprite 2 as its Y coordinate
+        oam[2 * 4 + 1] = 50;
+
+        let mut eval = SpriteEvaluator::default();
+        eval.start(2 * 4 + 1);
+        eval.step(&oam, 50, 8);
+
+        assert_eq!(eval.sprites(), &[2]);
+    }
+
     #[test]
     fn load_from_slice() {
         let data: [u8; 4] = [0, 2, 0, 3];
@@ -1 +1,4 @@
 pub mod sampler;
+pub mod palette;
+pub mod ntsc;
+pub mod wav;
@@ -0,0 +1,371 @@
+//
+// utils/ntsc.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use crate::ppu::{RawPixel, DISPLAY_WIDTH, DISPLAY_HEIGHT, PALETTE_ENTRIES};
+use crate::nes::PixelFormat;
+use super::palette::{PaletteGenerator, signal_level};
+
+/// Width of the filtered output. Every 3 input pixels produce 7 output pixels
+pub const NTSC_OUTPUT_WIDTH: usize = ((DISPLAY_WIDTH - 1) / 3 + 1) * 7;
+
+/// Number of phases in a color subcarrier cycle
+const PHASES: usize = 12;
+/// The PPU outputs 8 phases of the color subcarrier per pixel
+const SAMPLES_PER_PIXEL: usize = 8;
+const SAMPLES_PER_LINE: usize = DISPLAY_WIDTH * SAMPLES_PER_PIXEL;
+/// A scanline is 341 pixels, so the subcarrier phase shifts by 341 * 8 % 12 each line
+const LINE_PHASE_SHIFT: usize = 4;
+/// A frame is 262 scanlines, so the phase of the first scanline shifts by 262 * 341 * 8 % 12 each frame
+const FRAME_PHASE_SHIFT: usize = 4;
+/// A short frame skips one pixel, so the phase shifts by (262 * 341 - 1) * 8 % 12
+const SHORT_FRAME_PHASE_SHIFT: usize = 8;
+
+/// How the video signal reaches the display
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NtscSignal {
+    /// Luma and chroma share one signal. Produces dot crawl, color fringing and artifact colors
+    Composite,
+    /// Luma and chroma are separate. Colors bleed but do not interfere with luma
+    SVideo,
+    /// Each pixel is decoded on its own. Only the horizontal resampling softens the image
+    Rgb,
+}
+
+/// NTSC filter settings
+#[derive(Clone, Copy, Debug)]
+pub struct NtscSetup {
+    pub signal: NtscSignal,
+    pub luma_width: usize,         // Number of samples averaged for luma. Lower is sharper
+    pub chroma_width: usize,       // Number of samples averaged for chroma. Higher bleeds color further
+    pub picture: PaletteGenerator, // Hue, saturation, contrast, brightness and gamma
+}
+
+impl Default for NtscSetup {
+    fn default() -> Self {
+        NtscSetup::composite()
+    }
+}
+
+impl NtscSetup {
+    /// Composite video through the RCA jack
+    pub fn composite() -> Self {
+        NtscSetup::new(NtscSignal::Composite, 12, 24)
+    }
+
+    /// S-Video, as on a modded console
+    pub fn svideo() -> Self {
+        NtscSetup::new(NtscSignal::SVideo, 6, 24)
+    }
+
+    /// RGB, as on an RGB modded console
+    pub fn rgb() -> Self {
+        NtscSetup::new(NtscSignal::Rgb, 3, 3)
+    }
+
+    fn new(signal: NtscSignal, luma_width: usize, chroma_width: usize) -> Self {
+        NtscSetup {
+            signal,
+            luma_width,
+            chroma_width,
+            picture: PaletteGenerator::default(),
+        }
+    }
+}
+
+/// Simulates the NES's NTSC video output
+///
+/// Converts `PixelFormat::Indexed` frames into `NTSC_OUTPUT_WIDTH` x `DISPLAY_HEIGHT` frames. The color subcarrier
+/// phase changes each frame, just like on hardware, giving the characteristic dot crawl. With rendering enabled every
+/// other frame is one dot short, so the phase alternates between two values. Otherwise it cycles through three.
+///
+/// ```
+/// # use nescore::Nes;
+/// # use nescore::specs::{PixelFormat, DISPLAY_WIDTH, DISPLAY_HEIGHT};
+/// # use nescore::utils::ntsc::{NtscFilter, NtscSetup, NTSC_OUTPUT_WIDTH};
+/// let mut nes = Nes::default().pixel_format(PixelFormat::Indexed);
+/// let mut filter = NtscFilter::new(NtscSetup::composite()).pixel_format(PixelFormat::RGB8);
+///
+/// let mut frame = vec![0u8; DISPLAY_WIDTH * DISPLAY_HEIGHT * 2];
+/// let mut output = vec![0u8; NTSC_OUTPUT_WIDTH * DISPLAY_HEIGHT * 3];
+///
+/// nes.emulate_frame_into(&mut frame, DISPLAY_WIDTH * 2);
+/// filter.filter_frame(&frame, nes.short_frame(), &mut output, NTSC_OUTPUT_WIDTH * 3);
+/// ```
+pub struct NtscFilter {
+    setup: NtscSetup,
+    pixel_format: PixelFormat,
+    merge_fields: bool,
+    burst_phase: usize, // Phase of the first scanline of the previous frame
+
+    levels: Vec<[f32; PHASES]>, // Signal level of each color at each phase
+    luma: Vec<f32>,             // Luma of each color
+    chroma: Vec<(f32, f32)>,    // Demodulated I and Q of each color
+    cos: [f32; PHASES],         // Decoder subcarrier reference
+    sin: [f32; PHASES],
+
+    // Running sums of the decoded signals across a scanline
+    y_sum: Vec<f32>,
+    i_sum: Vec<f32>,
+    q_sum: Vec<f32>,
+    line: Vec<(f32, f32, f32)>,
+}
+
+impl NtscFilter {
+    pub fn new(setup: NtscSetup) -> Self {
+        let mut cos = [0f32; PHASES];
+        let mut sin = [0f32; PHASES];
+
+        for phase in 0..PHASES {
+            let angle = setup.picture.phase_angle(phase);
+            cos[phase] = angle.cos() as f32;
+            sin[phase] = angle.sin() as f32;
+        }
+
+        let levels: Vec<[f32; PHASES]> = (0..PALETTE_ENTRIES).map(|pixel| {
+            let mut levels = [0f32; PHASES];
+            for (phase, level) in levels.iter_mut().enumerate() {
+                *level = signal_level((pixel & 0x3F) as u8, (pixel >> 6) as u8, phase) as f32;
+            }
+            levels
+        }).collect();
+
+        let luma = levels.iter().map(|l| l.iter().sum::<f32>() / PHASES as f32).collect();
+        let chroma = levels.iter().map(|l| {
+            let i = l.iter().zip(cos.iter()).map(|(l, c)| l * c).sum::<f32>() / PHASES as f32;
+            let q = l.iter().zip(sin.iter()).map(|(l, s)| l * s).sum::<f32>() / PHASES as f32;
+            (i, q)
+        }).collect();
+
+        NtscFilter {
+            setup,
+            pixel_format: PixelFormat::RGB8,
+            merge_fields: false,
+            burst_phase: 0,
+
+            levels,
+            luma,
+            chroma,
+            cos,
+            sin,
+
+            y_sum: vec![0.0; SAMPLES_PER_LINE + 1],
+            i_sum: vec![0.0; SAMPLES_PER_LINE + 1],
+            q_sum: vec![0.0; SAMPLES_PER_LINE + 1],
+            line: vec![(0.0, 0.0, 0.0); NTSC_OUTPUT_WIDTH],
+        }
+    }
+
+    /// Set the output format
+    pub fn pixel_format(mut self, pixel_format: PixelFormat) -> Self {
+        assert!(pixel_format != PixelFormat::Indexed, "NTSC filter output must be an RGB format");
+        self.pixel_format = pixel_format;
+        self
+    }
+
+    /// Average consecutive frames to remove the flicker of dot crawl
+    pub fn merge_fields(mut self, merge_fields: bool) -> Self {
+        self.merge_fields = merge_fields;
+        self
+    }
+
+    /// Filter a `PixelFormat::Indexed` frame
+    ///
+    /// `short_frame` is whether the frame skipped a dot, as reported by `Nes::short_frame`. `stride` is the number of
+    /// bytes between the start of each output row.
+    pub fn filter_frame(&mut self, input: &[u8], short_frame: bool, output: &mut [u8], stride: usize) {
+        let bytes_per_pixel = self.pixel_format.num_bytes();
+        let row_size = NTSC_OUTPUT_WIDTH * bytes_per_pixel;
+
+        assert!(input.len() >= DISPLAY_WIDTH * DISPLAY_HEIGHT * 2, "Input must be an indexed frame");
+        assert!(stride >= row_size, "Stride must be at least {} bytes", row_size);
+        assert!(output.len() >= stride * (DISPLAY_HEIGHT - 1) + row_size, "Output is too small for a frame");
+
+        let frame_shift = if short_frame { SHORT_FRAME_PHASE_SHIFT } else { FRAME_PHASE_SHIFT };
+        self.burst_phase = (self.burst_phase + frame_shift) % PHASES;
+
+        let mut pixels = [0 as RawPixel; DISPLAY_WIDTH];
+
+        for y in 0..DISPLAY_HEIGHT {
+            for (x, pixel) in pixels.iter_mut().enumerate() {
+                let offset = (y * DISPLAY_WIDTH + x) * 2;
+                *pixel = u16::from_le_bytes([input[offset], input[offset + 1]]) & 0x1FF;
+            }
+
+            for yiq in self.line.iter_mut() {
+                *yiq = (0.0, 0.0, 0.0);
+            }
+
+            let phase = (self.burst_phase + y * LINE_PHASE_SHIFT) % PHASES;
+            self.decode_line(&pixels, phase);
+
+            if self.merge_fields {
+                // Blend with the phase of the previous frame
+                self.decode_line(&pixels, (phase + PHASES - frame_shift) % PHASES);
+                for yiq in self.line.iter_mut() {
+                    *yiq = (yiq.0 / 2.0, yiq.1 / 2.0, yiq.2 / 2.0);
+                }
+            }
+
+            for (x, &(luma, i, q)) in self.line.iter().enumerate() {
+                let rgb = self.setup.picture.yiq_to_rgb(luma as f64, i as f64, q as f64);
+                let offset = y * stride + x * bytes_per_pixel;
+                self.pixel_format.write_rgb(rgb, &mut output[offset..offset + bytes_per_pixel]);
+            }
+        }
+    }
+
+    /// Generate the scanline's signal starting at `phase` and add the decoded output to the line buffer
+    fn decode_line(&mut self, pixels: &[RawPixel], phase: usize) {
+        for sample in 0..SAMPLES_PER_LINE {
+            let pixel = pixels[sample / SAMPLES_PER_PIXEL] as usize;
+            let phase = (phase + sample) % PHASES;
+
+            let level = self.levels[pixel][phase];
+            let luma = self.luma[pixel];
+
+            let (y, i, q) = match self.setup.signal {
+                NtscSignal::Composite => (level, level * self.cos[phase], level * self.sin[phase]),
+                NtscSignal::SVideo => {
+                    let chroma = level - luma;
+                    (luma, chroma * self.cos[phase], chroma * self.sin[phase])
+                },
+                NtscSignal::Rgb => (luma, self.chroma[pixel].0, self.chroma[pixel].1),
+            };
+
+            self.y_sum[sample + 1] = self.y_sum[sample] + y;
+            self.i_sum[sample + 1] = self.i_sum[sample] + i;
+            self.q_sum[sample + 1] = self.q_sum[sample] + q;
+        }
+
+        for (x, yiq) in self.line.iter_mut().enumerate() {
+            // Center of the output pixel in samples
+            let center = (x * 2 + 1) * SAMPLES_PER_LINE / (NTSC_OUTPUT_WIDTH * 2);
+
+            yiq.0 += helpers::average(&self.y_sum, center, self.setup.luma_width);
+            yiq.1 += helpers::average(&self.i_sum, center, self.setup.chroma_width);
+            yiq.2 += helpers::average(&self.q_sum, center, self.setup.chroma_width);
+        }
+    }
+}
+
+mod helpers {
+    /// Average of `width` values centered on `center`, using running sums
+    pub fn average(sums: &[f32], center: usize, width: usize) -> f32 {
+        let samples = sums.len() - 1;
+        let width = width.clamp(1, samples);
+
+        let begin = center.saturating_sub(width / 2).min(samples - width);
+        let end = begin + width;
+
+        (sums[end] - sums[begin]) / width as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_frame(pixel: RawPixel) -> Vec<u8> {
+        (0..DISPLAY_WIDTH * DISPLAY_HEIGHT).flat_map(|_| pixel.to_le_bytes().to_vec()).collect()
+    }
+
+    fn filter(setup: NtscSetup, input: &[u8]) -> Vec<u8> {
+        let mut filter = NtscFilter::new(setup);
+        let mut output = vec![0u8; NTSC_OUTPUT_WIDTH * DISPLAY_HEIGHT * 3];
+        filter.filter_frame(input, false, &mut output, NTSC_OUTPUT_WIDTH * 3);
+
+        output
+    }
+
+    fn pixel(output: &[u8], x: usize, y: usize) -> (u8, u8, u8) {
+        let offset = (y * NTSC_OUTPUT_WIDTH + x) * 3;
+        (output[offset], output[offset + 1], output[offset + 2])
+    }
+
+    fn assert_close(a: (u8, u8, u8), b: (u8, u8, u8)) {
+        let diff = |a: u8, b: u8| (a as i32 - b as i32).abs();
+        assert!(diff(a.0, b.0) <= 2 && diff(a.1, b.1) <= 2 && diff(a.2, b.2) <= 2, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn output_width() {
+        assert_eq!(NTSC_OUTPUT_WIDTH, 602);
+    }
+
+    #[test]
+    fn solid_color_matches_palette() {
+        let palette = PaletteGenerator::default().generate();
+
+        for &setup in [NtscSetup::composite(), NtscSetup::svideo(), NtscSetup::rgb()].iter() {
+            for &color in [0x01u16, 0x16, 0x2A, 0x30, 0x0F].iter() {
+                let output = filter(setup, &solid_frame(color));
+                assert_close(pixel(&output, NTSC_OUTPUT_WIDTH / 2, 100), palette.color(color as u8, 0));
+            }
+        }
+    }
+
+    #[test]
+    fn emphasis() {
+        let palette = PaletteGenerator::default().generate();
+        let output = filter(NtscSetup::composite(), &solid_frame(0x30 | (0x01 << 6)));
+
+        assert_close(pixel(&output, 300, 10), palette.color(0x30, 0x01));
+    }
+
+    #[test]
+    fn dithering_blends() {
+        // Alternating black and white columns
+        let input: Vec<u8> = (0..DISPLAY_WIDTH * DISPLAY_HEIGHT)
+            .flat_map(|i| if i % 2 == 0 { vec![0x0F, 0x00] } else { vec![0x30, 0x00] })
+            .collect();
+
+        let (r, g, b) = pixel(&filter(NtscSetup::composite(), &input), 300, 50);
+        assert!(r > 0x20 && r < 0xF0);
+        assert!(g > 0x20 && g < 0xF0);
+        assert!(b > 0x20 && b < 0xF0);
+
+        // RGB keeps the columns mostly distinct
+        let output = filter(NtscSetup::rgb(), &input);
+        let (min, max) = (0..14).map(|x| pixel(&output, 300 + x, 50).0).fold((255, 0), |(min, max), r| (r.min(min), r.max(max)));
+        assert!(max - min > 0x80);
+    }
+
+    #[test]
+    fn phase_alternates_each_frame() {
+        // A sharp edge produces colored fringes that change with the subcarrier phase
+        let input: Vec<u8> = (0..DISPLAY_WIDTH * DISPLAY_HEIGHT)
+            .flat_map(|i| if (i % DISPLAY_WIDTH) < 128 { vec![0x0F, 0x00] } else { vec![0x30, 0x00] })
+            .collect();
+
+        let filter_frames = |short_frames: &[bool]| {
+            let mut filter = NtscFilter::new(NtscSetup::composite());
+
+            short_frames.iter().map(|&short_frame| {
+                let mut frame = vec![0u8; NTSC_OUTPUT_WIDTH * DISPLAY_HEIGHT * 3];
+                filter.filter_frame(&input, short_frame, &mut frame, NTSC_OUTPUT_WIDTH * 3);
+                frame
+            }).collect::<Vec<_>>()
+        };
+
+        // Rendering disabled. The pattern repeats every 3 frames
+        let frames = filter_frames(&[false, false, false, false]);
+        assert_ne!(frames[0], frames[1]);
+        assert_ne!(frames[1], frames[2]);
+        assert_eq!(frames[0], frames[3]);
+
+        // Rendering enabled. Every other frame is short and the pattern repeats every 2 frames
+        let frames = filter_frames(&[false, true, false, true]);
+        assert_ne!(frames[0], frames[1]);
+        assert_eq!(frames[0], frames[2]);
+        assert_eq!(frames[1], frames[3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn indexed_output_not_supported() {
+        let _ = NtscFilter::new(NtscSetup::default()).pixel_format(PixelFormat::Indexed);
+    }
+}
@@ -0,0 +1,206 @@
+//
+// utils/palette.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use crate::ppu::{Palette, PALETTE_COLORS, PALETTE_ENTRIES};
+
+use std::f64::consts::PI;
+
+// Composite signal voltages relative to sync
+// http://wiki.nesdev.com/w/index.php/NTSC_video
+const BLACK: f64 = 0.518;
+const WHITE: f64 = 1.962;
+const ATTENUATION: f64 = 0.746;
+const LEVELS_LOW: [f64; 4] = [0.350, 0.518, 0.962, 1.550];
+const LEVELS_HIGH: [f64; 4] = [1.094, 1.506, 1.962, 1.962];
+
+/// Gamma of the reference display the palette is converted to
+const DISPLAY_GAMMA: f64 = 2.2;
+
+/// Generate the 512 color NTSC palette from the PPU's composite video signal
+///
+/// Each color is modelled as the square wave the 2C02 outputs over the 12 phases of the color subcarrier, with the
+/// emphasis bits attenuating their part of the wave. The signal is then decoded to YIQ and converted to RGB.
+///
+/// ```
+/// # use nescore::utils::palette::PaletteGenerator;
+/// let palette = PaletteGenerator::default()
+///                 .hue(-5.0)
+///                 .saturation(1.2)
+///                 .generate();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PaletteGenerator {
+    hue: f64,        // Hue rotation in degrees
+    saturation: f64, // Chroma gain
+    contrast: f64,   // Signal gain
+    brightness: f64, // Luma offset
+    gamma: f64,      // Gamma of the decoded signal
+}
+
+impl Default for PaletteGenerator {
+    fn default() -> Self {
+        PaletteGenerator {
+            hue: 0.0,
+            saturation: 1.0,
+            contrast: 1.0,
+            brightness: 0.0,
+            gamma: 1.8,
+        }
+    }
+}
+
+impl PaletteGenerator {
+    /// Rotate the hue of all colors, in degrees
+    pub fn hue(mut self, hue: f64) -> Self {
+        self.hue = hue;
+        self
+    }
+
+    /// Scale the chroma signal. 0 is greyscale
+    pub fn saturation(mut self, saturation: f64) -> Self {
+        self.saturation = saturation;
+        self
+    }
+
+    /// Scale the decoded signal
+    pub fn contrast(mut self, contrast: f64) -> Self {
+        self.contrast = contrast;
+        self
+    }
+
+    /// Offset the luma signal. 0 leaves black unchanged
+    pub fn brightness(mut self, brightness: f64) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    /// Gamma of the simulated display
+    pub fn gamma(mut self, gamma: f64) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Compute the palette
+    pub fn generate(&self) -> Palette {
+        let mut colors = [(0, 0, 0); PALETTE_ENTRIES];
+
+        for (i, color) in colors.iter_mut().enumerate() {
+            *color = self.color((i % PALETTE_COLORS) as u8, (i / PALETTE_COLORS) as u8);
+        }
+
+        Palette::from_colors(colors)
+    }
+
+    /// Decode a single color index with the given emphasis bits
+    fn color(&self, color: u8, emphasis: u8) -> (u8, u8, u8) {
+        let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+
+        for phase in 0..12 {
+            let v = signal_level(color, emphasis, phase) / 12.0;
+            let angle = self.phase_angle(phase);
+
+            y += v;
+            i += v * angle.cos();
+            q += v * angle.sin();
+        }
+
+        self.yiq_to_rgb(y, i, q)
+    }
+
+    /// Angle of the decoder's color subcarrier reference at the given phase
+    pub(crate) fn phase_angle(&self, phase: usize) -> f64 {
+        self.hue.to_radians() + (phase as f64) * PI / 6.0
+    }
+
+    /// Apply the picture controls to a decoded signal and convert it to RGB
+    pub(crate) fn yiq_to_rgb(&self, y: f64, i: f64, q: f64) -> (u8, u8, u8) {
+        let y = y * self.contrast + self.brightness;
+        let i = i * self.contrast * self.saturation;
+        let q = q * self.contrast * self.saturation;
+
+        // YIQ to RGB using the FCC matrix
+        let r = y + 0.946882 * i + 0.623557 * q;
+        let g = y - 0.274788 * i - 0.635691 * q;
+        let b = y - 1.108545 * i + 1.709007 * q;
+
+        (self.channel(r), self.channel(g), self.channel(b))
+    }
+
+    fn channel(&self, value: f64) -> u8 {
+        let value = if value <= 0.0 { 0.0 } else { value.powf(DISPLAY_GAMMA / self.gamma) };
+        (255.95 * value).clamp(0.0, 255.0) as u8
+    }
+}
+
+/// Composite signal level of a color at one of the 12 subcarrier phases. 0 is black and 1 is white
+pub(crate) fn signal_level(color: u8, emphasis: u8, phase: usize) -> f64 {
+    let hue = (color & 0x0F) as usize;
+    // Colors $xE-$xF output black
+    let level = if hue > 0x0D { 1 } else { ((color >> 4) & 0x03) as usize };
+
+    // Color $x0 stays high and colors $xD-$xF stay low for the whole cycle
+    let low = if hue == 0x00 { LEVELS_HIGH[level] } else { LEVELS_LOW[level] };
+    let high = if hue < 0x0D { LEVELS_HIGH[level] } else { LEVELS_LOW[level] };
+
+    let in_phase = |hue: usize| (hue + phase + 8) % 12 < 6;
+
+    let mut signal = if in_phase(hue) { high } else { low };
+
+    // Each emphasis bit attenuates the signal during the phase of one color
+    let emphasized = (emphasis & 0x01 != 0 && in_phase(0x0C))
+                  || (emphasis & 0x02 != 0 && in_phase(0x04))
+                  || (emphasis & 0x04 != 0 && in_phase(0x08));
+    if emphasized {
+        signal *= ATTENUATION;
+    }
+
+    (signal - BLACK) / (WHITE - BLACK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_matches_builtin_ntsc() {
+        let generated = PaletteGenerator::default().generate().to_bytes();
+        let builtin = Palette::ntsc().to_bytes();
+
+        for (a, b) in generated.iter().zip(builtin.iter()) {
+            assert!((*a as i32 - *b as i32).abs() <= 1, "Generated {} Built-in {}", a, b);
+        }
+    }
+
+    #[test]
+    fn greyscale() {
+        let palette = PaletteGenerator::default().saturation(0.0).generate();
+
+        for color in 0..PALETTE_COLORS as u8 {
+            let (r, g, b) = palette.color(color, 0);
+            assert_eq!(r, g);
+            assert_eq!(g, b);
+        }
+    }
+
+    #[test]
+    fn brightness() {
+        let dark = PaletteGenerator::default().generate();
+        let bright = PaletteGenerator::default().brightness(0.1).generate();
+
+        assert_eq!(dark.color(0x0F, 0), (0, 0, 0));
+        assert!(bright.color(0x0F, 0).0 > 0);
+    }
+
+    #[test]
+    fn emphasis_darkens() {
+        let palette = PaletteGenerator::default().generate();
+
+        let (r, g, b) = palette.color(0x30, 0);
+        let (er, eg, eb) = palette.color(0x30, 0x01);
+
+        assert!(er <= r && eg < g && eb < b);
+    }
+}
@@ -4,44 +4,201 @@
 // @author Natesh Narain <nnaraindev@gmail.com>
 // @date Sep 19 2020
 //
-// TODO: Down Sampler trait?
 use crate::specs::Sample;
 
-/// Down Sampler
-/// Reduce APU generated output to host system playback rate
-#[derive(Debug)]
-pub struct DownSampler {
-    buffer: Vec<Sample>,
-    rate: usize,
+/// Zero crossings of the sinc kernel on each side of its center
+const ZERO_CROSSINGS: usize = 8;
+/// Kernel table entries per input sample
+const KERNEL_RESOLUTION: usize = 64;
+/// Cutoff frequency as a fraction of the output Nyquist frequency. Leaves room for the filter's transition band
+const CUTOFF: f64 = 0.9;
+
+/// Band-limited resampler
+///
+/// Converts APU output to the host playback rate using a windowed-sinc low pass filter, so content above the output
+/// Nyquist frequency is removed instead of aliasing. The ratio between the rates does not need to be an integer. Input
+/// is buffered between calls, so a stream can be processed one frame at a time.
+/// ```
+/// # use nescore::utils::sampler::Resampler;
+/// # use nescore::specs::APU_OUTPUT_RATE;
+/// let mut resampler = Resampler::new(APU_OUTPUT_RATE, 48000.0);
+///
+/// let mut output: Vec<f32> = Vec::new();
+/// resampler.process(&[0.0; 14916], &mut output);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    ratio: f64,        // Input samples per output sample
+    half_width: usize, // Half width of the kernel, in input samples
+    kernel: Vec<f32>,  // One side of the symmetric kernel, sampled KERNEL_RESOLUTION times per input sample
+    history: Vec<Sample>, // Input samples that still contribute to future output
+    position: f64,     // Position of the next output sample in `history`
 }
 
-impl DownSampler {
-    pub fn new(buffer: Vec<Sample>, input_rate: f32, output_rate: f32) -> Self {
-        DownSampler {
-            buffer,
-            rate: (input_rate / output_rate) as usize,
+impl Resampler {
+    pub fn new(input_rate: f32, output_rate: f32) -> Self {
+        let ratio = input_rate as f64 / output_rate as f64;
+        // Cutoff relative to the input Nyquist frequency
+        let cutoff = CUTOFF * (1.0 / ratio).min(1.0);
+        let half_width = (ZERO_CROSSINGS as f64 / cutoff).ceil() as usize;
+
+        let kernel = (0..=half_width * KERNEL_RESOLUTION).map(|i| {
+            let t = i as f64 / KERNEL_RESOLUTION as f64;
+            (cutoff * helpers::sinc(cutoff * t) * helpers::blackman(t / half_width as f64)) as f32
+        }).collect();
+
+        Resampler {
+            ratio,
+            half_width,
+            kernel,
+            // Start with silence so the first output samples have a full window
+            history: vec![0.0; half_width],
+            position: half_width as f64,
         }
     }
+
+    /// Input samples consumed per output sample
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    /// Resample a block of input, appending to `output`
+    pub fn process(&mut self, input: &[Sample], output: &mut Vec<f32>) {
+        self.history.extend_from_slice(input);
+
+        while self.position + (self.half_width as f64) < self.history.len() as f64 {
+            output.push(self.sample_at(self.position));
+            self.position += self.ratio;
+        }
+
+        // Discard input that no longer falls within the kernel
+        let consumed = (self.position as usize).saturating_sub(self.half_width);
+        self.history.drain(..consumed);
+        self.position -= consumed as f64;
+    }
+
+    /// Resample a block of input to signed 16-bit samples, appending to `output`
+    pub fn process_i16(&mut self, input: &[Sample], output: &mut Vec<i16>) {
+        let mut samples = Vec::with_capacity((input.len() as f64 / self.ratio) as usize + 1);
+        self.process(input, &mut samples);
+
+        output.extend(samples.into_iter().map(helpers::to_i16));
+    }
+
+    /// Clear buffered input
+    pub fn reset(&mut self) {
+        self.history = vec![0.0; self.half_width];
+        self.position = self.half_width as f64;
+    }
+
+    /// Apply the kernel centered at a fractional position in the history
+    fn sample_at(&self, position: f64) -> f32 {
+        let start = position as usize + 1 - self.half_width;
+        let end = position as usize + self.half_width;
+
+        self.history[start..=end].iter().enumerate().map(|(i, &sample)| {
+            let t = ((start + i) as f64 - position).abs();
+            let idx = ((t * KERNEL_RESOLUTION as f64) as usize).min(self.kernel.len() - 1);
+
+            sample * self.kernel[idx]
+        }).sum()
+    }
 }
 
-impl IntoIterator for DownSampler {
-    type Item = Sample;
-    type IntoIter = std::iter::StepBy<std::vec::IntoIter<Sample>>;
+mod helpers {
+    use std::f64::consts::PI;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.buffer.into_iter().step_by(self.rate)
+    /// Normalized sinc function
+    pub fn sinc(x: f64) -> f64 {
+        if x == 0.0 { 1.0 } else { (PI * x).sin() / (PI * x) }
+    }
+
+    /// Blackman window, where `x` is the distance from the center as a fraction of the half width
+    pub fn blackman(x: f64) -> f64 {
+        if x >= 1.0 { 0.0 } else { 0.42 + 0.5 * (PI * x).cos() + 0.08 * (2.0 * PI * x).cos() }
+    }
+
+    pub fn to_i16(sample: f32) -> i16 {
+        (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f32::consts::PI;
+
+    const INPUT_RATE: f32 = 1_789_773.0;
+    const OUTPUT_RATE: f32 = 44_100.0;
+
+    fn sine(freq: f32, len: usize) -> Vec<Sample> {
+        (0..len).map(|n| (2.0 * PI * freq * n as f32 / INPUT_RATE).sin()).collect()
+    }
+
+    /// Peak amplitude after the filter has settled
+    fn peak(samples: &[f32]) -> f32 {
+        samples[samples.len() / 2..].iter().fold(0.0, |peak, s| s.abs().max(peak))
+    }
+
+    #[test]
+    fn fractional_ratio() {
+        let mut resampler = Resampler::new(INPUT_RATE, OUTPUT_RATE);
+        let mut output = Vec::new();
+
+        // One second of input, a frame at a time
+        for _ in 0..60 {
+            resampler.process(&[0.0; 29830], &mut output);
+        }
+
+        // Output lags the input by half the kernel
+        let latency = (resampler.half_width as f64 / resampler.ratio()).ceil() as usize;
+        let expected = (29830.0 * 60.0 / resampler.ratio()) as usize;
+        assert!(output.len() <= expected && output.len() + latency + 1 >= expected, "Output: {}", output.len());
+    }
+
+    #[test]
+    fn streaming_matches_single_block() {
+        let input = sine(1000.0, 20000);
+
+        let mut output = Vec::new();
+        Resampler::new(INPUT_RATE, OUTPUT_RATE).process(&input, &mut output);
+
+        let mut resampler = Resampler::new(INPUT_RATE, OUTPUT_RATE);
+        let mut streamed = Vec::new();
+        for block in input.chunks(1234) {
+            resampler.process(block, &mut streamed);
+        }
+
+        assert_eq!(output, streamed);
+    }
+
+    #[test]
+    fn unity_dc_gain() {
+        let mut output = Vec::new();
+        Resampler::new(INPUT_RATE, OUTPUT_RATE).process(&[0.5; 20000], &mut output);
+
+        assert!((output.last().unwrap() - 0.5).abs() < 0.005);
+    }
+
+    #[test]
+    fn removes_content_above_nyquist() {
+        let resample = |freq: f32| {
+            let mut output = Vec::new();
+            Resampler::new(INPUT_RATE, OUTPUT_RATE).process(&sine(freq, 40000), &mut output);
+            peak(&output)
+        };
+
+        assert!((resample(1000.0) - 1.0).abs() < 0.02);
+        // A decimator would alias this to 8.1 kHz
+        assert!(resample(36200.0) < 0.01);
+    }
 
     #[test]
-    fn downsample() {
-        let buffer: Vec<Sample> = (0..100).map(|n| n as f32).collect();
-        let count = DownSampler::new(buffer, 100.0, 10.0).into_iter().count();
+    fn i16_output() {
+        let mut resampler = Resampler::new(INPUT_RATE, OUTPUT_RATE);
+        let mut output = Vec::new();
+        resampler.process_i16(&[1.5; 20000], &mut output);
 
-        assert_eq!(count, 10);
+        assert_eq!(*output.last().unwrap(), i16::MAX);
     }
 }
@@ -0,0 +1,159 @@
+//
+// utils/wav.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+use crate::specs::{Sample, APU_OUTPUT_RATE};
+use super::sampler::Resampler;
+
+use std::fs::File;
+use std::io::{self, Write, Seek, SeekFrom, BufWriter};
+
+const HEADER_SIZE: u32 = 44;
+const BITS_PER_SAMPLE: u16 = 16;
+
+/// Writes APU output to a mono, 16-bit PCM WAV file
+///
+/// Samples are resampled from the APU output rate to the file's sample rate. The same input always produces the same
+/// file, so renders can be compared byte for byte.
+/// ```
+/// # use nescore::utils::wav::WavWriter;
+/// # use std::io::Cursor;
+/// let mut wav = WavWriter::new(Cursor::new(Vec::new()), 44_100).unwrap();
+/// wav.write(&[0.0; 14916]).unwrap();
+/// let bytes = wav.finish().unwrap().into_inner();
+/// ```
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    sample_rate: u32,
+    resampler: Resampler,
+    buffer: Vec<i16>,
+    num_samples: u32,
+}
+
+impl WavWriter<BufWriter<File>> {
+    /// Create a WAV file at the given path
+    pub fn create(path: &str, sample_rate: u32) -> io::Result<Self> {
+        WavWriter::new(BufWriter::new(File::create(path)?), sample_rate)
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(writer: W, sample_rate: u32) -> io::Result<Self> {
+        let mut wav = WavWriter {
+            writer,
+            sample_rate,
+            resampler: Resampler::new(APU_OUTPUT_RATE, sample_rate as f32),
+            buffer: Vec::new(),
+            num_samples: 0,
+        };
+
+        // The header is rewritten with the final sizes when finished
+        wav.write_header()?;
+
+        Ok(wav)
+    }
+
+    /// Resample and write raw APU output
+    pub fn write(&mut self, samples: &[Sample]) -> io::Result<()> {
+        self.buffer.clear();
+        self.resampler.process_i16(samples, &mut self.buffer);
+
+        let buffer = std::mem::take(&mut self.buffer);
+        let result = self.write_pcm(&buffer);
+        self.buffer = buffer;
+
+        result
+    }
+
+    /// Write samples that are already at the file's sample rate
+    pub fn write_pcm(&mut self, samples: &[i16]) -> io::Result<()> {
+        for sample in samples {
+            self.writer.write_all(&sample.to_le_bytes())?;
+        }
+
+        self.num_samples += samples.len() as u32;
+
+        Ok(())
+    }
+
+    /// Number of samples written to the file
+    pub fn num_samples(&self) -> u32 {
+        self.num_samples
+    }
+
+    /// Complete the header and return the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.write_header()?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let block_align = BITS_PER_SAMPLE / 8;
+        let data_size = self.num_samples * block_align as u32;
+
+        let w = &mut self.writer;
+
+        w.write_all(b"RIFF")?;
+        w.write_all(&(HEADER_SIZE - 8 + data_size).to_le_bytes())?;
+        w.write_all(b"WAVE")?;
+
+        w.write_all(b"fmt ")?;
+        w.write_all(&16u32.to_le_bytes())?;
+        w.write_all(&1u16.to_le_bytes())?; // PCM
+        w.write_all(&1u16.to_le_bytes())?; // Mono
+        w.write_all(&self.sample_rate.to_le_bytes())?;
+        w.write_all(&(self.sample_rate * block_align as u32).to_le_bytes())?;
+        w.write_all(&block_align.to_le_bytes())?;
+        w.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+        w.write_all(b"data")?;
+        w.write_all(&data_size.to_le_bytes())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn header() {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new()), 48_000).unwrap();
+        wav.write_pcm(&[0x1234, -1]).unwrap();
+        let bytes = wav.finish().unwrap().into_inner();
+
+        assert_eq!(bytes.len(), 48);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[4..8], &40u32.to_le_bytes());
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(&bytes[24..28], &48_000u32.to_le_bytes());
+        assert_eq!(&bytes[28..32], &96_000u32.to_le_bytes());
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(&bytes[40..44], &4u32.to_le_bytes());
+        assert_eq!(&bytes[44..], &[0x34, 0x12, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn resampled_output() {
+        let mut wav = WavWriter::new(Cursor::new(Vec::new()), 44_100).unwrap();
+
+        // One second of APU output, a frame at a time
+        for _ in 0..60 {
+            wav.write(&[0.5; 14916]).unwrap();
+        }
+
+        assert!((44_000..=44_100).contains(&wav.num_samples()), "Samples: {}", wav.num_samples());
+
+        let bytes = wav.finish().unwrap().into_inner();
+        let last = i16::from_le_bytes([bytes[bytes.len() - 2], bytes[bytes.len() - 1]]);
+        assert!((last as f32 / i16::MAX as f32 - 0.5).abs() < 0.01);
+    }
+}
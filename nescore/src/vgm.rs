@@ -0,0 +1,370 @@
+//
+// vgm.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+
+//! Recording of sound register writes and export to the VGM format
+//! https://vgmrips.net/wiki/VGM_Specification
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+const CPU_CLOCK: u64 = 1_789_773;
+const VGM_SAMPLE_RATE: u64 = 44_100;
+
+const VGM_VERSION: u32 = 0x171;
+const VGM_HEADER_SIZE: usize = 0x100;
+
+const NES_APU_CLOCK: u32 = 1_789_772;
+/// VRC7 is logged as a YM2413. Bit 31 of the clock selects the VRC7 variant
+const YM2413_CLOCK: u32 = 3_579_545 | 0x8000_0000;
+/// Sunsoft 5B is logged as a YM2149
+const AY8910_CLOCK: u32 = 1_789_773;
+const AY8910_TYPE_YM2149: u8 = 0x10;
+
+/// Number of addresses the DMC can read samples from ($8000-$FFFF)
+const DPCM_MEMORY_SIZE: usize = 0x8000;
+
+// Commands
+const CMD_YM2413_WRITE: u8 = 0x51;
+const CMD_WAIT: u8 = 0x61;
+const CMD_WAIT_60HZ: u8 = 0x62;
+const CMD_WAIT_50HZ: u8 = 0x63;
+const CMD_END: u8 = 0x66;
+const CMD_DATA_BLOCK: u8 = 0x67;
+const CMD_AY8910_WRITE: u8 = 0xA0;
+const CMD_NES_APU_WRITE: u8 = 0xB4;
+const CMD_WAIT_SHORT: u8 = 0x70;
+
+const DATA_BLOCK_NES_APU_RAM: u8 = 0xC2;
+
+/// Log shared with the CPU bus. Writes are recorded while it is set
+pub(crate) type AudioLogRef = Rc<RefCell<Option<AudioLog>>>;
+
+/// A CPU write to a sound register. Cartridge sound chip registers use the chip's canonical address
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegisterWrite {
+    /// CPU cycle, relative to the start of the recording
+    pub cycle: u64,
+    pub addr: u16,
+    pub value: u8,
+}
+
+/// Memory the DMC will read a sample from, captured when the DMC is started
+#[derive(Debug, Clone, PartialEq)]
+pub struct DpcmBlock {
+    pub cycle: u64,
+    pub addr: u16,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogEntry {
+    Write(RegisterWrite),
+    Dpcm(DpcmBlock),
+}
+
+/// A log of writes to the APU ($4000-$4017) and cartridge sound chip registers
+/// ```no_run
+/// # use nescore::{Nes, Cartridge};
+/// # let cart = Cartridge::from_path("/path/to/rom").unwrap();
+/// let mut nes = Nes::from(cart);
+///
+/// nes.start_audio_log();
+/// for _ in 0..600 {
+///     nes.emulate_frame();
+/// }
+/// let vgm = nes.stop_audio_log().unwrap().to_vgm();
+/// ```
+pub struct AudioLog {
+    entries: Vec<LogEntry>,
+    cycles: u64,
+
+    // DMC sample address and length registers
+    dmc_addr: u8,
+    dmc_len: u8,
+    // Sample memory already captured. Blocks are only logged when the memory differs
+    dpcm_memory: Vec<Option<u8>>,
+}
+
+impl Default for AudioLog {
+    fn default() -> Self {
+        AudioLog {
+            entries: vec![],
+            cycles: 0,
+
+            dmc_addr: 0,
+            dmc_len: 0,
+            dpcm_memory: vec![None; DPCM_MEMORY_SIZE],
+        }
+    }
+}
+
+impl AudioLog {
+    /// Logged writes and sample memory, in order
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    /// Length of the recording in CPU cycles
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub(crate) fn cpu_tick(&mut self) {
+        self.cycles += 1;
+    }
+
+    pub(crate) fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x4012 => self.dmc_addr = value,
+            0x4013 => self.dmc_len = value,
+            _ => {},
+        }
+
+        self.entries.push(LogEntry::Write(RegisterWrite { cycle: self.cycles, addr, value }));
+    }
+
+    /// Capture the memory the DMC will play from. `read` reads from the CPU address space
+    pub(crate) fn capture_dpcm<F: Fn(u16) -> u8>(&mut self, read: F) {
+        let start = 0xC000 + (self.dmc_addr as u16) * 64;
+        let len = (self.dmc_len as usize) * 16 + 1;
+
+        // Addresses wrap from $FFFF to $8000
+        let mut addrs = (0..len).map(|i| 0x8000 | (start as usize + i) as u16 & 0x7FFF).peekable();
+
+        while let Some(first) = addrs.next() {
+            let mut data = vec![read(first)];
+            let mut last = first;
+
+            while let Some(addr) = addrs.next_if(|&addr| addr == last.wrapping_add(1)) {
+                data.push(read(addr));
+                last = addr;
+            }
+
+            let offset = (first & 0x7FFF) as usize;
+            let known = &mut self.dpcm_memory[offset..offset + data.len()];
+
+            if known.iter().zip(data.iter()).any(|(known, byte)| *known != Some(*byte)) {
+                for (known, byte) in known.iter_mut().zip(data.iter()) {
+                    *known = Some(*byte);
+                }
+
+                self.entries.push(LogEntry::Dpcm(DpcmBlock { cycle: self.cycles, addr: first, data }));
+            }
+        }
+    }
+
+    /// Export the log as a VGM 1.71 file
+    ///
+    /// APU writes use the NES APU chip and sample memory is written as NES APU RAM data blocks. VRC7 writes are
+    /// exported as YM2413 (VRC7 mode) and Sunsoft 5B writes as YM2149. VRC6 and MMC5 sound have no VGM equivalent and
+    /// are left out.
+    pub fn to_vgm(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![];
+        let mut samples = 0u64;
+
+        let mut uses_vrc7 = false;
+        let mut uses_5b = false;
+        let mut vrc7_register = 0u8;
+        let mut sunsoft_register = 0u8;
+
+        for entry in self.entries.iter() {
+            let cycle = match entry {
+                LogEntry::Write(write) => write.cycle,
+                LogEntry::Dpcm(block) => block.cycle,
+            };
+
+            helpers::wait(&mut data, &mut samples, helpers::cycle_to_sample(cycle));
+
+            match entry {
+                LogEntry::Dpcm(block) => {
+                    data.push(CMD_DATA_BLOCK);
+                    data.push(CMD_END);
+                    data.push(DATA_BLOCK_NES_APU_RAM);
+                    data.extend_from_slice(&(block.data.len() as u32 + 2).to_le_bytes());
+                    data.extend_from_slice(&block.addr.to_le_bytes());
+                    data.extend_from_slice(&block.data);
+                },
+                // OAM DMA and the controller strobe are not sound registers
+                LogEntry::Write(RegisterWrite { addr: 0x4014, .. }) | LogEntry::Write(RegisterWrite { addr: 0x4016, .. }) => {},
+                LogEntry::Write(RegisterWrite { addr: a @ 0x4000..=0x4017, value, .. }) => {
+                    data.extend_from_slice(&[CMD_NES_APU_WRITE, (a - 0x4000) as u8, *value]);
+                },
+                LogEntry::Write(RegisterWrite { addr: 0x9010, value, .. }) => vrc7_register = *value,
+                LogEntry::Write(RegisterWrite { addr: 0x9030, value, .. }) => {
+                    data.extend_from_slice(&[CMD_YM2413_WRITE, vrc7_register, *value]);
+                    uses_vrc7 = true;
+                },
+                LogEntry::Write(RegisterWrite { addr: 0xC000, value, .. }) => sunsoft_register = *value & 0x0F,
+                LogEntry::Write(RegisterWrite { addr: 0xE000, value, .. }) => {
+                    data.extend_from_slice(&[CMD_AY8910_WRITE, sunsoft_register, *value]);
+                    uses_5b = true;
+                },
+                LogEntry::Write(_) => {},
+            }
+        }
+
+        helpers::wait(&mut data, &mut samples, helpers::cycle_to_sample(self.cycles));
+        data.push(CMD_END);
+
+        let mut header = vec![0u8; VGM_HEADER_SIZE];
+        let mut set_u32 = |offset: usize, value: u32| header[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+
+        set_u32(0x00, u32::from_le_bytes(*b"Vgm "));
+        set_u32(0x04, (VGM_HEADER_SIZE + data.len() - 0x04) as u32);
+        set_u32(0x08, VGM_VERSION);
+        set_u32(0x10, if uses_vrc7 { YM2413_CLOCK } else { 0 });
+        set_u32(0x18, samples as u32);
+        set_u32(0x24, 60);
+        // Offsets are relative to their position in the header
+        set_u32(0x34, (VGM_HEADER_SIZE - 0x34) as u32);
+        set_u32(0x74, if uses_5b { AY8910_CLOCK } else { 0 });
+        set_u32(0x84, NES_APU_CLOCK);
+
+        if uses_5b {
+            header[0x78] = AY8910_TYPE_YM2149;
+            header[0x79] = 0x01; // Legacy output
+        }
+
+        header.extend(data);
+
+        header
+    }
+}
+
+mod helpers {
+    use super::*;
+
+    /// 44.1 kHz sample at the given CPU cycle
+    pub fn cycle_to_sample(cycle: u64) -> u64 {
+        cycle * VGM_SAMPLE_RATE / CPU_CLOCK
+    }
+
+    /// Emit wait commands to advance from `samples` to `target`
+    pub fn wait(data: &mut Vec<u8>, samples: &mut u64, target: u64) {
+        while *samples < target {
+            let remaining = target - *samples;
+
+            let waited = match remaining {
+                1..=16 => {
+                    data.push(CMD_WAIT_SHORT + (remaining - 1) as u8);
+                    remaining
+                },
+                735 => {
+                    data.push(CMD_WAIT_60HZ);
+                    735
+                },
+                882 => {
+                    data.push(CMD_WAIT_50HZ);
+                    882
+                },
+                _ => {
+                    let n = remaining.min(0xFFFF);
+                    data.push(CMD_WAIT);
+                    data.extend_from_slice(&(n as u16).to_le_bytes());
+                    n
+                },
+            };
+
+            *samples += waited;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u32(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+    }
+
+    #[test]
+    fn header() {
+        let mut log = AudioLog::default();
+        for _ in 0..CPU_CLOCK {
+            log.cpu_tick();
+        }
+
+        let vgm = log.to_vgm();
+
+        assert_eq!(&vgm[0..4], b"Vgm ");
+        assert_eq!(read_u32(&vgm, 0x04) as usize, vgm.len() - 4);
+        assert_eq!(read_u32(&vgm, 0x08), 0x171);
+        assert_eq!(read_u32(&vgm, 0x18), 44_100);
+        assert_eq!(read_u32(&vgm, 0x34), 0xCC);
+        assert_eq!(read_u32(&vgm, 0x84), NES_APU_CLOCK);
+        assert_eq!(read_u32(&vgm, 0x10), 0);
+
+        // One second of waits, then the end of the data
+        assert_eq!(&vgm[0x100..], &[0x61, 0x44, 0xAC, 0x66]);
+    }
+
+    #[test]
+    fn apu_writes() {
+        let mut log = AudioLog::default();
+
+        log.write(0x4000, 0xBF);
+        for _ in 0..406 {
+            log.cpu_tick();
+        }
+        log.write(0x4016, 0x01);
+        log.write(0x4015, 0x01);
+
+        let vgm = log.to_vgm();
+
+        // 406 cycles is 10 samples
+        assert_eq!(&vgm[0x100..], &[0xB4, 0x00, 0xBF, 0x79, 0xB4, 0x15, 0x01, 0x66]);
+    }
+
+    #[test]
+    fn expansion_writes() {
+        let mut log = AudioLog::default();
+
+        log.write(0x9010, 0x30);
+        log.write(0x9030, 0x1F);
+        log.write(0xC000, 0x08);
+        log.write(0xE000, 0x0F);
+        log.write(0x9000, 0x8F);
+
+        let vgm = log.to_vgm();
+
+        assert_eq!(read_u32(&vgm, 0x10), YM2413_CLOCK);
+        assert_eq!(read_u32(&vgm, 0x74), AY8910_CLOCK);
+        assert_eq!(vgm[0x78], AY8910_TYPE_YM2149);
+        // The VRC6 write is dropped
+        assert_eq!(&vgm[0x100..], &[0x51, 0x30, 0x1F, 0xA0, 0x08, 0x0F, 0x66]);
+    }
+
+    #[test]
+    fn dpcm_blocks() {
+        let mut log = AudioLog::default();
+
+        // 65 bytes from $FFC0, wrapping to $8000
+        log.write(0x4012, 0xFF);
+        log.write(0x4013, 0x04);
+        log.capture_dpcm(|addr| addr as u8);
+
+        let blocks: Vec<&DpcmBlock> = log.entries().iter().filter_map(|entry| match entry {
+            LogEntry::Dpcm(block) => Some(block),
+            _ => None,
+        }).collect();
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!((blocks[0].addr, blocks[0].data.len()), (0xFFC0, 64));
+        assert_eq!((blocks[1].addr, blocks[1].data.clone()), (0x8000, vec![0x00]));
+        assert_eq!(blocks[0].data[0], 0xC0);
+
+        // Unchanged memory is not captured again
+        let entries = log.entries().len();
+        log.capture_dpcm(|addr| addr as u8);
+        assert_eq!(log.entries().len(), entries);
+
+        let vgm = log.to_vgm();
+        assert_eq!(&vgm[0x106..0x10D], &[0x67, 0x66, 0xC2, 0x42, 0x00, 0x00, 0x00]);
+        assert_eq!(&vgm[0x10D..0x10F], &[0xC0, 0xFF]);
+    }
+}
@@ -8,12 +8,19 @@ pub fn run_test(nes: &mut Nes, fail_msg: &str) {
     run_test_with_ignore(nes, fail_msg, vec![]);
 }
 
+/// Frames to wait for a test ROM to report a result
+const MAX_FRAMES: usize = 60 * 60 * 2;
+
 pub fn run_test_with_ignore(nes: &mut Nes, fail_msg: &str, ignore: Vec<String>) {
     let mut result_text = String::from("");
+    let mut frames = 0;
 
     while !should_exit(&result_text) {
+        assert!(frames < MAX_FRAMES, "{}: No result after {} frames:\n{}", fail_msg, MAX_FRAMES, result_text);
+
         nes.emulate_frame();
         result_text = read_result_text(&nes);
+        frames += 1;
     }
 
     // Run another few times to let the test ROM finish writing text to the screen
@@ -0,0 +1,13 @@
+//
+// ppu_open_bus.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+mod common;
+
+#[test]
+fn ppu_open_bus() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_open_bus/ppu_open_bus.nes");
+    common::run_test(&mut nes, "PPU open bus test failed with");
+}
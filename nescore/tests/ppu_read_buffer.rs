@@ -0,0 +1,13 @@
+//
+// ppu_read_buffer.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+mod common;
+
+#[test]
+fn ppu_read_buffer() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_read_buffer/test_ppu_read_buffer.nes");
+    common::run_test(&mut nes, "PPU read buffer test failed with");
+}
@@ -0,0 +1,109 @@
+//
+// vbl_nmi.rs
+//
+// @author Natesh Narain <nnaraindev@gmail.com>
+// @date Oct 18 2026
+//
+mod common;
+
+#[test]
+fn ppu_vbl_nmi_vbl_basics() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/01-vbl_basics.nes");
+    common::run_test(&mut nes, "VBL basics test failed with");
+}
+
+#[test]
+fn ppu_vbl_nmi_vbl_set_time() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/02-vbl_set_time.nes");
+    common::run_test(&mut nes, "VBL set time test failed with");
+}
+
+#[test]
+fn ppu_vbl_nmi_vbl_clear_time() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/03-vbl_clear_time.nes");
+    common::run_test(&mut nes, "VBL clear time test failed with");
+}
+
+#[test]
+fn ppu_vbl_nmi_nmi_control() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/04-nmi_control.nes");
+    common::run_test(&mut nes, "NMI control test failed with");
+}
+
+#[test]
+fn ppu_vbl_nmi_nmi_timing() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/05-nmi_timing.nes");
+    common::run_test(&mut nes, "NMI timing test failed with");
+}
+
+#[test]
+fn ppu_vbl_nmi_suppression() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/06-suppression.nes");
+    common::run_test(&mut nes, "Suppression test failed with");
+}
+
+#[test]
+fn ppu_vbl_nmi_nmi_on_timing() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/07-nmi_on_timing.nes");
+    common::run_test(&mut nes, "NMI on timing test failed with");
+}
+
+#[test]
+fn ppu_vbl_nmi_nmi_off_timing() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/08-nmi_off_timing.nes");
+    common::run_test(&mut nes, "NMI off timing test failed with");
+}
+
+#[test]
+fn ppu_vbl_nmi_even_odd_frames() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/09-even_odd_frames.nes");
+    common::run_test(&mut nes, "Even/odd frames test failed with");
+}
+
+#[test]
+fn ppu_vbl_nmi_even_odd_timing() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/ppu_vbl_nmi/rom_singles/10-even_odd_timing.nes");
+    common::run_test(&mut nes, "Even/odd timing test failed with");
+}
+
+#[test]
+fn vbl_nmi_timing_frame_basics() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/vbl_nmi_timing/1.frame_basics.nes");
+    common::run_test(&mut nes, "Frame basics test failed with");
+}
+
+#[test]
+fn vbl_nmi_timing_vbl_timing() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/vbl_nmi_timing/2.vbl_timing.nes");
+    common::run_test(&mut nes, "VBL timing test failed with");
+}
+
+#[test]
+fn vbl_nmi_timing_even_odd_frames() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/vbl_nmi_timing/3.even_odd_frames.nes");
+    common::run_test(&mut nes, "Even/odd frames test failed with");
+}
+
+#[test]
+fn vbl_nmi_timing_vbl_clear_timing() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/vbl_nmi_timing/4.vbl_clear_timing.nes");
+    common::run_test(&mut nes, "VBL clear timing test failed with");
+}
+
+#[test]
+fn vbl_nmi_timing_nmi_suppression() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/vbl_nmi_timing/5.nmi_suppression.nes");
+    common::run_test(&mut nes, "NMI suppression test failed with");
+}
+
+#[test]
+fn vbl_nmi_timing_nmi_disable() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/vbl_nmi_timing/6.nmi_disable.nes");
+    common::run_test(&mut nes, "NMI disable test failed with");
+}
+
+#[test]
+fn vbl_nmi_timing_nmi_timing() {
+    let mut nes = common::init_nes("tests/roms/nes-test-roms/vbl_nmi_timing/7.nmi_timing.nes");
+    common::run_test(&mut nes, "NMI timing test failed with");
+}